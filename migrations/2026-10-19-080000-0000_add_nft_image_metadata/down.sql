@@ -0,0 +1,5 @@
+ALTER TABLE nft
+    DROP COLUMN width,
+    DROP COLUMN height,
+    DROP COLUMN dominant_colors,
+    DROP COLUMN taken_at;
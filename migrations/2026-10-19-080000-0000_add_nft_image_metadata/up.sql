@@ -0,0 +1,6 @@
+-- Metadata yang diekstrak dari gambar saat upload (EXIF sudah dibuang dari file)
+ALTER TABLE nft
+    ADD COLUMN width INTEGER,
+    ADD COLUMN height INTEGER,
+    ADD COLUMN dominant_colors TEXT[] NOT NULL DEFAULT '{}',
+    ADD COLUMN taken_at TIMESTAMP;
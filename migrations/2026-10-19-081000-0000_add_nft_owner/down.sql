@@ -0,0 +1,3 @@
+DROP INDEX IF EXISTS idx_nft_owner_id;
+ALTER TABLE nft DROP COLUMN owner_id;
+ALTER TABLE users DROP COLUMN is_admin;
@@ -0,0 +1,14 @@
+-- Admin boleh mengubah/menghapus item galeri milik siapa pun
+ALTER TABLE users ADD COLUMN is_admin BOOLEAN NOT NULL DEFAULT FALSE;
+UPDATE users SET is_admin = TRUE WHERE username = 'admin';
+
+-- Pemilik item galeri, diisi dari subject JWT saat upload.
+-- Item lama tidak punya pemilik dan hanya bisa diubah oleh admin.
+ALTER TABLE nft
+    ADD COLUMN owner_id UUID,
+    ADD CONSTRAINT fk_nft_owner
+        FOREIGN KEY(owner_id)
+        REFERENCES users(id)
+        ON DELETE SET NULL;
+
+CREATE INDEX idx_nft_owner_id ON nft(owner_id);
@@ -0,0 +1,3 @@
+ALTER TABLE nft
+    DROP COLUMN attributes,
+    DROP COLUMN cid;
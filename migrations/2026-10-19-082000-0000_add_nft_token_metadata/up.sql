@@ -0,0 +1,4 @@
+-- Trait ERC-721 ([{"trait_type": ..., "value": ...}]) dan CIDv1 dari file yang disimpan
+ALTER TABLE nft
+    ADD COLUMN attributes JSONB NOT NULL DEFAULT '[]',
+    ADD COLUMN cid VARCHAR;
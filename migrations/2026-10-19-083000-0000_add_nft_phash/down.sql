@@ -0,0 +1 @@
+ALTER TABLE nft DROP COLUMN phash;
@@ -0,0 +1,2 @@
+-- dHash 64-bit dari gambar, dipakai untuk deteksi duplikat / near-duplicate
+ALTER TABLE nft ADD COLUMN phash BIGINT;
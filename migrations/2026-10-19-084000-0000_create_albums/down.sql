@@ -0,0 +1,3 @@
+DROP TABLE IF EXISTS album_items;
+DROP TABLE IF EXISTS albums;
+DROP TYPE IF EXISTS visibility;
@@ -0,0 +1,39 @@
+CREATE TYPE visibility AS ENUM ('public', 'unlisted', 'private');
+
+CREATE TABLE albums (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    owner_id UUID NOT NULL,
+    title VARCHAR(255) NOT NULL,
+    description TEXT,
+    cover_item_id UUID,
+    visibility visibility NOT NULL DEFAULT 'public',
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+    CONSTRAINT fk_album_owner
+        FOREIGN KEY(owner_id)
+        REFERENCES users(id)
+        ON DELETE CASCADE,
+    CONSTRAINT fk_album_cover
+        FOREIGN KEY(cover_item_id)
+        REFERENCES nft(id)
+        ON DELETE SET NULL
+);
+
+CREATE INDEX idx_albums_owner_id ON albums(owner_id);
+
+CREATE TRIGGER update_albums_updated_at
+BEFORE UPDATE ON albums
+FOR EACH ROW
+EXECUTE PROCEDURE update_updated_at_column();
+
+-- Urutan item di dalam album
+CREATE TABLE album_items (
+    album_id UUID NOT NULL REFERENCES albums(id) ON DELETE CASCADE,
+    nft_id UUID NOT NULL REFERENCES nft(id) ON DELETE CASCADE,
+    position INTEGER NOT NULL,
+    added_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (album_id, nft_id)
+);
+
+CREATE INDEX idx_album_items_position ON album_items(album_id, position);
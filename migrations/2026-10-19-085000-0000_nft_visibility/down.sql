@@ -0,0 +1,4 @@
+DROP INDEX IF EXISTS idx_nft_visibility;
+
+ALTER TABLE nft
+    DROP COLUMN visibility;
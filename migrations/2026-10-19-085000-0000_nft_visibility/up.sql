@@ -0,0 +1,5 @@
+-- Visibility per item galeri, memakai enum yang sama dengan album
+ALTER TABLE nft
+    ADD COLUMN visibility visibility NOT NULL DEFAULT 'public';
+
+CREATE INDEX idx_nft_visibility ON nft(visibility);
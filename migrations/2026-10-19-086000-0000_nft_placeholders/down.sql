@@ -0,0 +1,3 @@
+ALTER TABLE nft
+    DROP COLUMN lqip,
+    DROP COLUMN blurhash;
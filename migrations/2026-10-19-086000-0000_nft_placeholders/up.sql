@@ -0,0 +1,4 @@
+-- Placeholder selama gambar dimuat: string BlurHash dan data URI base64 kecil (LQIP)
+ALTER TABLE nft
+    ADD COLUMN blurhash VARCHAR(64),
+    ADD COLUMN lqip TEXT;
@@ -0,0 +1,2 @@
+ALTER TABLE nft
+    DROP COLUMN size_bytes;
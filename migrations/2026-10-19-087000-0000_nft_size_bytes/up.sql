@@ -0,0 +1,4 @@
+-- Total byte semua file milik item (original + rendition), untuk kuota per user.
+-- Item lama tercatat 0 karena ukuran file tidak bisa dibaca dari SQL.
+ALTER TABLE nft
+    ADD COLUMN size_bytes BIGINT NOT NULL DEFAULT 0;
@@ -0,0 +1,5 @@
+ALTER TABLE nft
+    DROP COLUMN watermark;
+
+ALTER TABLE users
+    DROP COLUMN watermark_default;
@@ -0,0 +1,6 @@
+-- Watermark pada rendition Large/Medium: default per user, bisa diubah per item
+ALTER TABLE users
+    ADD COLUMN watermark_default BOOLEAN NOT NULL DEFAULT FALSE;
+
+ALTER TABLE nft
+    ADD COLUMN watermark BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,5 @@
+ALTER TABLE nft
+    DROP COLUMN duration_ms,
+    DROP COLUMN media_type;
+
+DROP TYPE media_type;
@@ -0,0 +1,6 @@
+-- Jenis media item galeri, supaya client tahu cara menampilkannya
+CREATE TYPE media_type AS ENUM ('image', 'animated_image', 'video');
+
+ALTER TABLE nft
+    ADD COLUMN media_type media_type NOT NULL DEFAULT 'image',
+    ADD COLUMN duration_ms INTEGER;
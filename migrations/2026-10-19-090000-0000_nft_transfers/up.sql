@@ -0,0 +1,17 @@
+-- Riwayat kepemilikan item galeri. Setiap record menyimpan hash record
+-- sebelumnya dan ditandatangani server (Ed25519), jadi perubahan di tengah
+-- rantai bisa dideteksi.
+CREATE TABLE nft_transfers (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    nft_id UUID NOT NULL REFERENCES nft(id) ON DELETE CASCADE,
+    sequence INTEGER NOT NULL,
+    -- NULL pada record genesis (upload pertama)
+    from_user_id UUID REFERENCES users(id),
+    to_user_id UUID NOT NULL REFERENCES users(id),
+    transferred_at TIMESTAMP NOT NULL,
+    prev_hash VARCHAR(64),
+    record_hash VARCHAR(64) NOT NULL,
+    signature TEXT NOT NULL,
+
+    CONSTRAINT uq_nft_transfers_sequence UNIQUE (nft_id, sequence)
+);
@@ -0,0 +1,5 @@
+DROP INDEX idx_nft_missing_alt_text;
+
+ALTER TABLE nft
+    DROP COLUMN long_description,
+    DROP COLUMN alt_text;
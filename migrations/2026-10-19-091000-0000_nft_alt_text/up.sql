@@ -0,0 +1,6 @@
+-- Teks alternatif untuk pembaca layar; NULL = masuk antrean review alt text
+ALTER TABLE nft
+    ADD COLUMN alt_text TEXT,
+    ADD COLUMN long_description TEXT;
+
+CREATE INDEX idx_nft_missing_alt_text ON nft(created_at) WHERE alt_text IS NULL;
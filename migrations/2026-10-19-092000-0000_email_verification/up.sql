@@ -0,0 +1,19 @@
+-- NULL = email belum diverifikasi
+ALTER TABLE users ADD COLUMN email_verified_at TIMESTAMP;
+-- Akun yang sudah ada sebelum verifikasi email dianggap terverifikasi
+UPDATE users SET email_verified_at = created_at;
+
+CREATE TYPE user_token_purpose AS ENUM ('email_verification');
+
+-- Token sekali pakai; yang disimpan hanya SHA-256 dari token yang dikirim ke user
+CREATE TABLE user_tokens (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    purpose user_token_purpose NOT NULL,
+    token_hash VARCHAR(64) NOT NULL UNIQUE,
+    expires_at TIMESTAMP NOT NULL,
+    used_at TIMESTAMP,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_user_tokens_user_purpose ON user_tokens(user_id, purpose, created_at);
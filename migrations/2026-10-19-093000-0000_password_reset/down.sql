@@ -0,0 +1,9 @@
+ALTER TABLE users DROP COLUMN IF EXISTS tokens_revoked_at;
+
+-- Postgres tidak bisa menghapus nilai enum, jadi tipenya dibuat ulang
+DELETE FROM user_tokens WHERE purpose = 'password_reset';
+ALTER TYPE user_token_purpose RENAME TO user_token_purpose_old;
+CREATE TYPE user_token_purpose AS ENUM ('email_verification');
+ALTER TABLE user_tokens
+    ALTER COLUMN purpose TYPE user_token_purpose USING purpose::text::user_token_purpose;
+DROP TYPE user_token_purpose_old;
@@ -0,0 +1,4 @@
+ALTER TYPE user_token_purpose ADD VALUE 'password_reset';
+
+-- JWT yang diterbitkan sebelum waktu ini ditolak (mis. setelah reset password)
+ALTER TABLE users ADD COLUMN tokens_revoked_at TIMESTAMP;
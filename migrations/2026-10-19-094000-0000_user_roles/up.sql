@@ -0,0 +1,7 @@
+-- Menggantikan users.is_admin
+CREATE TYPE user_role AS ENUM ('admin', 'editor', 'author', 'reader');
+
+-- User baru jadi author: boleh menulis dan upload, belum boleh menerbitkan artikel
+ALTER TABLE users ADD COLUMN role user_role NOT NULL DEFAULT 'author';
+UPDATE users SET role = 'admin' WHERE is_admin;
+ALTER TABLE users DROP COLUMN is_admin;
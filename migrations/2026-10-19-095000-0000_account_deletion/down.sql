@@ -0,0 +1,6 @@
+ALTER TABLE articles DROP CONSTRAINT fk_author;
+ALTER TABLE articles
+    ADD CONSTRAINT fk_author FOREIGN KEY (author_id) REFERENCES users(id) ON DELETE CASCADE;
+
+ALTER TABLE articles DROP COLUMN IF EXISTS deleted_at;
+ALTER TABLE users DROP COLUMN IF EXISTS deleted_at;
@@ -0,0 +1,10 @@
+-- Hapus akun = soft delete + anonimisasi, row user tetap ada
+ALTER TABLE users ADD COLUMN deleted_at TIMESTAMP;
+
+-- Artikel milik akun yang dihapus ikut di-soft delete, tidak dihapus permanen
+ALTER TABLE articles ADD COLUMN deleted_at TIMESTAMP;
+
+-- Hapus user secara permanen tidak boleh lagi ikut menghapus artikelnya
+ALTER TABLE articles DROP CONSTRAINT fk_author;
+ALTER TABLE articles
+    ADD CONSTRAINT fk_author FOREIGN KEY (author_id) REFERENCES users(id) ON DELETE RESTRICT;
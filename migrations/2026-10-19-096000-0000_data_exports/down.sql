@@ -0,0 +1,5 @@
+DROP TABLE IF EXISTS data_exports;
+DROP TYPE IF EXISTS export_status;
+DROP TABLE IF EXISTS login_history;
+DROP INDEX IF EXISTS idx_quotes_user;
+ALTER TABLE quotes DROP COLUMN IF EXISTS user_id;
@@ -0,0 +1,30 @@
+-- Pemilik quote; quote lama tidak punya pemilik
+ALTER TABLE quotes ADD COLUMN user_id UUID REFERENCES users(id) ON DELETE SET NULL;
+CREATE INDEX idx_quotes_user ON quotes(user_id);
+
+CREATE TABLE login_history (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    succeeded BOOLEAN NOT NULL,
+    ip_address VARCHAR(45),
+    user_agent TEXT,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_login_history_user ON login_history(user_id, created_at);
+
+CREATE TYPE export_status AS ENUM ('pending', 'processing', 'ready', 'failed', 'expired');
+
+-- Ekspor data pribadi (ZIP), dibuat di background lalu diunduh lewat link bertanda tangan
+CREATE TABLE data_exports (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    status export_status NOT NULL DEFAULT 'pending',
+    size_bytes BIGINT,
+    error TEXT,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    completed_at TIMESTAMP,
+    expires_at TIMESTAMP
+);
+
+CREATE INDEX idx_data_exports_user ON data_exports(user_id, created_at);
@@ -0,0 +1,4 @@
+DROP INDEX IF EXISTS idx_nft_feed;
+DROP INDEX IF EXISTS idx_articles_feed;
+DROP INDEX IF EXISTS idx_quotes_user_created;
+DROP TABLE IF EXISTS follows;
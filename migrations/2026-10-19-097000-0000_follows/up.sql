@@ -0,0 +1,19 @@
+CREATE TABLE follows (
+    follower_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    followee_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (follower_id, followee_id),
+    CONSTRAINT follows_not_self CHECK (follower_id <> followee_id)
+);
+
+-- Daftar follower; daftar following sudah tercakup primary key
+CREATE INDEX idx_follows_followee ON follows(followee_id, created_at);
+
+-- Artikel yang langsung terbit dulu tidak mengisi published_at; feed mengurutkan berdasarkan kolom ini
+UPDATE articles SET published_at = created_at WHERE status = 'published' AND published_at IS NULL;
+
+-- Feed: konten terbaru per user, urut mundur
+CREATE INDEX idx_quotes_user_created ON quotes(user_id, created_at);
+CREATE INDEX idx_articles_feed ON articles(author_id, published_at)
+    WHERE status = 'published' AND deleted_at IS NULL;
+CREATE INDEX idx_nft_feed ON nft(owner_id, created_at) WHERE visibility = 'public';
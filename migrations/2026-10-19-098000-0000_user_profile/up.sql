@@ -0,0 +1,6 @@
+-- avatar: nama file di PUBLIC_DIR/avatars (persegi, lihat PUT /users/me/avatar)
+ALTER TABLE users
+    ADD COLUMN avatar VARCHAR(255),
+    ADD COLUMN bio TEXT,
+    ADD COLUMN website VARCHAR(255),
+    ADD COLUMN location VARCHAR(100);
@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS login_throttles;
+DROP TYPE IF EXISTS login_throttle_scope;
@@ -0,0 +1,15 @@
+CREATE TYPE login_throttle_scope AS ENUM ('username', 'ip');
+
+-- Percobaan login gagal per username dan per IP. Username yang tidak
+-- terdaftar juga dicatat, supaya lockout tidak membocorkan akun mana yang ada.
+CREATE TABLE login_throttles (
+    scope login_throttle_scope NOT NULL,
+    subject VARCHAR(255) NOT NULL,
+    failures INTEGER NOT NULL DEFAULT 0,
+    last_failed_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    locked_until TIMESTAMP,
+    PRIMARY KEY (scope, subject)
+);
+
+-- Pembersihan catatan yang sudah kedaluwarsa
+CREATE INDEX idx_login_throttles_last_failed ON login_throttles(last_failed_at);
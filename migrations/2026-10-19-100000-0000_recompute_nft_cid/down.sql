@@ -0,0 +1,2 @@
+-- CID lama tidak bisa dikembalikan; jalankan ulang backfill_cid kalau perlu
+SELECT 1;
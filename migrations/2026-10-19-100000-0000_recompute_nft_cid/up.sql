@@ -0,0 +1,4 @@
+-- CID lama dihitung dari seluruh file sebagai satu block raw; untuk file di atas
+-- 256 KiB hasilnya tidak sama dengan `ipfs add`. Dikosongkan, lalu dihitung ulang
+-- dengan `cargo run --bin backfill_cid`.
+UPDATE nft SET cid = NULL;
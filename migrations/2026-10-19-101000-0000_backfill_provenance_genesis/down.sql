@@ -0,0 +1,5 @@
+-- Hanya genesis hasil migrasi yang belum pernah dilanjutkan transfer
+DELETE FROM nft_transfers t
+WHERE t.sequence = 0
+  AND t.signature = ''
+  AND NOT EXISTS (SELECT 1 FROM nft_transfers n WHERE n.nft_id = t.nft_id AND n.sequence > 0);
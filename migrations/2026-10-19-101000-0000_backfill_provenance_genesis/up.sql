@@ -0,0 +1,21 @@
+-- Record genesis untuk item yang belum punya rantai provenance, memakai pemilik
+-- saat migrasi dan waktu upload. Hash dihitung sama persis dengan
+-- `provenance::record_hash`; signature Ed25519 tidak bisa dibuat di SQL, jadi
+-- dibiarkan kosong lalu diisi dengan `cargo run --bin sign_provenance`.
+INSERT INTO nft_transfers (nft_id, sequence, from_user_id, to_user_id, transferred_at, prev_hash, record_hash, signature)
+SELECT
+    n.id,
+    0,
+    NULL,
+    n.owner_id,
+    date_trunc('microseconds', n.created_at),
+    NULL,
+    encode(sha256(convert_to(
+        n.id::text || '|0||' || n.owner_id::text || '|'
+            || to_char(date_trunc('microseconds', n.created_at), 'YYYY-MM-DD"T"HH24:MI:SS.US') || '|',
+        'UTF8'
+    )), 'hex'),
+    ''
+FROM nft n
+WHERE n.owner_id IS NOT NULL
+  AND NOT EXISTS (SELECT 1 FROM nft_transfers t WHERE t.nft_id = n.id);
@@ -0,0 +1,4 @@
+-- Diperbarui berkala selama ekspor diproses; ekspor dianggap macet kalau
+-- heartbeat-nya berhenti, bukan karena umurnya
+ALTER TABLE data_exports ADD COLUMN heartbeat_at TIMESTAMP NOT NULL DEFAULT NOW();
+UPDATE data_exports SET heartbeat_at = COALESCE(completed_at, created_at);
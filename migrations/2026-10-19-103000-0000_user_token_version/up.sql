@@ -0,0 +1,6 @@
+-- Naik setiap kali semua JWT milik user dicabut (reset/ganti password, ganti
+-- role, hapus akun); token membawa nilainya di klaim `ver`. Perbandingan waktu
+-- lewat `iat` hanya presisi detik.
+ALTER TABLE users ADD COLUMN token_version INTEGER NOT NULL DEFAULT 0;
+-- Token lama tanpa klaim `ver` dianggap versi 0; user yang token-nya pernah dicabut harus login ulang
+UPDATE users SET token_version = 1 WHERE tokens_revoked_at IS NOT NULL;
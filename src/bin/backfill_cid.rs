@@ -0,0 +1,95 @@
+// Isi kolom `nft.cid` untuk item yang belum punya CID (item lama, atau setelah
+// migrasi yang mengosongkannya). CID dihitung dari file original di PUBLIC_DIR.
+//
+//   cargo run --bin backfill_cid -- [--dry-run]
+
+use std::fs;
+use std::process::exit;
+
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use quoteyourlife_be::db::{establish_connection, get_conn};
+use quoteyourlife_be::{ipfs, storage};
+
+fn main() {
+    dotenvy::dotenv().ok();
+    let dry_run = parse_args();
+
+    let public_dir = storage::public_dir();
+    let pool = establish_connection();
+    let mut conn = get_conn(&pool).expect("Failed to get connection from pool");
+
+    let rows = {
+        use quoteyourlife_be::schema::nft::dsl::*;
+        nft.filter(cid.is_null())
+            .select((id, filename))
+            .load::<(Uuid, String)>(&mut conn)
+            .expect("Failed to load nft rows")
+    };
+    println!("{} item(s) without CID", rows.len());
+
+    let mut filled = 0;
+    let mut failed = 0;
+    for (row_id, stored) in &rows {
+        let data = match fs::read(public_dir.join(stored)) {
+            Ok(data) => data,
+            Err(err) => {
+                eprintln!("Failed to read {} for {}: {}", stored, row_id, err);
+                failed += 1;
+                continue;
+            }
+        };
+        let computed = ipfs::cid_v1(&data);
+        if dry_run {
+            println!("  {} -> {}", row_id, computed);
+            continue;
+        }
+        use quoteyourlife_be::schema::nft::dsl::*;
+        // Jangan timpa CID yang diisi upload/backfill lain sementara script ini jalan
+        match diesel::update(nft.find(row_id).filter(cid.is_null()))
+            .set(cid.eq(&computed))
+            .execute(&mut conn)
+        {
+            Ok(_) => {
+                println!("  {} -> {}", row_id, computed);
+                filled += 1;
+            }
+            Err(err) => {
+                eprintln!("Failed to update {}: {}", row_id, err);
+                failed += 1;
+            }
+        }
+    }
+
+    if dry_run {
+        println!("\nDry run: nothing changed.");
+    } else {
+        println!("\n{} CID(s) filled, {} failed", filled, failed);
+    }
+    if failed > 0 {
+        exit(1);
+    }
+}
+
+fn parse_args() -> bool {
+    let mut dry_run = false;
+    for arg in std::env::args().skip(1) {
+        match arg.as_str() {
+            "--dry-run" => dry_run = true,
+            "-h" | "--help" => usage(""),
+            other => usage(&format!("Unknown argument: {}", other)),
+        }
+    }
+    dry_run
+}
+
+fn usage(message: &str) -> ! {
+    if !message.is_empty() {
+        eprintln!("{}\n", message);
+    }
+    eprintln!("Usage: backfill_cid [--dry-run]");
+    eprintln!();
+    eprintln!("  --dry-run  Print the computed CIDs without saving them");
+    exit(if message.is_empty() { 0 } else { 2 });
+}
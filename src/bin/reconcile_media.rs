@@ -0,0 +1,186 @@
+// Cocokkan isi PUBLIC_DIR dengan tabel `nft`.
+//
+// - File yatim (orphan): ada di disk tapi tidak direferensikan row mana pun,
+//   biasanya dari upload yang gagal insert setelah file ditulis.
+// - Referensi menggantung (dangling): row `nft` yang file-nya tidak ada.
+//
+// Default hanya melaporkan (dry-run). Pakai --quarantine atau --delete untuk
+// menindak file yatim; row yang menggantung hanya dilaporkan.
+//
+//   cargo run --bin reconcile_media -- [--dry-run | --quarantine | --delete] [--min-age-minutes N]
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use std::process::exit;
+use std::time::{Duration, SystemTime};
+
+use chrono::Utc;
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use quoteyourlife_be::db::{establish_connection, get_conn};
+use quoteyourlife_be::storage;
+
+// Folder karantina di dalam PUBLIC_DIR (diawali titik supaya tidak ikut dipindai)
+const QUARANTINE_DIR: &str = ".quarantine";
+// File yang lebih baru dari ini dilewati: bisa jadi upload yang row-nya belum di-insert
+const DEFAULT_MIN_AGE_MINUTES: u64 = 60;
+
+enum Mode {
+    DryRun,
+    Quarantine,
+    Delete,
+}
+
+struct Options {
+    mode: Mode,
+    min_age: Duration,
+}
+
+fn main() {
+    dotenvy::dotenv().ok();
+    let options = parse_args();
+
+    let public_dir = storage::public_dir();
+    let pool = establish_connection();
+    let mut conn = get_conn(&pool).expect("Failed to get connection from pool");
+
+    let rows = {
+        use quoteyourlife_be::schema::nft::dsl::*;
+        nft.select((id, title, filename))
+            .load::<(Uuid, String, String)>(&mut conn)
+            .expect("Failed to load nft rows")
+    };
+
+    let mut referenced: HashSet<String> = HashSet::new();
+    for (_, _, stored) in &rows {
+        referenced.extend(storage::stored_files(stored));
+    }
+
+    let on_disk = list_files(&public_dir);
+    let on_disk_names: HashSet<&String> = on_disk.iter().map(|(name, _)| name).collect();
+
+    println!("PUBLIC_DIR: {}", public_dir.display());
+    println!("{} rows, {} files on disk\n", rows.len(), on_disk.len());
+
+    let now = SystemTime::now();
+    let mut orphans: Vec<&(String, fs::Metadata)> = Vec::new();
+    let mut skipped_recent = 0;
+    for entry in &on_disk {
+        if referenced.contains(&entry.0) {
+            continue;
+        }
+        let age = entry.1.modified().ok().and_then(|m| now.duration_since(m).ok());
+        if age.is_none_or(|age| age < options.min_age) {
+            skipped_recent += 1;
+            continue;
+        }
+        orphans.push(entry);
+    }
+
+    println!("Orphan files ({}):", orphans.len());
+    for (name, metadata) in &orphans {
+        println!("  {} ({} bytes)", name, metadata.len());
+    }
+    if skipped_recent > 0 {
+        println!("  ({} unreferenced files newer than {} minutes skipped)", skipped_recent, options.min_age.as_secs() / 60);
+    }
+
+    let dangling: Vec<_> = rows.iter()
+        .filter(|(_, _, stored)| !on_disk_names.contains(stored))
+        .collect();
+    println!("\nDangling rows ({}):", dangling.len());
+    for (row_id, row_title, stored) in &dangling {
+        println!("  {} \"{}\" -> {}", row_id, row_title, stored);
+    }
+
+    match options.mode {
+        Mode::DryRun => {
+            println!("\nDry run: nothing changed. Use --quarantine or --delete to act on orphan files.");
+        }
+        Mode::Quarantine => {
+            let target = public_dir
+                .join(QUARANTINE_DIR)
+                .join(Utc::now().format("%Y%m%d-%H%M%S").to_string());
+            if !orphans.is_empty() {
+                fs::create_dir_all(&target).expect("Failed to create quarantine directory");
+            }
+            for (name, _) in &orphans {
+                match fs::rename(public_dir.join(name), target.join(name)) {
+                    Ok(()) => println!("Quarantined {}", name),
+                    Err(err) => eprintln!("Failed to quarantine {}: {}", name, err),
+                }
+            }
+            if !orphans.is_empty() {
+                println!("\nOrphan files moved to {}", target.display());
+            }
+        }
+        Mode::Delete => {
+            for (name, _) in &orphans {
+                match fs::remove_file(public_dir.join(name)) {
+                    Ok(()) => println!("Deleted {}", name),
+                    Err(err) => eprintln!("Failed to delete {}: {}", name, err),
+                }
+            }
+        }
+    }
+}
+
+fn parse_args() -> Options {
+    let mut options = Options {
+        mode: Mode::DryRun,
+        min_age: Duration::from_secs(DEFAULT_MIN_AGE_MINUTES * 60),
+    };
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--dry-run" => options.mode = Mode::DryRun,
+            "--quarantine" => options.mode = Mode::Quarantine,
+            "--delete" => options.mode = Mode::Delete,
+            "--min-age-minutes" => {
+                let minutes: u64 = args.next()
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or_else(|| usage("--min-age-minutes needs a number"));
+                options.min_age = Duration::from_secs(minutes * 60);
+            }
+            "-h" | "--help" => usage(""),
+            other => usage(&format!("Unknown argument: {}", other)),
+        }
+    }
+    options
+}
+
+fn usage(message: &str) -> ! {
+    if !message.is_empty() {
+        eprintln!("{}\n", message);
+    }
+    eprintln!("Usage: reconcile_media [--dry-run | --quarantine | --delete] [--min-age-minutes N]");
+    eprintln!();
+    eprintln!("  --dry-run            Report mismatches only (default)");
+    eprintln!("  --quarantine         Move orphan files to PUBLIC_DIR/{}/<timestamp>/", QUARANTINE_DIR);
+    eprintln!("  --delete             Delete orphan files");
+    eprintln!("  --min-age-minutes N  Ignore files modified in the last N minutes (default {})", DEFAULT_MIN_AGE_MINUTES);
+    exit(if message.is_empty() { 0 } else { 2 });
+}
+
+// File biasa di level teratas PUBLIC_DIR, tanpa file/folder tersembunyi
+fn list_files(dir: &Path) -> Vec<(String, fs::Metadata)> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(err) => {
+            eprintln!("Failed to read {}: {}", dir.display(), err);
+            exit(1);
+        }
+    };
+    let mut files: Vec<(String, fs::Metadata)> = entries
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let name = entry.file_name().into_string().ok()?;
+            let metadata = entry.metadata().ok()?;
+            (metadata.is_file() && !name.starts_with('.')).then_some((name, metadata))
+        })
+        .collect();
+    files.sort_by(|a, b| a.0.cmp(&b.0));
+    files
+}
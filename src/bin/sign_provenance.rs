@@ -0,0 +1,111 @@
+// Tandatangani record provenance yang signature-nya masih kosong (genesis yang
+// diisi migrasi). Hash record dihitung ulang dulu; record yang isinya tidak
+// cocok dengan hash-nya dilewati supaya tidak ikut "disahkan".
+//
+//   cargo run --bin sign_provenance -- [--dry-run]
+
+use std::process::exit;
+
+use diesel::prelude::*;
+
+use quoteyourlife_be::db::{establish_connection, get_conn};
+use quoteyourlife_be::models::NftTransfer;
+use quoteyourlife_be::provenance;
+
+fn main() {
+    dotenvy::dotenv().ok();
+    let dry_run = parse_args();
+
+    if let Err(err) = provenance::public_key() {
+        eprintln!("{}", err);
+        exit(2);
+    }
+    let pool = establish_connection();
+    let mut conn = get_conn(&pool).expect("Failed to get connection from pool");
+
+    let records = {
+        use quoteyourlife_be::schema::nft_transfers::dsl::*;
+        nft_transfers
+            .filter(signature.eq(""))
+            .order((nft_id.asc(), sequence.asc()))
+            .select(NftTransfer::as_select())
+            .load(&mut conn)
+            .expect("Failed to load provenance records")
+    };
+    println!("{} unsigned record(s)", records.len());
+
+    let mut signed = 0;
+    let mut failed = 0;
+    for record in &records {
+        let expected = provenance::record_hash(
+            record.nft_id,
+            record.sequence,
+            record.from_user_id,
+            record.to_user_id,
+            record.transferred_at,
+            record.prev_hash.as_deref(),
+        );
+        if expected != record.record_hash {
+            eprintln!("Skipping {} #{}: record does not match its hash", record.nft_id, record.sequence);
+            failed += 1;
+            continue;
+        }
+        if dry_run {
+            println!("  {} #{}", record.nft_id, record.sequence);
+            continue;
+        }
+        let value = match provenance::sign(&record.record_hash) {
+            Ok(value) => value,
+            Err(err) => {
+                eprintln!("Failed to sign {} #{}: {}", record.nft_id, record.sequence, err);
+                failed += 1;
+                continue;
+            }
+        };
+        use quoteyourlife_be::schema::nft_transfers::dsl::*;
+        match diesel::update(nft_transfers.find(record.id).filter(signature.eq("")))
+            .set(signature.eq(&value))
+            .execute(&mut conn)
+        {
+            Ok(_) => {
+                println!("  {} #{} signed", record.nft_id, record.sequence);
+                signed += 1;
+            }
+            Err(err) => {
+                eprintln!("Failed to update {} #{}: {}", record.nft_id, record.sequence, err);
+                failed += 1;
+            }
+        }
+    }
+
+    if dry_run {
+        println!("\nDry run: nothing changed.");
+    } else {
+        println!("\n{} record(s) signed, {} failed", signed, failed);
+    }
+    if failed > 0 {
+        exit(1);
+    }
+}
+
+fn parse_args() -> bool {
+    let mut dry_run = false;
+    for arg in std::env::args().skip(1) {
+        match arg.as_str() {
+            "--dry-run" => dry_run = true,
+            "-h" | "--help" => usage(""),
+            other => usage(&format!("Unknown argument: {}", other)),
+        }
+    }
+    dry_run
+}
+
+fn usage(message: &str) -> ! {
+    if !message.is_empty() {
+        eprintln!("{}\n", message);
+    }
+    eprintln!("Usage: sign_provenance [--dry-run]");
+    eprintln!();
+    eprintln!("  --dry-run  List the records that would be signed without saving");
+    exit(if message.is_empty() { 0 } else { 2 });
+}
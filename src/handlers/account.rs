@@ -0,0 +1,387 @@
+use tracing::{info, debug, warn};
+use axum::{
+    extract::Extension,
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use chrono::{Duration, NaiveDateTime};
+use crate::db::{PgPool, PgPooledConnection, get_conn};
+use quoteyourlife_be::models::{
+    ForgotPasswordPayload, NewUserToken, ResetPasswordPayload, User, UserToken, UserTokenPurposeEnum, VerifyEmailPayload
+};
+use super::AppError;
+use bcrypt::{hash, DEFAULT_COST};
+use diesel::prelude::*;
+use jsonwebtoken::TokenData;
+use serde_json::json;
+use std::env;
+use uuid::Uuid;
+use crate::utils::jwt::Claims;
+use crate::utils::mailer::{self, Email};
+use crate::utils::{user_token, validation};
+
+// Jeda minimal antar email token (verifikasi/reset) untuk user yang sama
+const RESEND_COOLDOWN_SECS: i64 = 60;
+
+pub async fn verify_email(
+    Extension(pool): Extension<PgPool>,
+    Json(payload): Json<VerifyEmailPayload>
+) -> Result<Json<serde_json::Value>, AppError> {
+    info!("[POST /auth/verify-email] Received email verification request");
+
+    let user_id = tokio::task::spawn_blocking(move || -> Result<_, AppError> {
+        let mut conn = get_conn(&pool)?;
+        conn.transaction::<_, AppError, _>(|conn| {
+            let token = consume_token(conn, &payload.token, UserTokenPurposeEnum::EmailVerification)?;
+            use quoteyourlife_be::schema::users::dsl::*;
+            diesel::update(users.find(token.user_id))
+                .filter(email_verified_at.is_null())
+                .set(email_verified_at.eq(diesel::dsl::now))
+                .execute(conn)?;
+            Ok(token.user_id)
+        })
+    })
+    .await
+    .map_err(AppError::AsyncTaskError)?
+    ?;
+
+    info!("[POST /auth/verify-email] Email verified for user {}", user_id);
+    Ok(Json(json!({
+        "status": "success",
+        "message": "Email berhasil diverifikasi"
+    })))
+}
+
+pub async fn resend_verification(
+    Extension(pool): Extension<PgPool>,
+    Extension(token): Extension<TokenData<Claims>>,
+) -> Result<impl IntoResponse, AppError> {
+    info!("[POST /auth/verify-email/resend] Received request to resend verification email");
+
+    let current_user = token.claims.user_id()?;
+    let (user, raw_token) = tokio::task::spawn_blocking(move || -> Result<_, AppError> {
+        let mut conn = get_conn(&pool)?;
+        let user = {
+            use quoteyourlife_be::schema::users::dsl::*;
+            users.find(current_user)
+                .first::<User>(&mut conn)
+                .optional()?
+                .ok_or(AppError::NotFound("User tidak ditemukan".to_string()))?
+        };
+        if user.email_verified_at.is_some() {
+            return Err(AppError::BadRequest("Email sudah terverifikasi".to_string()));
+        }
+
+        if recently_issued(&mut conn, user.id, UserTokenPurposeEnum::EmailVerification)? {
+            return Err(AppError::TooManyRequests(format!(
+                "Tunggu {} detik sebelum meminta email verifikasi lagi", RESEND_COOLDOWN_SECS
+            )));
+        }
+        let raw_token = issue_token(&mut conn, user.id, UserTokenPurposeEnum::EmailVerification, verification_ttl())?;
+        Ok((user, raw_token))
+    })
+    .await
+    .map_err(AppError::AsyncTaskError)?
+    ?;
+
+    send_verification_email(&user, raw_token).await?;
+    info!("[POST /auth/verify-email/resend] Verification email sent to user {}", user.id);
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(json!({
+            "status": "success",
+            "message": "Email verifikasi sudah dikirim ulang"
+        }))
+    ))
+}
+
+/// Selalu 202 dengan pesan yang sama, terdaftar atau tidak emailnya.
+/// Pencarian user dan pengiriman email jalan di belakang supaya waktu
+/// respons juga tidak membocorkan apa-apa.
+pub async fn forgot_password(
+    Extension(pool): Extension<PgPool>,
+    Json(payload): Json<ForgotPasswordPayload>
+) -> Result<impl IntoResponse, AppError> {
+    info!("[POST /auth/forgot-password] Received password reset request");
+
+    let address = validation::validate_email(&payload.email)?;
+    tokio::spawn(async move {
+        if let Err(err) = send_password_reset(pool, address).await {
+            warn!("[POST /auth/forgot-password] Failed to send reset email: {:?}", err);
+        }
+    });
+
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(json!({
+            "status": "success",
+            "message": "Kalau email tersebut terdaftar, link reset password sudah dikirim"
+        }))
+    ))
+}
+
+pub async fn reset_password(
+    Extension(pool): Extension<PgPool>,
+    Json(payload): Json<ResetPasswordPayload>
+) -> Result<Json<serde_json::Value>, AppError> {
+    info!("[POST /auth/reset-password] Received password reset");
+
+    let user = tokio::task::spawn_blocking(move || -> Result<_, AppError> {
+        let mut conn = get_conn(&pool)?;
+        // Password yang ditolak validasi membatalkan transaksi, token masih bisa dipakai lagi
+        conn.transaction::<_, AppError, _>(|conn| {
+            let token = consume_token(conn, &payload.token, UserTokenPurposeEnum::PasswordReset)?;
+            use quoteyourlife_be::schema::users::dsl::*;
+            let user = users.find(token.user_id).first::<User>(conn)?;
+            validation::validate_password(&payload.password, &user.username)?;
+            let hashed = hash(&payload.password, DEFAULT_COST)
+                .map_err(|err| AppError::GeneralError(format!("Gagal memproses password: {}", err)))?;
+
+            let now = chrono::Utc::now().naive_utc();
+            // Semua sesi (JWT) yang lama tidak berlaku lagi
+            diesel::update(users.find(user.id))
+                .set((password_hash.eq(hashed), tokens_revoked_at.eq(now), token_version.eq(token_version + 1)))
+                .execute(conn)?;
+            // Link reset sampai ke inbox, jadi emailnya terbukti milik user
+            diesel::update(users.find(user.id))
+                .filter(email_verified_at.is_null())
+                .set(email_verified_at.eq(now))
+                .execute(conn)?;
+            revoke_tokens(conn, user.id)?;
+            Ok(user)
+        })
+    })
+    .await
+    .map_err(AppError::AsyncTaskError)?
+    ?;
+
+    info!("[POST /auth/reset-password] Password reset for user {}", user.id);
+    Ok(Json(json!({
+        "status": "success",
+        "message": "Password berhasil diganti, silakan login ulang"
+    })))
+}
+
+async fn send_password_reset(pool: PgPool, address: String) -> Result<(), AppError> {
+    let issued = tokio::task::spawn_blocking(move || -> Result<_, AppError> {
+        let mut conn = get_conn(&pool)?;
+        let user = {
+            use quoteyourlife_be::schema::users::dsl::*;
+            users.filter(email.eq(&address))
+                .first::<User>(&mut conn)
+                .optional()?
+        };
+        let Some(user) = user else {
+            debug!("Password reset requested for unknown email");
+            return Ok(None);
+        };
+        // Permintaan beruntun diabaikan diam-diam, membalas 429 sama saja membocorkan akun
+        if recently_issued(&mut conn, user.id, UserTokenPurposeEnum::PasswordReset)? {
+            debug!("Password reset for {} throttled", user.id);
+            return Ok(None);
+        }
+        let raw_token = issue_token(&mut conn, user.id, UserTokenPurposeEnum::PasswordReset, password_reset_ttl())?;
+        Ok(Some((user, raw_token)))
+    })
+    .await
+    .map_err(AppError::AsyncTaskError)?
+    ?;
+
+    let Some((user, raw_token)) = issued else {
+        return Ok(());
+    };
+    let email = token_email(
+        &user,
+        "Reset password QuoteYourLife",
+        "Buka link berikut untuk membuat password baru",
+        "reset-password",
+        &raw_token,
+        &format!("{} menit", password_reset_ttl().num_minutes()),
+    );
+    send_email(email).await?;
+    info!("Password reset email sent to user {}", user.id);
+    Ok(())
+}
+
+// EMAIL_VERIFICATION_TTL_HOURS, default 24 jam
+pub fn verification_ttl() -> Duration {
+    let hours = env::var("EMAIL_VERIFICATION_TTL_HOURS")
+        .ok()
+        .and_then(|raw| raw.trim().parse::<i64>().ok())
+        .filter(|hours| *hours > 0)
+        .unwrap_or(24);
+    Duration::hours(hours)
+}
+
+// PASSWORD_RESET_TTL_MINUTES, default 30 menit
+pub fn password_reset_ttl() -> Duration {
+    let minutes = env::var("PASSWORD_RESET_TTL_MINUTES")
+        .ok()
+        .and_then(|raw| raw.trim().parse::<i64>().ok())
+        .filter(|minutes| *minutes > 0)
+        .unwrap_or(30);
+    Duration::minutes(minutes)
+}
+
+/// Buat token baru untuk user dan kembalikan token aslinya (hanya hash-nya
+/// yang disimpan). Token lama dengan tujuan yang sama yang belum dipakai
+/// ikut dimatikan, jadi hanya link terbaru yang berlaku.
+pub fn issue_token(
+    conn: &mut PgPooledConnection,
+    owner: Uuid,
+    token_purpose: UserTokenPurposeEnum,
+    ttl: Duration
+) -> Result<String, AppError> {
+    use quoteyourlife_be::schema::user_tokens::dsl::*;
+    let now = chrono::Utc::now().naive_utc();
+    diesel::update(user_tokens)
+        .filter(user_id.eq(owner))
+        .filter(purpose.eq(token_purpose))
+        .filter(used_at.is_null())
+        .set(used_at.eq(now))
+        .execute(conn)?;
+
+    let (raw_token, hashed) = user_token::generate();
+    diesel::insert_into(user_tokens)
+        .values(&NewUserToken {
+            user_id: owner,
+            purpose: token_purpose,
+            token_hash: hashed,
+            expires_at: now + ttl,
+        })
+        .execute(conn)?;
+    debug!("Issued {:?} token for user {}", token_purpose, owner);
+    Ok(raw_token)
+}
+
+/// Matikan semua token user yang belum dipakai, apa pun tujuannya.
+pub fn revoke_tokens(conn: &mut PgPooledConnection, owner: Uuid) -> Result<(), AppError> {
+    use quoteyourlife_be::schema::user_tokens::dsl::*;
+    let revoked = diesel::update(user_tokens)
+        .filter(user_id.eq(owner))
+        .filter(used_at.is_null())
+        .set(used_at.eq(chrono::Utc::now().naive_utc()))
+        .execute(conn)?;
+    debug!("Revoked {} outstanding token(s) for user {}", revoked, owner);
+    Ok(())
+}
+
+fn recently_issued(
+    conn: &mut PgPooledConnection,
+    owner: Uuid,
+    token_purpose: UserTokenPurposeEnum
+) -> Result<bool, AppError> {
+    use quoteyourlife_be::schema::user_tokens::dsl::*;
+    let last_sent = user_tokens
+        .filter(user_id.eq(owner))
+        .filter(purpose.eq(token_purpose))
+        .select(diesel::dsl::max(created_at))
+        .first::<Option<NaiveDateTime>>(conn)?;
+    let now = chrono::Utc::now().naive_utc();
+    Ok(last_sent.is_some_and(|sent| now - sent < Duration::seconds(RESEND_COOLDOWN_SECS)))
+}
+
+/// Tandai token sebagai terpakai. Token yang tidak dikenal, sudah dipakai
+/// atau kedaluwarsa ditolak dengan 400. Panggil di dalam transaksi.
+pub fn consume_token(
+    conn: &mut PgPooledConnection,
+    raw_token: &str,
+    token_purpose: UserTokenPurposeEnum
+) -> Result<UserToken, AppError> {
+    use quoteyourlife_be::schema::user_tokens::dsl::*;
+    let invalid = || AppError::BadRequest("Token tidak valid atau sudah dipakai".to_string());
+    if raw_token.trim().is_empty() {
+        return Err(invalid());
+    }
+    let token = user_tokens
+        .filter(token_hash.eq(user_token::hash(raw_token)))
+        .filter(purpose.eq(token_purpose))
+        .filter(used_at.is_null())
+        .for_update()
+        .select(UserToken::as_select())
+        .first(conn)
+        .optional()?
+        .ok_or_else(invalid)?;
+    let now = chrono::Utc::now().naive_utc();
+    if token.expires_at < now {
+        return Err(AppError::BadRequest("Token sudah kedaluwarsa".to_string()));
+    }
+    diesel::update(user_tokens.find(token.id))
+        .set(used_at.eq(now))
+        .execute(conn)?;
+    Ok(token)
+}
+
+pub async fn send_verification_email(user: &User, raw_token: String) -> Result<(), AppError> {
+    let email = token_email(
+        user,
+        "Verifikasi email QuoteYourLife",
+        "Buka link berikut untuk memverifikasi email kamu",
+        "verify-email",
+        &raw_token,
+        &format!("{} jam", verification_ttl().num_hours()),
+    );
+    send_email(email).await
+}
+
+// Tanpa APP_BASE_URL user cukup menyalin token ke aplikasi
+fn token_email(user: &User, subject: &str, instruction: &str, path: &str, raw_token: &str, validity: &str) -> Email {
+    let link = env::var("APP_BASE_URL")
+        .map(|base| format!("{}:\n{}/{}?token={}\n\n", instruction, base.trim_end_matches('/'), path, raw_token))
+        .unwrap_or_default();
+    Email {
+        to: user.email.clone(),
+        subject: subject.to_string(),
+        body: format!(
+            "Halo {},\n\n{}Token: {}\nBerlaku {}.\n",
+            user.username,
+            link,
+            raw_token,
+            validity
+        ),
+    }
+}
+
+async fn send_email(email: Email) -> Result<(), AppError> {
+    tokio::task::spawn_blocking(move || mailer::mailer()?.send(&email))
+        .await
+        .map_err(AppError::AsyncTaskError)?
+}
+
+/// Akun yang emailnya belum diverifikasi hanya boleh menyimpan konten
+/// pribadi (draft artikel, item galeri private).
+pub fn ensure_verified(conn: &mut PgPooledConnection, owner: Uuid, action: &str) -> Result<(), AppError> {
+    use quoteyourlife_be::schema::users::dsl::*;
+    let verified = users.find(owner)
+        .select(email_verified_at)
+        .first::<Option<NaiveDateTime>>(conn)
+        .optional()?
+        .flatten()
+        .is_some();
+    if verified {
+        return Ok(());
+    }
+    Err(unverified_error(owner, action))
+}
+
+pub fn unverified_error(owner: Uuid, action: &str) -> AppError {
+    warn!("Unverified user {} tried to {}", owner, action);
+    AppError::Forbidden(format!("Verifikasi email dulu sebelum {}", action))
+}
+
+/// JWT ditolak kalau akunnya sudah dihapus, atau versinya tertinggal dari
+/// `users.token_version` (sesi dicabut setelah token dibuat).
+pub fn ensure_token_current(conn: &mut PgPooledConnection, claims: &Claims) -> Result<(), AppError> {
+    let owner = claims.user_id()?;
+    use quoteyourlife_be::schema::users::dsl::*;
+    let current = users.find(owner)
+        .filter(deleted_at.is_null())
+        .select(token_version)
+        .first::<i32>(conn)
+        .optional()?;
+    if current != Some(claims.ver) {
+        return Err(AppError::JWTValidationError(jsonwebtoken::errors::ErrorKind::InvalidToken.into()));
+    }
+    Ok(())
+}
@@ -0,0 +1,378 @@
+use tracing::{info, debug};
+use axum::{
+    extract::{Extension, Path},
+    http::{HeaderMap, StatusCode},
+    Json,
+};
+use crate::db::{PgPool, PgPooledConnection, get_conn};
+use quoteyourlife_be::models::{
+    Album,
+    AlbumDetail,
+    AlbumPayload,
+    NewAlbum,
+    NewAlbumItem,
+    NFT,
+    UpdateAlbum,
+    VisibilityEnum
+};
+use super::{AppError, is_admin_user};
+use diesel::prelude::*;
+use jsonwebtoken::TokenData;
+use serde::Deserialize;
+use uuid::Uuid;
+use crate::utils::jwt::{Claims, optional_claims};
+
+#[derive(Deserialize)]
+pub struct AlbumItemsPayload {
+    items: Vec<Uuid>,
+}
+
+#[derive(Deserialize)]
+pub struct AddAlbumItemPayload {
+    nft_id: Uuid,
+    // Posisi tujuan (0 = paling depan); kosong = ditaruh di akhir
+    position: Option<usize>,
+}
+
+pub async fn get_all_albums(
+    Extension(pool): Extension<PgPool>,
+    headers: HeaderMap
+) -> Result<Json<Vec<Album>>, AppError> {
+    info!("[GET /albums] Received request to fetch albums");
+
+    let viewer = optional_claims(&pool, &headers).await?.map(|c| c.user_id()).transpose()?;
+    let results = tokio::task::spawn_blocking(move || -> Result<_, AppError> {
+        let mut conn = get_conn(&pool)?;
+        use quoteyourlife_be::schema::albums::dsl::*;
+        // Album unlisted/private tidak muncul di daftar, kecuali milik user sendiri
+        let mut query = albums.into_boxed().filter(visibility.eq(VisibilityEnum::Public));
+        if let Some(viewer_id) = viewer {
+            query = query.or_filter(owner_id.eq(viewer_id));
+        }
+        let results = query.order(created_at.desc()).load::<Album>(&mut conn)?;
+        Ok(results)
+    })
+    .await
+    .map_err(AppError::AsyncTaskError)?
+    ?;
+
+    info!("[GET /albums] Successfully fetched {} albums", results.len());
+    Ok(Json(results))
+}
+
+pub async fn get_album(
+    Extension(pool): Extension<PgPool>,
+    Path(album_id): Path<Uuid>,
+    headers: HeaderMap
+) -> Result<Json<AlbumDetail>, AppError> {
+    info!("[GET /albums/{}] Received request to fetch album", album_id);
+
+    let viewer = optional_claims(&pool, &headers).await?.map(|c| c.user_id()).transpose()?;
+    let detail = tokio::task::spawn_blocking(move || -> Result<_, AppError> {
+        let mut conn = get_conn(&pool)?;
+        let album = find_viewable_album(&mut conn, album_id, viewer)?;
+        let items = load_album_items(&mut conn, album.id, viewer)?;
+        Ok(AlbumDetail { album, items })
+    })
+    .await
+    .map_err(AppError::AsyncTaskError)?
+    ?;
+
+    debug!("Album {} has {} items", album_id, detail.items.len());
+    Ok(Json(detail))
+}
+
+pub async fn create_new_album(
+    Extension(pool): Extension<PgPool>,
+    Extension(token): Extension<TokenData<Claims>>,
+    Json(payload): Json<AlbumPayload>
+) -> Result<Json<Album>, AppError> {
+    info!("[POST /albums] Received request to create new album");
+    debug!("Request payload: {:?}", payload);
+
+    if payload.title.trim().is_empty() {
+        return Err(AppError::BadRequest("Judul album tidak boleh kosong".to_string()));
+    }
+
+    let user_id = token.claims.user_id()?;
+    let new_album = tokio::task::spawn_blocking(move || -> Result<_, AppError> {
+        let mut conn = get_conn(&pool)?;
+        if let Some(cover) = payload.cover_item_id {
+            ensure_items_viewable(&mut conn, &[cover], user_id)?;
+        }
+        let values = NewAlbum {
+            owner_id: user_id,
+            title: payload.title,
+            description: payload.description,
+            cover_item_id: payload.cover_item_id,
+            visibility: payload.visibility.unwrap_or(VisibilityEnum::Public),
+        };
+        use quoteyourlife_be::schema::albums::dsl::*;
+        let result = diesel::insert_into(albums)
+            .values(&values)
+            .returning(Album::as_returning())
+            .get_result(&mut conn)?;
+        Ok(result)
+    })
+    .await
+    .map_err(AppError::AsyncTaskError)?
+    ?;
+
+    info!("[POST /albums] Successfully created new album with ID: {}", new_album.id);
+    Ok(Json(new_album))
+}
+
+pub async fn update_album(
+    Extension(pool): Extension<PgPool>,
+    Extension(token): Extension<TokenData<Claims>>,
+    Path(album_id): Path<Uuid>,
+    Json(payload): Json<UpdateAlbum>
+) -> Result<Json<Album>, AppError> {
+    info!("[PATCH /albums/{}] Received request to update album", album_id);
+    debug!("Request payload: {:?}", payload);
+
+    if payload.title.is_none()
+        && payload.description.is_none()
+        && payload.cover_item_id.is_none()
+        && payload.visibility.is_none()
+    {
+        return Err(AppError::BadRequest("Tidak ada field yang diubah".to_string()));
+    }
+
+    let user_id = token.claims.user_id()?;
+    let updated = tokio::task::spawn_blocking(move || -> Result<_, AppError> {
+        let mut conn = get_conn(&pool)?;
+        let album = find_editable_album(&mut conn, album_id, user_id)?;
+        if let Some(cover) = payload.cover_item_id {
+            ensure_items_viewable(&mut conn, &[cover], user_id)?;
+        }
+        use quoteyourlife_be::schema::albums::dsl::*;
+        let result = diesel::update(albums.find(album.id))
+            .set(&payload)
+            .returning(Album::as_returning())
+            .get_result(&mut conn)?;
+        Ok(result)
+    })
+    .await
+    .map_err(AppError::AsyncTaskError)?
+    ?;
+
+    info!("[PATCH /albums/{}] Successfully updated album", album_id);
+    Ok(Json(updated))
+}
+
+pub async fn delete_album(
+    Extension(pool): Extension<PgPool>,
+    Extension(token): Extension<TokenData<Claims>>,
+    Path(album_id): Path<Uuid>
+) -> Result<StatusCode, AppError> {
+    info!("[DELETE /albums/{}] Received request to delete album", album_id);
+
+    let user_id = token.claims.user_id()?;
+    tokio::task::spawn_blocking(move || -> Result<_, AppError> {
+        let mut conn = get_conn(&pool)?;
+        let album = find_editable_album(&mut conn, album_id, user_id)?;
+        // Item galerinya tidak ikut terhapus, hanya isi album (ON DELETE CASCADE)
+        use quoteyourlife_be::schema::albums::dsl::*;
+        diesel::delete(albums.find(album.id)).execute(&mut conn)?;
+        Ok(())
+    })
+    .await
+    .map_err(AppError::AsyncTaskError)?
+    ?;
+
+    info!("[DELETE /albums/{}] Successfully deleted album", album_id);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// Ganti seluruh isi album dengan urutan baru (dipakai juga untuk reorder)
+pub async fn set_album_items(
+    Extension(pool): Extension<PgPool>,
+    Extension(token): Extension<TokenData<Claims>>,
+    Path(album_id): Path<Uuid>,
+    Json(payload): Json<AlbumItemsPayload>
+) -> Result<Json<AlbumDetail>, AppError> {
+    info!("[PUT /albums/{}/items] Received request to set {} album items", album_id, payload.items.len());
+
+    let mut seen = std::collections::HashSet::new();
+    if let Some(duplicate) = payload.items.iter().find(|item| !seen.insert(**item)) {
+        return Err(AppError::BadRequest(format!("Item {} muncul lebih dari sekali", duplicate)));
+    }
+
+    let user_id = token.claims.user_id()?;
+    let detail = tokio::task::spawn_blocking(move || -> Result<_, AppError> {
+        let mut conn = get_conn(&pool)?;
+        let album = find_editable_album(&mut conn, album_id, user_id)?;
+        ensure_items_viewable(&mut conn, &payload.items, user_id)?;
+        replace_album_items(&mut conn, album.id, &payload.items)?;
+        let items = load_album_items(&mut conn, album.id, Some(user_id))?;
+        Ok(AlbumDetail { album, items })
+    })
+    .await
+    .map_err(AppError::AsyncTaskError)?
+    ?;
+
+    info!("[PUT /albums/{}/items] Album now has {} items", album_id, detail.items.len());
+    Ok(Json(detail))
+}
+
+pub async fn add_album_item(
+    Extension(pool): Extension<PgPool>,
+    Extension(token): Extension<TokenData<Claims>>,
+    Path(album_id): Path<Uuid>,
+    Json(payload): Json<AddAlbumItemPayload>
+) -> Result<Json<AlbumDetail>, AppError> {
+    info!("[POST /albums/{}/items] Received request to add item {}", album_id, payload.nft_id);
+
+    let user_id = token.claims.user_id()?;
+    let detail = tokio::task::spawn_blocking(move || -> Result<_, AppError> {
+        let mut conn = get_conn(&pool)?;
+        let album = find_editable_album(&mut conn, album_id, user_id)?;
+        ensure_items_viewable(&mut conn, &[payload.nft_id], user_id)?;
+
+        // Urutan lengkap, termasuk item private milik orang lain yang tidak ikut ditampilkan
+        let mut ids: Vec<Uuid> = {
+            use quoteyourlife_be::schema::album_items;
+            album_items::table
+                .filter(album_items::album_id.eq(album.id))
+                .order(album_items::position.asc())
+                .select(album_items::nft_id)
+                .load::<Uuid>(&mut conn)?
+        };
+        ids.retain(|item_id| *item_id != payload.nft_id);
+        let index = payload.position.unwrap_or(ids.len()).min(ids.len());
+        ids.insert(index, payload.nft_id);
+        replace_album_items(&mut conn, album.id, &ids)?;
+
+        let items = load_album_items(&mut conn, album.id, Some(user_id))?;
+        Ok(AlbumDetail { album, items })
+    })
+    .await
+    .map_err(AppError::AsyncTaskError)?
+    ?;
+
+    info!("[POST /albums/{}/items] Album now has {} items", album_id, detail.items.len());
+    Ok(Json(detail))
+}
+
+pub async fn remove_album_item(
+    Extension(pool): Extension<PgPool>,
+    Extension(token): Extension<TokenData<Claims>>,
+    Path((album_id, item_id)): Path<(Uuid, Uuid)>
+) -> Result<StatusCode, AppError> {
+    info!("[DELETE /albums/{}/items/{}] Received request to remove album item", album_id, item_id);
+
+    let user_id = token.claims.user_id()?;
+    tokio::task::spawn_blocking(move || -> Result<_, AppError> {
+        let mut conn = get_conn(&pool)?;
+        let album = find_editable_album(&mut conn, album_id, user_id)?;
+        use quoteyourlife_be::schema::album_items;
+        let removed = diesel::delete(album_items::table.find((album.id, item_id))).execute(&mut conn)?;
+        if removed == 0 {
+            return Err(AppError::NotFound(format!("Item {} tidak ada di album ini", item_id)));
+        }
+        Ok(())
+    })
+    .await
+    .map_err(AppError::AsyncTaskError)?
+    ?;
+
+    info!("[DELETE /albums/{}/items/{}] Successfully removed album item", album_id, item_id);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Album yang boleh dilihat `viewer`. Album private milik orang lain
+/// dilaporkan sebagai 404 supaya keberadaannya tidak bocor.
+pub fn find_viewable_album(
+    conn: &mut PgPooledConnection,
+    album_id: Uuid,
+    viewer: Option<Uuid>
+) -> Result<Album, AppError> {
+    let album = find_album(conn, album_id)?;
+    if album.visibility != VisibilityEnum::Private {
+        return Ok(album);
+    }
+    match viewer {
+        Some(viewer_id) if viewer_id == album.owner_id || is_admin_user(conn, viewer_id)? => Ok(album),
+        _ => Err(album_not_found(album_id)),
+    }
+}
+
+/// Isi album sesuai urutan. Item private milik orang lain disembunyikan,
+/// walaupun albumnya bisa dilihat.
+pub fn load_album_items(conn: &mut PgPooledConnection, album: Uuid, viewer: Option<Uuid>) -> Result<Vec<NFT>, AppError> {
+    use quoteyourlife_be::schema::{album_items, nft};
+    let mut query = album_items::table
+        .inner_join(nft::table)
+        .filter(album_items::album_id.eq(album))
+        .into_boxed();
+    let is_admin = match viewer {
+        Some(viewer_id) => is_admin_user(conn, viewer_id)?,
+        None => false,
+    };
+    if !is_admin {
+        let hidden = nft::visibility.eq(VisibilityEnum::Private)
+            .and(nft::owner_id.is_distinct_from(viewer));
+        query = query.filter(diesel::dsl::not(hidden));
+    }
+    let items = query
+        .order(album_items::position.asc())
+        .select(NFT::as_select())
+        .load::<NFT>(conn)?;
+    Ok(items)
+}
+
+fn find_album(conn: &mut PgPooledConnection, album_id: Uuid) -> Result<Album, AppError> {
+    use quoteyourlife_be::schema::albums::dsl::*;
+    albums.find(album_id)
+        .first::<Album>(conn)
+        .optional()?
+        .ok_or_else(|| album_not_found(album_id))
+}
+
+// Hanya pemilik album atau admin yang boleh mengubah
+fn find_editable_album(conn: &mut PgPooledConnection, album_id: Uuid, user_id: Uuid) -> Result<Album, AppError> {
+    let album = find_viewable_album(conn, album_id, Some(user_id))?;
+    if album.owner_id == user_id || is_admin_user(conn, user_id)? {
+        Ok(album)
+    } else {
+        Err(AppError::Forbidden("Hanya pemilik atau admin yang boleh mengubah album ini".to_string()))
+    }
+}
+
+// Item private orang lain diperlakukan sama dengan item yang tidak ada, seperti find_viewable_nft
+fn ensure_items_viewable(conn: &mut PgPooledConnection, ids: &[Uuid], user_id: Uuid) -> Result<(), AppError> {
+    let admin = is_admin_user(conn, user_id)?;
+    use quoteyourlife_be::schema::nft::dsl::*;
+    let mut query = nft.filter(id.eq_any(ids)).select(id).into_boxed();
+    if !admin {
+        query = query.filter(visibility.ne(VisibilityEnum::Private).or(owner_id.eq(user_id)));
+    }
+    let found: Vec<Uuid> = query.load(conn)?;
+    match ids.iter().find(|item_id| !found.contains(item_id)) {
+        Some(missing) => Err(AppError::BadRequest(format!("NFT {} tidak ditemukan", missing))),
+        None => Ok(()),
+    }
+}
+
+fn replace_album_items(conn: &mut PgPooledConnection, album: Uuid, ids: &[Uuid]) -> Result<(), AppError> {
+    use quoteyourlife_be::schema::album_items::dsl::*;
+    let rows: Vec<NewAlbumItem> = ids.iter()
+        .enumerate()
+        .map(|(index, item_id)| NewAlbumItem {
+            album_id: album,
+            nft_id: *item_id,
+            position: index as i32,
+        })
+        .collect();
+    conn.transaction::<_, diesel::result::Error, _>(|conn| {
+        diesel::delete(album_items.filter(album_id.eq(album))).execute(conn)?;
+        diesel::insert_into(album_items).values(&rows).execute(conn)?;
+        Ok(())
+    })?;
+    Ok(())
+}
+
+fn album_not_found(album_id: Uuid) -> AppError {
+    AppError::NotFound(format!("Album {} tidak ditemukan", album_id))
+}
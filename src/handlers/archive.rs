@@ -0,0 +1,206 @@
+use tracing::{info, debug, warn};
+use axum::{
+    body::Body,
+    extract::{Extension, Query},
+    http::{header, HeaderMap, HeaderValue},
+    response::{IntoResponse, Response},
+};
+use crate::db::{PgPool, get_conn};
+use quoteyourlife_be::models::{ArchiveEntry, ArchiveManifest, NFT, VisibilityEnum};
+use quoteyourlife_be::storage::{self, Variant};
+use super::{AppError, is_admin_user};
+use super::album::{find_viewable_album, load_album_items};
+use diesel::prelude::*;
+use chrono::NaiveDateTime;
+use serde::Deserialize;
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use tokio_util::io::{ReaderStream, SyncIoBridge};
+use uuid::Uuid;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+use crate::utils::jwt::optional_claims;
+
+// Buffer pipe antara penulis ZIP dan body response; penulis menunggu kalau client lambat
+const ARCHIVE_BUFFER_SIZE: usize = 64 * 1024;
+
+#[derive(Deserialize)]
+pub struct ArchiveQuery {
+    album: Option<Uuid>,
+    author: Option<String>,
+}
+
+// File yang akan dimasukkan ke arsip
+struct ArchiveFile {
+    name: String,
+    path: PathBuf,
+    modified: NaiveDateTime,
+}
+
+/// ZIP berisi file original item galeri + `manifest.json`. Arsip ditulis
+/// sambil dikirim (tanpa kompresi, gambar/video sudah terkompresi), jadi
+/// tidak pernah disimpan utuh di memori maupun di disk.
+pub async fn get_gallery_archive(
+    Extension(pool): Extension<PgPool>,
+    Query(params): Query<ArchiveQuery>,
+    headers: HeaderMap
+) -> Result<Response, AppError> {
+    info!("[GET /gallery/archive] Received request for gallery archive");
+
+    if params.album.is_none() && params.author.is_none() {
+        return Err(AppError::BadRequest("Isi parameter 'album' atau 'author'".to_string()));
+    }
+    let viewer = optional_claims(&pool, &headers).await?.map(|c| c.user_id()).transpose()?;
+    let author_filter = params.author.clone();
+    let (album, items, viewer_is_admin) = tokio::task::spawn_blocking(move || -> Result<_, AppError> {
+        let mut conn = get_conn(&pool)?;
+        let is_admin = match viewer {
+            Some(viewer_id) => is_admin_user(&mut conn, viewer_id)?,
+            None => false,
+        };
+        // ?album=<id>: urutan kurasi album, bisa dipersempit dengan ?author=
+        if let Some(album_id) = params.album {
+            let album = find_viewable_album(&mut conn, album_id, viewer)?;
+            let mut items = load_album_items(&mut conn, album.id, viewer)?;
+            if let Some(name) = &params.author {
+                items.retain(|item| &item.author == name);
+            }
+            return Ok((Some(album), items, is_admin));
+        }
+
+        use quoteyourlife_be::schema::nft::dsl::*;
+        let name = params.author.unwrap_or_default();
+        let mut query = nft.into_boxed().filter(author.eq(name));
+        // Sama seperti GET /gallery: hanya item publik dan milik sendiri
+        query = match viewer {
+            Some(viewer_id) => query.filter(visibility.eq(VisibilityEnum::Public).or(owner_id.eq(viewer_id))),
+            None => query.filter(visibility.eq(VisibilityEnum::Public)),
+        };
+        let items = query.order(created_at.asc()).load::<NFT>(&mut conn)?;
+        Ok((None, items, is_admin))
+    })
+    .await
+    .map_err(AppError::AsyncTaskError)?
+    ?;
+    debug!("Archiving {} item(s)", items.len());
+
+    let download_name = match &album {
+        Some(album) => format!("album-{}.zip", album.id),
+        None => format!("gallery-{}.zip", safe_filename(author_filter.as_deref().unwrap_or_default())),
+    };
+
+    let public_dir = storage::public_dir();
+    let mut files = Vec::new();
+    let mut entries = Vec::new();
+    for (index, item) in items.into_iter().enumerate() {
+        let is_owner = viewer.is_some() && item.owner_id == viewer;
+        let Some(variant) = archive_variant(&item, is_owner || viewer_is_admin) else {
+            // Tetap tercantum di manifest (sama seperti daftar item album), tanpa file
+            debug!("File of NFT {} not included: {:?} item of another user", item.id, item.visibility);
+            entries.push(ArchiveEntry { file: None, variant: None, item });
+            continue;
+        };
+        let path = public_dir.join(storage::variant_filename(&item.filename, variant));
+        let file = if path.exists() {
+            let extension = item.filename.rsplit_once('.').map(|(_, ext)| ext).unwrap_or("bin");
+            let name = format!("files/{:04}-{}.{}", index + 1, item.id, extension);
+            files.push(ArchiveFile { name: name.clone(), path, modified: item.created_at });
+            Some(name)
+        } else {
+            warn!("[GET /gallery/archive] File missing for NFT {}: {}", item.id, path.display());
+            None
+        };
+        entries.push(ArchiveEntry { file, variant: Some(variant.to_string()), item });
+    }
+
+    let manifest = ArchiveManifest {
+        generated_at: chrono::Utc::now().naive_utc(),
+        album,
+        author: author_filter,
+        items: entries,
+    };
+    let generated_at = manifest.generated_at;
+    let manifest = serde_json::to_vec_pretty(&manifest)
+        .map_err(|err| AppError::GeneralError(format!("Gagal membuat manifest: {}", err)))?;
+
+    // Penulis ZIP jalan di thread blocking, hasilnya mengalir lewat pipe ke body response
+    let (writer, reader) = tokio::io::duplex(ARCHIVE_BUFFER_SIZE);
+    let writer = SyncIoBridge::new(writer);
+    tokio::task::spawn_blocking(move || {
+        match write_archive(writer, &manifest, generated_at, &files) {
+            Ok(()) => debug!("Gallery archive finished: {} file(s)", files.len()),
+            // Biasanya client memutus download di tengah jalan
+            Err(err) => warn!("Gallery archive aborted: {}", err),
+        }
+    });
+
+    let mut response = Body::from_stream(ReaderStream::new(reader)).into_response();
+    let response_headers = response.headers_mut();
+    response_headers.insert(header::CONTENT_TYPE, HeaderValue::from_static("application/zip"));
+    response_headers.insert(
+        header::CONTENT_DISPOSITION,
+        HeaderValue::from_str(&format!("attachment; filename=\"{}\"", download_name))
+            .expect("nama file sudah dibersihkan"),
+    );
+    info!("[GET /gallery/archive] Streaming archive {}", download_name);
+    Ok(response)
+}
+
+/// Aturan yang sama dengan `GET /media/{id}/{variant}` tanpa signature: arsip
+/// tidak membawa URL bertanda tangan, jadi item unlisted/private hanya disertakan
+/// untuk pemilik dan admin (yang memang bisa membuat URL-nya). Original item
+/// yang di-watermark hanya untuk mereka; yang lain dapat versi Large.
+fn archive_variant(item: &NFT, privileged: bool) -> Option<Variant> {
+    if privileged {
+        return Some(Variant::Original);
+    }
+    if item.visibility != VisibilityEnum::Public {
+        return None;
+    }
+    Some(if item.watermark { Variant::Large } else { Variant::Original })
+}
+
+fn write_archive<W: Write>(
+    writer: W,
+    manifest: &[u8],
+    generated_at: NaiveDateTime,
+    files: &[ArchiveFile]
+) -> zip::result::ZipResult<()> {
+    let mut zip = ZipWriter::new_stream(writer);
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+
+    zip.start_file("manifest.json", with_modified(options, generated_at))?;
+    zip.write_all(manifest)?;
+    for file in files {
+        // File yang hilang setelah manifest dibuat dilewati, arsip tetap valid
+        let mut source = match File::open(&file.path) {
+            Ok(source) => source,
+            Err(err) => {
+                warn!("Skipping {} in archive: {}", file.path.display(), err);
+                continue;
+            }
+        };
+        let size = source.metadata()?.len();
+        let file_options = options.large_file(size >= u32::MAX as u64);
+        zip.start_file(file.name.as_str(), with_modified(file_options, file.modified))?;
+        io::copy(&mut source, &mut zip)?;
+    }
+    zip.finish()?;
+    Ok(())
+}
+
+// Format waktu ZIP hanya 1980-2107; di luar itu pakai default
+pub(super) fn with_modified(options: SimpleFileOptions, modified: NaiveDateTime) -> SimpleFileOptions {
+    match zip::DateTime::try_from(modified) {
+        Ok(modified) => options.last_modified_time(modified),
+        Err(_) => options,
+    }
+}
+
+// Karakter aman untuk header Content-Disposition
+pub(super) fn safe_filename(raw: &str) -> String {
+    raw.chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect()
+}
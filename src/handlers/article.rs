@@ -1,24 +1,54 @@
 use tracing::{info, debug};
 use axum::{
     extract::Extension,
+    http::HeaderMap,
     Json,
 };
-use crate::db::{PgPool, get_conn}; 
-use quoteyourlife_be::models::{Article, NewArticle};
+use crate::db::{PgPool, PgPooledConnection, get_conn}; 
+use quoteyourlife_be::models::{Article, ArticleStatusEnum, ArticleWithAuthor, NewArticle, Permission, User, VisibilityEnum};
 use super::AppError;
+use super::account::ensure_verified;
 use diesel::prelude::*;
+use jsonwebtoken::TokenData;
+use serde_json::Value;
+use std::collections::HashMap;
+use uuid::Uuid;
+use crate::utils::jwt::{Claims, optional_claims};
 
 pub async fn get_all_articles(
     Extension(pool): Extension<PgPool>,
-) -> Result<Json<Vec<Article>>, AppError> {
+    headers: HeaderMap
+) -> Result<Json<Vec<ArticleWithAuthor>>, AppError> {
     info!("[GET /article] Received request to fetch all articles");
     debug!("Starting database query for articles");
-    
+
+    let viewer = optional_claims(&pool, &headers).await?;
     let articles = tokio::task::spawn_blocking(move || -> Result<_, AppError> {
         let mut conn = get_conn(&pool)?;
-        use quoteyourlife_be::schema::articles::dsl::*;
-        let results = articles.load::<Article>(&mut conn)?;
-        Ok(results)
+        use quoteyourlife_be::schema::{articles, users};
+        // Artikel milik akun yang sudah dihapus tidak ditampilkan
+        let mut query = articles::table
+            .inner_join(users::table)
+            .filter(articles::deleted_at.is_null())
+            .into_boxed();
+        // Draft hanya untuk penulisnya; editor/admin melihat semua draft
+        match viewer {
+            Some(claims) if claims.role.can(Permission::PublishArticle) => {}
+            Some(claims) => {
+                query = query.filter(
+                    articles::status.eq(ArticleStatusEnum::Published)
+                        .or(articles::author_id.eq(claims.user_id()?))
+                );
+            }
+            None => query = query.filter(articles::status.eq(ArticleStatusEnum::Published)),
+        }
+        let (mut results, authors): (Vec<Article>, Vec<User>) = query
+            .select((Article::as_select(), User::as_select()))
+            .load::<(Article, User)>(&mut conn)?
+            .into_iter()
+            .unzip();
+        embed_gallery_alt_text(&mut conn, &mut results)?;
+        Ok(with_authors(results, authors))
     })
     .await
     .map_err(AppError::AsyncTaskError)?
@@ -31,25 +61,109 @@ pub async fn get_all_articles(
 
 pub async fn create_new_article(
     Extension(pool): Extension<PgPool>,
-    Json(payload): Json<NewArticle>
-) -> Result<Json<Article>, AppError> {
+    Extension(token): Extension<TokenData<Claims>>,
+    Json(mut payload): Json<NewArticle>
+) -> Result<Json<ArticleWithAuthor>, AppError> {
     info!("[POST /article] Received request to create new article");
     debug!("Request payload - title: {}, slug: {}, status: {:?}", payload.title, payload.slug, payload.status);
     
+    let user_id = token.claims.user_id()?;
+    payload.author_id = user_id;
+    // Author hanya boleh membuat draft, yang menerbitkan editor/admin
+    if payload.status == ArticleStatusEnum::Published {
+        token.claims.require(Permission::PublishArticle)?;
+        payload.published_at = Some(chrono::Utc::now().naive_utc());
+    }
     let new_article = tokio::task::spawn_blocking(move || -> Result<_, AppError> {
         let mut conn = get_conn(&pool)?;
+        // Draft boleh, terbit harus dengan email terverifikasi
+        if payload.status == ArticleStatusEnum::Published {
+            ensure_verified(&mut conn, user_id, "menerbitkan artikel")?;
+        }
         use quoteyourlife_be::schema::articles::dsl::*;
-        let result = diesel::insert_into(articles)
+        let mut result = diesel::insert_into(articles)
             .values(payload)
             .returning(Article::as_returning())
             .get_result(&mut conn)?;
-        Ok(result)
+        embed_gallery_alt_text(&mut conn, std::slice::from_mut(&mut result))?;
+        use quoteyourlife_be::schema::users::dsl::users;
+        let author = users.find(result.author_id).first::<User>(&mut conn)?;
+        Ok(ArticleWithAuthor { article: result, author: author.into() })
     })
     .await
     .map_err(AppError::AsyncTaskError)?
     ?;
 
-    info!("[POST /article] Successfully created new article with ID: {}", new_article.id);
+    info!("[POST /article] Successfully created new article with ID: {}", new_article.article.id);
     debug!("Created article: {:?}", new_article);
     Ok(Json(new_article))
-}
\ No newline at end of file
+}
+
+// Pasangkan artikel dengan penulisnya (urutan sama dengan hasil join)
+fn with_authors(list: Vec<Article>, authors: Vec<User>) -> Vec<ArticleWithAuthor> {
+    list.into_iter()
+        .zip(authors)
+        .map(|(article, author)| ArticleWithAuthor { article, author: author.into() })
+        .collect()
+}
+
+/// Blok Editor.js yang menunjuk item galeri (`data.nft_id`, atau URL
+/// `/media/{id}/...` di `data.file.url`) diberi `alt_text` dan
+/// `long_description` dari item itu. Alt text yang ditulis penulis artikel
+/// di blok tidak ditimpa.
+fn embed_gallery_alt_text(conn: &mut PgPooledConnection, list: &mut [Article]) -> Result<(), AppError> {
+    let ids: Vec<Uuid> = list.iter()
+        .flat_map(|article| content_blocks(&article.content))
+        .filter_map(embedded_item)
+        .collect();
+    if ids.is_empty() {
+        return Ok(());
+    }
+
+    use quoteyourlife_be::schema::nft::dsl::*;
+    let found: HashMap<Uuid, (Option<String>, Option<String>)> = nft
+        .filter(id.eq_any(&ids))
+        .filter(visibility.ne(VisibilityEnum::Private))
+        .select((id, alt_text, long_description))
+        .load::<(Uuid, Option<String>, Option<String>)>(conn)?
+        .into_iter()
+        .map(|(item_id, item_alt, item_description)| (item_id, (item_alt, item_description)))
+        .collect();
+
+    for article in list.iter_mut() {
+        let Some(blocks) = article.content.get_mut("blocks").and_then(Value::as_array_mut) else {
+            continue;
+        };
+        for block in blocks.iter_mut() {
+            let Some((item_alt, item_description)) = embedded_item(block).and_then(|item_id| found.get(&item_id)) else {
+                continue;
+            };
+            let Some(data) = block.get_mut("data").and_then(Value::as_object_mut) else {
+                continue;
+            };
+            let has_own_alt = data.get("alt_text").and_then(Value::as_str).is_some_and(|alt| !alt.trim().is_empty());
+            if !has_own_alt {
+                data.insert("alt_text".to_string(), item_alt.clone().map_or(Value::Null, Value::String));
+            }
+            data.insert("long_description".to_string(), item_description.clone().map_or(Value::Null, Value::String));
+        }
+    }
+    Ok(())
+}
+
+fn content_blocks(content: &Value) -> impl Iterator<Item = &Value> {
+    content.get("blocks")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+}
+
+fn embedded_item(block: &Value) -> Option<Uuid> {
+    let data = block.get("data")?;
+    if let Some(item_id) = data.get("nft_id").and_then(Value::as_str) {
+        return item_id.parse().ok();
+    }
+    let url = data.get("file")?.get("url")?.as_str()?;
+    let (_, path) = url.split_once("/media/")?;
+    path.split('/').next()?.parse().ok()
+}
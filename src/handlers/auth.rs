@@ -1,24 +1,38 @@
-use axum::{Extension, response::IntoResponse};
-use diesel::{ExpressionMethods, RunQueryDsl, query_dsl::methods::FilterDsl};
-use quoteyourlife_be::{db::{PgPool, get_conn}, models::User};
+use axum::{Extension, response::{IntoResponse, Response}};
+use diesel::{Connection, ExpressionMethods, OptionalExtension, RunQueryDsl, SelectableHelper, query_dsl::methods::FilterDsl};
+use quoteyourlife_be::{db::{PgPool, get_conn}, models::{NewLoginHistory, NewUser, SelfUser, User, UserTokenPurposeEnum}};
+use std::net::SocketAddr;
 use tracing::{info, debug, error, warn};
 use axum::{
     Json,
-    http::StatusCode,
+    extract::ConnectInfo,
+    http::{header, HeaderMap, StatusCode},
     http::Request,
     body::Body
 };
 use serde_json::json;
 use serde::Deserialize;
 use bcrypt::{
-    verify
+    hash,
+    verify,
+    DEFAULT_COST
 };
-use crate::handlers::AppError;
+use crate::handlers::{AppError, map_user_conflict};
+use crate::handlers::account::{issue_token, send_verification_email, verification_ttl};
+use crate::handlers::lockout;
 use crate::utils::jwt::create_jwt;
 use crate::utils::jwt::{
     Claims,
-    verify_jwt as verify_jwt_utils
+    authenticate
 };
+use crate::utils::validation;
+use crate::utils::client::{client_ip, user_agent};
+use std::sync::LazyLock;
+
+// Hash pembanding untuk username yang tidak terdaftar
+static DUMMY_HASH: LazyLock<String> = LazyLock::new(|| {
+    hash("quoteyourlife-dummy-password", DEFAULT_COST).expect("Gagal membuat hash dummy")
+});
 
 #[derive(Deserialize)]
 pub struct LoginData {
@@ -26,100 +40,237 @@ pub struct LoginData {
     password: String
 }
 
+#[derive(Deserialize)]
+pub struct RegisterData {
+    username: String,
+    email: String,
+    password: String,
+    full_name: Option<String>
+}
+
+pub async fn register(
+    Extension(pool): Extension<PgPool>,
+    Json(payload): Json<RegisterData>
+) -> Result<impl IntoResponse, AppError> {
+    info!("[POST /auth/register] Received registration request");
+    debug!("Registration attempt for username: {}", payload.username);
+
+    let user_name = validation::validate_username(&payload.username)?;
+    let user_email = validation::validate_email(&payload.email)?;
+    let name = validation::validate_full_name(payload.full_name.as_deref())?;
+    validation::validate_password(&payload.password, &user_name)?;
+
+    // bcrypt sengaja lambat, jangan jalan di thread async
+    let password = payload.password;
+    let hashed = tokio::task::spawn_blocking(move || hash(password, DEFAULT_COST))
+        .await
+        .map_err(AppError::AsyncTaskError)?
+        .map_err(|err| AppError::GeneralError(format!("Gagal memproses password: {}", err)))?;
+
+    let new_user = NewUser {
+        username: user_name,
+        email: user_email,
+        password_hash: hashed,
+        full_name: name,
+    };
+    let (user, verification_token) = tokio::task::spawn_blocking(move || -> Result<_, AppError> {
+        let mut conn = get_conn(&pool)?;
+        conn.transaction::<_, AppError, _>(|conn| {
+            use quoteyourlife_be::schema::users::dsl::*;
+            // Username/email yang sudah dipakai ditangkap constraint UNIQUE -> 409
+            let result = diesel::insert_into(users)
+                .values(&new_user)
+                .returning(User::as_returning())
+                .get_result(conn)
+                .map_err(map_user_conflict)?;
+            let raw_token = issue_token(conn, result.id, UserTokenPurposeEnum::EmailVerification, verification_ttl())?;
+            Ok((result, raw_token))
+        })
+    })
+    .await
+    .map_err(AppError::AsyncTaskError)?
+    ?;
+
+    info!("[POST /auth/register] Registered new user: {} ({})", user.username, user.id);
+    // Akun tetap dibuat walau email gagal terkirim; user bisa minta kirim ulang
+    let message = match send_verification_email(&user, verification_token).await {
+        Ok(()) => "Registrasi berhasil, cek email untuk verifikasi",
+        Err(err) => {
+            warn!("[POST /auth/register] Failed to send verification email to {}: {:?}", user.id, err);
+            "Registrasi berhasil, tapi email verifikasi gagal dikirim. Minta kirim ulang setelah login"
+        }
+    };
+    Ok((
+        StatusCode::CREATED,
+        Json(json!({
+            "status": "success",
+            "message": message,
+            "user": SelfUser::from(user)
+        }))
+    ))
+}
+
+/// Login gagal (username tidak ada atau password salah) selalu dijawab
+/// sama, begitu juga login yang sedang dikunci. Username yang tidak
+/// terdaftar tetap dicocokkan dengan hash dummy supaya waktu responsnya
+/// tidak berbeda.
 pub async fn login(
     Extension(pool): Extension<PgPool>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
     Json(payload): Json<LoginData>
-) -> impl IntoResponse {
+) -> Result<Response, AppError> {
     info!("[POST /auth/login] Received login request");
     debug!("Login attempt for username: {}", payload.username);
     
     let user_name = payload.username;
     let password = payload.password;
-    if user_name == "" || password == "" {
+    if user_name.is_empty() || password.is_empty() {
         warn!("[POST /auth/login] Login failed: Username/password is empty");
         error!("Username/password tidak boleh kosong!");
-        return (
-            StatusCode::BAD_REQUEST,
-            Json(json!({
-                "status": "fail",
-                "message": "Username/password tidak boleh kosong!"
-            }))
-        )
+        return Ok(login_failed(StatusCode::BAD_REQUEST, "Username/password tidak boleh kosong!"));
     }
+    let ip = client_ip(&headers, addr);
 
     let _user_name = user_name.clone();
+    let _ip = ip.clone();
+    let _pool = pool.clone();
     let result = tokio::task::spawn_blocking(move || -> Result<_, AppError>{
-        let mut conn = get_conn(&pool)?;
+        let mut conn = get_conn(&_pool)?;
+        if let Some(wait) = lockout::retry_after(&mut conn, &_user_name, &_ip)? {
+            return Ok(Err(wait));
+        }
         use quoteyourlife_be::schema::users::dsl::*;
-        let results = users.filter(username.eq(&_user_name)).load::<User>(&mut conn)?;
-        Ok(results)
+        let found = users.filter(username.eq(&_user_name))
+            .filter(deleted_at.is_null())
+            .first::<User>(&mut conn)
+            .optional()?;
+        Ok(Ok(found))
     })
         .await
-        .map_err(AppError::AsyncTaskError).unwrap().unwrap();
-    
-    if result.is_empty() {
-        warn!("[POST /auth/login] Login failed: Username not found - {}", user_name);
-        error!("Username tidak ditemukan!");
-        return (
-            StatusCode::NOT_FOUND,
-            Json(json!({
-                "status": "fail",
-                "message": "Username tidak ditemukan!"
-            }))
-        )
+        .map_err(AppError::AsyncTaskError)??;
+    let found = match result {
+        Ok(found) => found,
+        Err(wait) => {
+            warn!("[POST /auth/login] Login rejected: {} or {} is locked for {}s", user_name, ip, wait);
+            return Ok(locked(wait));
+        }
+    };
+
+    // bcrypt sengaja lambat, jangan jalan di thread async
+    let stored_hash = found.as_ref().map_or_else(|| DUMMY_HASH.clone(), |user| user.password_hash.clone());
+    let password_ok = tokio::task::spawn_blocking(move || verify(&password, &stored_hash))
+        .await
+        .map_err(AppError::AsyncTaskError)?
+        .map_err(|err| AppError::GeneralError(format!("Gagal memeriksa password: {}", err)))?
+        && found.is_some();
+    if let Some(user) = &found {
+        record_login(pool.clone(), NewLoginHistory {
+            user_id: user.id,
+            succeeded: password_ok,
+            ip_address: Some(ip.clone()),
+            user_agent: user_agent(&headers),
+        });
     }
-    info!("[POST /auth/login] User found: {}", &result[0].username);
-    debug!("Verifying password for user: {}", &result[0].username);
-    
-    if verify(&password, &result[0].password_hash).expect("Gagal verifikasi") {
-        info!("[POST /auth/login] Login successful for user: {}", &result[0].username);
-        debug!("Generating JWT token for user: {}", &result[0].id);
-
-        let token = tokio::task::spawn_blocking(move || -> Result <String, AppError>{
-            create_jwt(&result[0].id.to_string())
-        }).await
-        .expect("Error pada saat tokenisasi");
-
-        let token = match token {
-            Ok(token) => {
-                info!("[POST /auth/login] JWT token generated successfully for user: {}", user_name);
-                (
-                    StatusCode::ACCEPTED,
-                    Json(json!({
-                        "status": "success",
-                        "message": "Login Berhasil",
-                        "token": &token
-                    }))
-                )
-            },
-            Err(err) => {
-                error!("[POST /auth/login] JWT generation failed: {:?}", err);
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(json!({
-                        "status": "fail",
-                        "message": "Login Gagal, Internal server error"
-                    }))
-                )
-            }
-        };
-        token
-
-    } else {
-        warn!("[POST /auth/login] Login failed: Wrong password for user - {}", user_name);
-        error!("Login Gagal: Password Salah!");
-        return (
-            StatusCode::UNAUTHORIZED,
-            Json(json!({
-                "status": "fail",
-                "message": "Login Gagal: Password Salah!"
-            }))
-        )
+
+    let Some(user) = found.filter(|_| password_ok) else {
+        warn!("[POST /auth/login] Login failed: Wrong username or password - {}", user_name);
+        let _user_name = user_name.clone();
+        let owner = tokio::task::spawn_blocking(move || -> Result<_, AppError> {
+            let mut conn = get_conn(&pool)?;
+            let outcome = lockout::record_failure(&mut conn, &_user_name, &ip)?;
+            let Some(wait) = outcome.username_locked else {
+                return Ok(None);
+            };
+            warn!("[POST /auth/login] Username {} locked for {}s after repeated failures", _user_name, wait);
+            // Dicari juga untuk username yang tidak terdaftar, emailnya saja yang tidak ada
+            use quoteyourlife_be::schema::users::dsl::*;
+            let owner = users.filter(username.eq(&_user_name))
+                .filter(deleted_at.is_null())
+                .first::<User>(&mut conn)
+                .optional()?;
+            Ok(owner.map(|owner| (owner, ip, wait)))
+        })
+        .await
+        .map_err(AppError::AsyncTaskError)??;
+        if let Some((owner, ip, wait)) = owner {
+            tokio::task::spawn_blocking(move || lockout::notify_locked(&owner, &ip, wait));
+        }
+        error!("Login Gagal: Username atau password salah!");
+        return Ok(login_failed(StatusCode::UNAUTHORIZED, "Login Gagal: Username atau password salah!"));
+    };
+
+    info!("[POST /auth/login] Login successful for user: {}", &user.username);
+    debug!("Generating JWT token for user: {}", &user.id);
+    let _pool = pool.clone();
+    let _user_name = user_name.clone();
+    let token = tokio::task::spawn_blocking(move || -> Result <String, AppError>{
+        let mut conn = get_conn(&_pool)?;
+        lockout::clear_username(&mut conn, &_user_name)?;
+        create_jwt(&user.id.to_string(), user.role, user.token_version)
+    }).await
+    .map_err(AppError::AsyncTaskError)?;
+
+    match token {
+        Ok(token) => {
+            info!("[POST /auth/login] JWT token generated successfully for user: {}", user_name);
+            Ok((
+                StatusCode::ACCEPTED,
+                Json(json!({
+                    "status": "success",
+                    "message": "Login Berhasil",
+                    "token": &token
+                }))
+            ).into_response())
+        },
+        Err(err) => {
+            error!("[POST /auth/login] JWT generation failed: {:?}", err);
+            Ok(login_failed(StatusCode::INTERNAL_SERVER_ERROR, "Login Gagal, Internal server error"))
+        }
     }
-    
+}
+
+fn login_failed(status: StatusCode, message: &str) -> Response {
+    (
+        status,
+        Json(json!({
+            "status": "fail",
+            "message": message
+        }))
+    ).into_response()
+}
+
+// Bentuk yang sama untuk lock per username dan per IP
+fn locked(retry_after: i64) -> Response {
+    (
+        StatusCode::TOO_MANY_REQUESTS,
+        [(header::RETRY_AFTER, retry_after.to_string())],
+        Json(json!({
+            "status": "fail",
+            "message": "Login Gagal: Terlalu banyak percobaan, coba lagi nanti",
+            "retry_after": retry_after
+        }))
+    ).into_response()
+}
+
+// Dicatat di background, login tidak perlu menunggu
+fn record_login(pool: PgPool, entry: NewLoginHistory) {
+    tokio::task::spawn_blocking(move || {
+        let result = get_conn(&pool).map_err(AppError::PoolError).and_then(|mut conn| {
+            use quoteyourlife_be::schema::login_history::dsl::*;
+            diesel::insert_into(login_history)
+                .values(&entry)
+                .execute(&mut conn)
+                .map_err(AppError::DatabaseError)
+        });
+        if let Err(err) = result {
+            warn!("Failed to record login history for {}: {:?}", entry.user_id, err);
+        }
+    });
 }
 
 pub async fn verify_jwt(
+    Extension(pool): Extension<PgPool>,
     request: Request<Body>
 ) -> Result<Json<Claims>, AppError> {
     info!("[POST /auth/verify] Received JWT verification request");
@@ -142,12 +293,14 @@ pub async fn verify_jwt(
         })?;
 
     debug!("Verifying JWT token");
-    let user_claims = verify_jwt_utils(token)
+    let user_claims = authenticate(&pool, token)
+        .await
         .map_err(|err| {
             warn!("[POST /auth/verify] JWT verification failed: {:?}", err);
             error!("JWT validation error: {:?}", err);
-            AppError::JWTValidationError(err)
-        })?;
+            err
+        })?
+        .claims;
 
     info!("[POST /auth/verify] JWT verification successful for user_id: {}", user_claims.sub);
     Ok(Json(user_claims))
@@ -0,0 +1,186 @@
+use tracing::{info, debug, warn};
+use axum::{
+    extract::Extension,
+    Json,
+};
+use crate::db::{PgPool, get_conn};
+use quoteyourlife_be::models::{SelfUser, User};
+use quoteyourlife_be::storage::{self, Variant};
+use super::AppError;
+use super::account::unverified_error;
+use diesel::prelude::*;
+use jsonwebtoken::TokenData;
+use tokio::fs;
+use axum_extra::extract::Multipart;
+use uuid::Uuid;
+use crate::utils::jwt::Claims;
+use crate::utils::media;
+
+// File avatar mentah; hasilnya selalu diperkecil ke 512x512
+const MAX_AVATAR_BYTES: usize = 10 * 1024 * 1024;
+
+/// Ganti avatar (multipart, field `avatar`). Gambar diproses seperti upload
+/// galeri (EXIF dibuang, orientasi diterapkan), di-crop persegi lalu
+/// diperkecil. File avatar lama dihapus setelah yang baru tersimpan.
+pub async fn update_my_avatar(
+    Extension(pool): Extension<PgPool>,
+    Extension(token): Extension<TokenData<Claims>>,
+    mut multipart: Multipart
+) -> Result<Json<SelfUser>, AppError> {
+    info!("[PUT /users/me/avatar] Received request to update avatar");
+
+    let owner = token.claims.user_id()?;
+    let mut data = None;
+    while let Some(field) = multipart.next_field().await
+        .map_err(|err| AppError::BadRequest(format!("Body multipart tidak valid: {}", err)))?
+    {
+        let name = field.name().unwrap_or_default().to_string();
+        if name != "avatar" {
+            debug!("Unknown field ignored: {}", name);
+            continue;
+        }
+        let bytes = field.bytes().await
+            .map_err(|err| AppError::BadRequest(format!("Gagal membaca field 'avatar': {}", err)))?;
+        debug!("Avatar received: {} bytes", bytes.len());
+        data = Some(bytes);
+    }
+    let data = data.ok_or(AppError::BadRequest("Field 'avatar' wajib diisi".to_string()))?;
+    if data.len() > MAX_AVATAR_BYTES {
+        return Err(AppError::PayloadTooLarge(format!(
+            "Avatar maksimal {} MB",
+            MAX_AVATAR_BYTES / (1024 * 1024)
+        )));
+    }
+
+    // Avatar tampil di profil publik, sama seperti item galeri yang tidak private
+    let _pool = pool.clone();
+    tokio::task::spawn_blocking(move || -> Result<_, AppError> {
+        let mut conn = get_conn(&_pool)?;
+        use quoteyourlife_be::schema::users::dsl::*;
+        let verified = users.find(owner)
+            .filter(deleted_at.is_null())
+            .select(email_verified_at)
+            .first::<Option<chrono::NaiveDateTime>>(&mut conn)
+            .optional()?
+            .ok_or(AppError::NotFound("User tidak ditemukan".to_string()))?
+            .is_some();
+        if !verified {
+            return Err(unverified_error(owner, "memasang avatar"));
+        }
+        Ok(())
+    })
+    .await
+    .map_err(AppError::AsyncTaskError)?
+    ?;
+
+    let prepared = tokio::task::spawn_blocking(move || {
+        media::sanitize_image(&data).and_then(media::prepare_avatar)
+    })
+    .await
+    .map_err(AppError::AsyncTaskError)?
+    ?;
+
+    let filename = format!("{}.{}", Uuid::new_v4(), prepared.extension);
+    let avatar_dir = storage::avatar_dir();
+    fs::create_dir_all(&avatar_dir).await
+        .map_err(|err| AppError::GeneralError(format!("Gagal membuat folder avatar: {}", err)))?;
+    let written = async {
+        fs::write(avatar_dir.join(&filename), &prepared.data).await?;
+        for rendition in &prepared.renditions {
+            let rendition_name = storage::rendition_filename(&filename, Variant::Original, rendition.extension());
+            fs::write(avatar_dir.join(rendition_name), &rendition.data).await?;
+        }
+        Ok::<_, std::io::Error>(())
+    }
+    .await;
+    if let Err(err) = written {
+        remove_avatar_files(&filename).await;
+        return Err(AppError::GeneralError(format!("Gagal menyimpan avatar: {}", err)));
+    }
+    debug!("Avatar written: {} ({}x{}, {} bytes)", filename, prepared.width, prepared.height, prepared.data.len());
+
+    let new_avatar = filename.clone();
+    let result = tokio::task::spawn_blocking(move || -> Result<_, AppError> {
+        let mut conn = get_conn(&pool)?;
+        conn.transaction::<_, AppError, _>(|conn| {
+            use quoteyourlife_be::schema::users::dsl::*;
+            let previous = users.find(owner)
+                .filter(deleted_at.is_null())
+                .select(avatar)
+                .for_update()
+                .first::<Option<String>>(conn)
+                .optional()?
+                .ok_or(AppError::NotFound("User tidak ditemukan".to_string()))?;
+            let updated = diesel::update(users.find(owner))
+                .set(avatar.eq(&new_avatar))
+                .returning(User::as_returning())
+                .get_result(conn)?;
+            Ok((updated, previous))
+        })
+    })
+    .await
+    .map_err(AppError::AsyncTaskError)
+    .and_then(|result| result);
+    let (user, previous) = match result {
+        Ok(result) => result,
+        Err(err) => {
+            remove_avatar_files(&filename).await;
+            return Err(err);
+        }
+    };
+    if let Some(previous) = previous {
+        remove_avatar_files(&previous).await;
+    }
+
+    info!("[PUT /users/me/avatar] Avatar of {} updated to {}", user.id, filename);
+    Ok(Json(user.into()))
+}
+
+pub async fn delete_my_avatar(
+    Extension(pool): Extension<PgPool>,
+    Extension(token): Extension<TokenData<Claims>>,
+) -> Result<Json<SelfUser>, AppError> {
+    info!("[DELETE /users/me/avatar] Received request to remove avatar");
+
+    let owner = token.claims.user_id()?;
+    let (user, previous) = tokio::task::spawn_blocking(move || -> Result<_, AppError> {
+        let mut conn = get_conn(&pool)?;
+        conn.transaction::<_, AppError, _>(|conn| {
+            use quoteyourlife_be::schema::users::dsl::*;
+            let previous = users.find(owner)
+                .filter(deleted_at.is_null())
+                .select(avatar)
+                .for_update()
+                .first::<Option<String>>(conn)
+                .optional()?
+                .ok_or(AppError::NotFound("User tidak ditemukan".to_string()))?;
+            let updated = diesel::update(users.find(owner))
+                .set(avatar.eq(None::<String>))
+                .returning(User::as_returning())
+                .get_result(conn)?;
+            Ok((updated, previous))
+        })
+    })
+    .await
+    .map_err(AppError::AsyncTaskError)?
+    ?;
+
+    if let Some(previous) = previous {
+        remove_avatar_files(&previous).await;
+    }
+    info!("[DELETE /users/me/avatar] Avatar of {} removed", user.id);
+    Ok(Json(user.into()))
+}
+
+/// Hapus file avatar beserta versi AVIF/WebP-nya; file yang tidak ada dilewati.
+pub async fn remove_avatar_files(filename: &str) {
+    let avatar_dir = storage::avatar_dir();
+    for stored in storage::stored_files(filename) {
+        let file_path = avatar_dir.join(&stored);
+        match fs::remove_file(&file_path).await {
+            Ok(()) => debug!("Removed avatar file: {}", file_path.display()),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+            Err(err) => warn!("Failed to remove avatar file {}: {}", file_path.display(), err),
+        }
+    }
+}
@@ -0,0 +1,531 @@
+use tracing::{info, debug, warn, error};
+use axum::{
+    body::Body,
+    extract::{Extension, Path, Query, Request},
+    http::{header, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use crate::db::{PgPool, PgPooledConnection, get_conn};
+use quoteyourlife_be::models::{
+    Album, Article, DataExport, DataExportView, ExportStatusEnum, LoginHistory, NFT, Quote, SelfUser, User
+};
+use quoteyourlife_be::storage::{self, Variant};
+use super::AppError;
+use super::archive::{safe_filename, with_modified};
+use super::media::SignatureQuery;
+use chrono::{Duration, NaiveDateTime};
+use diesel::prelude::*;
+use jsonwebtoken::TokenData;
+use serde::Serialize;
+use std::env;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path as FilePath, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use tower::ServiceExt;
+use tower_http::services::ServeFile;
+use uuid::Uuid;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+use crate::utils::jwt::Claims;
+use crate::utils::mailer::{self, Email};
+use crate::utils::markdown::editorjs_to_markdown;
+use crate::utils::signed_url;
+
+// Ekspor pending/processing yang heartbeat-nya berhenti selama ini dianggap gagal (mis. server restart)
+const STALE_EXPORT_MINUTES: i64 = 10;
+// Selama diproses, heartbeat_at diperbarui sesering ini
+const HEARTBEAT_INTERVAL_SECS: u64 = 60;
+
+const README: &str = "Ekspor data pribadi QuoteYourLife
+
+profile.json        data akun
+avatar.*            foto profil (kalau ada)
+quotes.json         quote yang kamu tulis (quote sebelum Oktober 2026 tidak tercatat pemiliknya)
+articles.json       artikel, termasuk konten Editor.js (JSON)
+articles/*.md       artikel yang sama dalam format Markdown
+gallery.json        metadata item galeri
+gallery/*           file original item galeri
+albums.json         album beserta urutan itemnya
+login_history.json  riwayat login (berhasil dan gagal), IP dan user agent
+
+QuoteYourLife tidak punya fitur komentar, jadi tidak ada data komentar yang disimpan.
+";
+
+// Album dengan daftar item sesuai urutan
+#[derive(Serialize)]
+struct ExportedAlbum {
+    #[serde(flatten)]
+    album: Album,
+    item_ids: Vec<Uuid>,
+}
+
+// Semua data milik user, dikumpulkan sebelum ZIP ditulis
+struct ExportData {
+    user: User,
+    quotes: Vec<Quote>,
+    articles: Vec<Article>,
+    gallery: Vec<NFT>,
+    albums: Vec<ExportedAlbum>,
+    logins: Vec<LoginHistory>,
+}
+
+/// Mulai ekspor data pribadi. ZIP dibuat di background; status dan link
+/// download-nya dilihat lewat `GET /users/me/exports`.
+pub async fn request_export(
+    Extension(pool): Extension<PgPool>,
+    Extension(token): Extension<TokenData<Claims>>,
+) -> Result<(StatusCode, Json<DataExportView>), AppError> {
+    info!("[POST /users/me/export] Received request for data export");
+
+    let owner = token.claims.user_id()?;
+    let _pool = pool.clone();
+    let export = tokio::task::spawn_blocking(move || -> Result<_, AppError> {
+        let mut conn = get_conn(&_pool)?;
+        expire_exports(&mut conn)?;
+        conn.transaction::<_, AppError, _>(|conn| {
+            // Kunci baris user supaya dua request bersamaan tidak sama-sama lolos cek
+            {
+                use quoteyourlife_be::schema::users::dsl::*;
+                users.find(owner)
+                    .filter(deleted_at.is_null())
+                    .select(id)
+                    .for_update()
+                    .first::<Uuid>(conn)
+                    .optional()?
+                    .ok_or(AppError::NotFound("User tidak ditemukan".to_string()))?;
+            }
+            use quoteyourlife_be::schema::data_exports::dsl::*;
+            let running = data_exports.filter(user_id.eq(owner))
+                .filter(status.eq_any([ExportStatusEnum::Pending, ExportStatusEnum::Processing]))
+                .count()
+                .get_result::<i64>(conn)?;
+            if running > 0 {
+                return Err(AppError::Conflict("Ekspor sebelumnya masih diproses".to_string()));
+            }
+            let created = diesel::insert_into(data_exports)
+                .values(user_id.eq(owner))
+                .returning(DataExport::as_returning())
+                .get_result(conn)?;
+            Ok(created)
+        })
+    })
+    .await
+    .map_err(AppError::AsyncTaskError)?
+    ?;
+
+    let export_id = export.id;
+    tokio::task::spawn_blocking(move || run_export(&pool, export_id, owner));
+
+    info!("[POST /users/me/export] Export {} queued for user {}", export_id, owner);
+    Ok((StatusCode::ACCEPTED, Json(DataExportView { export, download_url: None })))
+}
+
+pub async fn list_exports(
+    Extension(pool): Extension<PgPool>,
+    Extension(token): Extension<TokenData<Claims>>,
+) -> Result<Json<Vec<DataExportView>>, AppError> {
+    info!("[GET /users/me/exports] Received request for data exports");
+
+    let owner = token.claims.user_id()?;
+    let exports = tokio::task::spawn_blocking(move || -> Result<_, AppError> {
+        let mut conn = get_conn(&pool)?;
+        expire_exports(&mut conn)?;
+        use quoteyourlife_be::schema::data_exports::dsl::*;
+        let result = data_exports.filter(user_id.eq(owner))
+            .order(created_at.desc())
+            .select(DataExport::as_select())
+            .load(&mut conn)?;
+        Ok(result)
+    })
+    .await
+    .map_err(AppError::AsyncTaskError)?
+    ?;
+
+    debug!("Found {} export(s) for user {}", exports.len(), owner);
+    let views = exports.into_iter()
+        .map(|export| {
+            let download_url = match (export.status, export.expires_at) {
+                (ExportStatusEnum::Ready, Some(expires)) => {
+                    Some(signed_url::export_path(export.id, expires.and_utc().timestamp()))
+                }
+                _ => None,
+            };
+            DataExportView { export, download_url }
+        })
+        .collect();
+    Ok(Json(views))
+}
+
+/// Download ZIP ekspor. Tanpa JWT, aksesnya lewat signature di URL
+/// (dari `GET /users/me/exports`) yang berlaku sampai ekspor kedaluwarsa.
+pub async fn download_export(
+    Extension(pool): Extension<PgPool>,
+    Path(export_id): Path<Uuid>,
+    Query(params): Query<SignatureQuery>,
+    request: Request
+) -> Result<Response, AppError> {
+    info!("[GET /exports/{}] Received request for export download", export_id);
+
+    let (Some(expires), Some(signature)) = (params.exp, params.sig.as_deref()) else {
+        return Err(AppError::Forbidden("URL ekspor memerlukan signature".to_string()));
+    };
+    if !signed_url::verify_export(export_id, expires, signature) {
+        warn!("[GET /exports/{}] Invalid export signature", export_id);
+        return Err(AppError::Forbidden("Signature URL ekspor tidak valid".to_string()));
+    }
+    if expires < chrono::Utc::now().timestamp() {
+        return Err(AppError::Forbidden("URL ekspor sudah kedaluwarsa".to_string()));
+    }
+
+    let export = tokio::task::spawn_blocking(move || -> Result<_, AppError> {
+        let mut conn = get_conn(&pool)?;
+        use quoteyourlife_be::schema::data_exports::dsl::*;
+        let result = data_exports.find(export_id)
+            .filter(status.eq(ExportStatusEnum::Ready))
+            .select(DataExport::as_select())
+            .first(&mut conn)
+            .optional()?
+            .ok_or(AppError::NotFound("Ekspor tidak ditemukan atau sudah kedaluwarsa".to_string()))?;
+        Ok(result)
+    })
+    .await
+    .map_err(AppError::AsyncTaskError)?
+    ?;
+
+    let file_path = export_file(export.id);
+    if !file_path.exists() {
+        warn!("[GET /exports/{}] Export file missing: {}", export_id, file_path.display());
+        return Err(AppError::NotFound("Ekspor tidak ditemukan atau sudah kedaluwarsa".to_string()));
+    }
+    let mut response = ServeFile::new(&file_path)
+        .oneshot(request)
+        .await
+        .map_err(|err| AppError::GeneralError(format!("Gagal membaca file ekspor: {}", err)))?
+        .map(Body::new)
+        .into_response();
+    let download_name = format!("quoteyourlife-export-{}.zip", export.created_at.format("%Y%m%d"));
+    let response_headers = response.headers_mut();
+    response_headers.insert(
+        header::CONTENT_DISPOSITION,
+        HeaderValue::from_str(&format!("attachment; filename=\"{}\"", download_name))
+            .expect("nama file hanya ASCII"),
+    );
+    response_headers.insert(header::CACHE_CONTROL, HeaderValue::from_static("private, no-store"));
+    debug!("Serving export {} with status {}", export.id, response.status());
+    Ok(response)
+}
+
+// Jalan di thread blocking; hasil akhirnya dicatat di baris data_exports
+fn run_export(pool: &PgPool, export_id: Uuid, owner: Uuid) {
+    let result = get_conn(pool)
+        .map_err(AppError::PoolError)
+        .and_then(|mut conn| {
+            start_export(&mut conn, export_id)?;
+            // Heartbeat berhenti sendiri begitu `_running` di-drop (proses selesai atau gagal)
+            let (_running, stopped) = mpsc::channel::<()>();
+            let heartbeat_pool = pool.clone();
+            thread::spawn(move || heartbeat(&heartbeat_pool, export_id, stopped));
+            let (size, user) = build_export(&mut conn, export_id, owner)?;
+            finish_export(&mut conn, export_id, size, &user)
+        });
+    if let Err(err) = result {
+        error!("Data export {} failed: {:?}", export_id, err);
+        let _ = fs::remove_file(export_file(export_id).with_extension("zip.part"));
+        let marked = get_conn(pool).map_err(AppError::PoolError).and_then(|mut conn| {
+            use quoteyourlife_be::schema::data_exports::dsl::*;
+            diesel::update(
+                data_exports.find(export_id)
+                    .filter(status.eq_any([ExportStatusEnum::Pending, ExportStatusEnum::Processing]))
+            )
+                .set((
+                    status.eq(ExportStatusEnum::Failed),
+                    error.eq("Gagal membuat arsip ekspor, silakan coba lagi"),
+                    completed_at.eq(chrono::Utc::now().naive_utc()),
+                ))
+                .execute(&mut conn)
+                .map_err(AppError::DatabaseError)
+        });
+        if let Err(err) = marked {
+            error!("Failed to mark export {} as failed: {:?}", export_id, err);
+        }
+    }
+}
+
+// Pending -> processing; ekspor yang sudah ditandai gagal tidak dijalankan lagi
+fn start_export(conn: &mut PgPooledConnection, export_id: Uuid) -> Result<(), AppError> {
+    use quoteyourlife_be::schema::data_exports::dsl::*;
+    let started = diesel::update(data_exports.find(export_id).filter(status.eq(ExportStatusEnum::Pending)))
+        .set((status.eq(ExportStatusEnum::Processing), heartbeat_at.eq(chrono::Utc::now().naive_utc())))
+        .execute(conn)?;
+    if started == 0 {
+        return Err(AppError::Conflict(format!("Ekspor {} sudah tidak pending", export_id)));
+    }
+    Ok(())
+}
+
+fn heartbeat(pool: &PgPool, export_id: Uuid, stopped: mpsc::Receiver<()>) {
+    while let Err(RecvTimeoutError::Timeout) = stopped.recv_timeout(std::time::Duration::from_secs(HEARTBEAT_INTERVAL_SECS)) {
+        let touched = get_conn(pool).map_err(AppError::PoolError).and_then(|mut conn| {
+            use quoteyourlife_be::schema::data_exports::dsl::*;
+            diesel::update(data_exports.find(export_id).filter(status.eq(ExportStatusEnum::Processing)))
+                .set(heartbeat_at.eq(chrono::Utc::now().naive_utc()))
+                .execute(&mut conn)
+                .map_err(AppError::DatabaseError)
+        });
+        if let Err(err) = touched {
+            warn!("Failed to update heartbeat of export {}: {:?}", export_id, err);
+        }
+    }
+}
+
+fn finish_export(conn: &mut PgPooledConnection, export_id: Uuid, size: i64, user: &User) -> Result<(), AppError> {
+    let now = chrono::Utc::now().naive_utc();
+    let expiry = now + export_ttl();
+    let finished = {
+        use quoteyourlife_be::schema::data_exports::dsl::*;
+        diesel::update(data_exports.find(export_id).filter(status.eq(ExportStatusEnum::Processing)))
+            .set((
+                status.eq(ExportStatusEnum::Ready),
+                size_bytes.eq(size),
+                completed_at.eq(now),
+                expires_at.eq(expiry),
+            ))
+            .execute(conn)?
+    };
+    // Sudah ditandai gagal selagi diproses; arsipnya tidak boleh jadi bisa diunduh
+    if finished == 0 {
+        let _ = fs::remove_file(export_file(export_id));
+        return Err(AppError::Conflict(format!("Ekspor {} sudah tidak diproses", export_id)));
+    }
+    info!("Data export {} ready ({} bytes)", export_id, size);
+
+    // Gagal kirim email tidak membatalkan ekspor, link tetap ada di daftar ekspor
+    let email = Email {
+        to: user.email.clone(),
+        subject: "Ekspor data QuoteYourLife sudah siap".to_string(),
+        body: format!(
+            "Halo {},\n\nEkspor data yang kamu minta sudah siap. Unduh dari halaman akun sebelum {} UTC, setelah itu file-nya dihapus.\n",
+            user.username,
+            expiry.format("%Y-%m-%d %H:%M")
+        ),
+    };
+    if let Err(err) = mailer::mailer().and_then(|mailer| mailer.send(&email)) {
+        warn!("Failed to send export notification for {}: {:?}", export_id, err);
+    }
+    Ok(())
+}
+
+fn build_export(conn: &mut PgPooledConnection, export_id: Uuid, owner: Uuid) -> Result<(i64, User), AppError> {
+    let data = load_export_data(conn, owner)?;
+    debug!(
+        "Exporting {} quote(s), {} article(s), {} gallery item(s) for {}",
+        data.quotes.len(),
+        data.articles.len(),
+        data.gallery.len(),
+        owner
+    );
+
+    let dir = export_dir();
+    fs::create_dir_all(&dir)
+        .map_err(|err| AppError::GeneralError(format!("Gagal membuat EXPORT_DIR: {}", err)))?;
+    // Ditulis ke .part dulu supaya file yang setengah jadi tidak pernah bisa diunduh
+    let final_path = export_file(export_id);
+    let part_path = final_path.with_extension("zip.part");
+    let file = File::create(&part_path)
+        .map_err(|err| AppError::GeneralError(format!("Gagal membuat file ekspor: {}", err)))?;
+    write_export(file, &data)
+        .map_err(|err| AppError::GeneralError(format!("Gagal menulis file ekspor: {}", err)))?;
+    fs::rename(&part_path, &final_path)
+        .map_err(|err| AppError::GeneralError(format!("Gagal menyimpan file ekspor: {}", err)))?;
+    let size = fs::metadata(&final_path)
+        .map_err(|err| AppError::GeneralError(format!("Gagal membaca file ekspor: {}", err)))?
+        .len();
+    Ok((size as i64, data.user))
+}
+
+fn load_export_data(conn: &mut PgPooledConnection, owner: Uuid) -> Result<ExportData, AppError> {
+    let user = {
+        use quoteyourlife_be::schema::users::dsl::*;
+        users.find(owner).first::<User>(conn)?
+    };
+    let quotes = {
+        use quoteyourlife_be::schema::quotes::dsl::*;
+        quotes.filter(user_id.eq(owner)).order(created_at.asc()).load::<Quote>(conn)?
+    };
+    let articles = {
+        use quoteyourlife_be::schema::articles::dsl::*;
+        articles.filter(author_id.eq(owner)).order(created_at.asc()).load::<Article>(conn)?
+    };
+    let gallery = {
+        use quoteyourlife_be::schema::nft::dsl::*;
+        nft.filter(owner_id.eq(owner)).order(created_at.asc()).load::<NFT>(conn)?
+    };
+    let owned_albums = {
+        use quoteyourlife_be::schema::albums::dsl::*;
+        albums.filter(owner_id.eq(owner)).order(created_at.asc()).load::<Album>(conn)?
+    };
+    let album_ids: Vec<Uuid> = owned_albums.iter().map(|album| album.id).collect();
+    let memberships = {
+        use quoteyourlife_be::schema::album_items::dsl::*;
+        album_items.filter(album_id.eq_any(&album_ids))
+            .order((album_id, position.asc()))
+            .select((album_id, nft_id))
+            .load::<(Uuid, Uuid)>(conn)?
+    };
+    let albums = owned_albums.into_iter()
+        .map(|album| {
+            let item_ids = memberships.iter()
+                .filter(|(album_id, _)| *album_id == album.id)
+                .map(|(_, nft_id)| *nft_id)
+                .collect();
+            ExportedAlbum { album, item_ids }
+        })
+        .collect();
+    let logins = {
+        use quoteyourlife_be::schema::login_history::dsl::*;
+        login_history.filter(user_id.eq(owner)).order(created_at.desc()).load::<LoginHistory>(conn)?
+    };
+    Ok(ExportData { user, quotes, articles, gallery, albums, logins })
+}
+
+fn write_export(file: File, data: &ExportData) -> zip::result::ZipResult<()> {
+    let mut zip = ZipWriter::new(file);
+    // Tanpa kompresi, sama seperti arsip galeri
+    let options = with_modified(
+        SimpleFileOptions::default().compression_method(CompressionMethod::Stored),
+        chrono::Utc::now().naive_utc(),
+    );
+
+    zip.start_file("README.txt", options)?;
+    zip.write_all(README.as_bytes())?;
+    write_json(&mut zip, options, "profile.json", &SelfUser::from(data.user.clone()))?;
+    if let Some(avatar) = &data.user.avatar {
+        let extension = avatar.rsplit_once('.').map(|(_, ext)| ext).unwrap_or("bin");
+        add_file(&mut zip, options, &format!("avatar.{}", extension), &storage::avatar_dir().join(avatar))?;
+    }
+    write_json(&mut zip, options, "quotes.json", &data.quotes)?;
+    write_json(&mut zip, options, "articles.json", &data.articles)?;
+    for (index, article) in data.articles.iter().enumerate() {
+        let name = format!("articles/{:03}-{}.md", index + 1, safe_filename(&article.slug));
+        zip.start_file(name, options)?;
+        zip.write_all(article_markdown(article).as_bytes())?;
+    }
+    write_json(&mut zip, options, "gallery.json", &data.gallery)?;
+
+    let public_dir = storage::public_dir();
+    for item in &data.gallery {
+        let path = public_dir.join(storage::variant_filename(&item.filename, Variant::Original));
+        let extension = item.filename.rsplit_once('.').map(|(_, ext)| ext).unwrap_or("bin");
+        add_file(&mut zip, options, &format!("gallery/{}.{}", item.id, extension), &path)?;
+    }
+    write_json(&mut zip, options, "albums.json", &data.albums)?;
+    write_json(&mut zip, options, "login_history.json", &data.logins)?;
+    zip.finish()?.sync_all()?;
+    Ok(())
+}
+
+// File yang hilang dari disk dilewati, ekspor tetap jalan
+fn add_file(
+    zip: &mut ZipWriter<File>,
+    options: SimpleFileOptions,
+    name: &str,
+    path: &FilePath
+) -> zip::result::ZipResult<()> {
+    let mut source = match File::open(path) {
+        Ok(source) => source,
+        Err(err) => {
+            warn!("Skipping {} in export: {}", path.display(), err);
+            return Ok(());
+        }
+    };
+    let size = source.metadata()?.len();
+    zip.start_file(name, options.large_file(size >= u32::MAX as u64))?;
+    io::copy(&mut source, zip)?;
+    Ok(())
+}
+
+fn write_json<T: Serialize>(
+    zip: &mut ZipWriter<File>,
+    options: SimpleFileOptions,
+    name: &str,
+    value: &T
+) -> zip::result::ZipResult<()> {
+    zip.start_file(name, options)?;
+    serde_json::to_writer_pretty(&mut *zip, value).map_err(io::Error::from)?;
+    Ok(())
+}
+
+// Front matter (string dalam format JSON juga valid YAML) + konten Markdown
+fn article_markdown(article: &Article) -> String {
+    let quoted = |value: &str| serde_json::to_string(value).unwrap_or_default();
+    let timestamp = |value: Option<NaiveDateTime>| value
+        .map(|value| value.format("%Y-%m-%dT%H:%M:%SZ").to_string())
+        .unwrap_or_else(|| "null".to_string());
+    format!(
+        "---\ntitle: {}\nslug: {}\nstatus: {:?}\ncreated_at: {}\npublished_at: {}\n---\n\n# {}\n\n{}",
+        quoted(&article.title),
+        quoted(&article.slug),
+        article.status,
+        timestamp(Some(article.created_at)),
+        timestamp(article.published_at),
+        article.title,
+        editorjs_to_markdown(&article.content)
+    )
+}
+
+/// Ekspor yang lewat masa berlakunya: file dihapus, status jadi expired.
+/// Ekspor yang macet (server restart di tengah proses, heartbeat berhenti)
+/// ditandai gagal.
+fn expire_exports(conn: &mut PgPooledConnection) -> Result<(), AppError> {
+    use quoteyourlife_be::schema::data_exports::dsl::*;
+    let now = chrono::Utc::now().naive_utc();
+    let expired = data_exports.filter(status.eq(ExportStatusEnum::Ready))
+        .filter(expires_at.lt(now))
+        .select(id)
+        .load::<Uuid>(conn)?;
+    for export_id in &expired {
+        if let Err(err) = fs::remove_file(export_file(*export_id)) && err.kind() != io::ErrorKind::NotFound {
+            warn!("Failed to delete expired export {}: {}", export_id, err);
+        }
+    }
+    if !expired.is_empty() {
+        diesel::update(data_exports.filter(id.eq_any(&expired)))
+            .set(status.eq(ExportStatusEnum::Expired))
+            .execute(conn)?;
+        debug!("Expired {} data export(s)", expired.len());
+    }
+
+    diesel::update(
+        data_exports.filter(status.eq_any([ExportStatusEnum::Pending, ExportStatusEnum::Processing]))
+            .filter(heartbeat_at.lt(now - Duration::minutes(STALE_EXPORT_MINUTES)))
+    )
+    .set((
+        status.eq(ExportStatusEnum::Failed),
+        error.eq("Ekspor terhenti, silakan coba lagi"),
+        completed_at.eq(now),
+    ))
+    .execute(conn)?;
+    Ok(())
+}
+
+// EXPORT_DIR, default "exports"
+fn export_dir() -> PathBuf {
+    PathBuf::from(env::var("EXPORT_DIR").unwrap_or_else(|_| "exports".to_string()))
+}
+
+fn export_file(export_id: Uuid) -> PathBuf {
+    export_dir().join(format!("{}.zip", export_id))
+}
+
+// EXPORT_TTL_HOURS, default 48 jam
+fn export_ttl() -> Duration {
+    let hours = env::var("EXPORT_TTL_HOURS")
+        .ok()
+        .and_then(|raw| raw.trim().parse::<i64>().ok())
+        .filter(|hours| *hours > 0)
+        .unwrap_or(48);
+    Duration::hours(hours)
+}
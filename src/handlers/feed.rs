@@ -0,0 +1,206 @@
+use tracing::{info, debug};
+use axum::{
+    extract::{Extension, Query},
+    Json,
+};
+use crate::db::{PgPool, PgPooledConnection, get_conn};
+use quoteyourlife_be::models::{
+    ArticleStatusEnum, ArticleSummary, FeedContent, FeedItem, NFT, Page, PublicUser, Quote, User, VisibilityEnum
+};
+use super::AppError;
+use diesel::prelude::*;
+use jsonwebtoken::TokenData;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use uuid::Uuid;
+use crate::utils::cursor::{Cursor, PageQuery};
+use crate::utils::jwt::Claims;
+
+// Urutan antar sumber kalau waktunya sama persis, bagian dari cursor
+const QUOTE_RANK: u8 = 0;
+const ARTICLE_RANK: u8 = 1;
+const GALLERY_RANK: u8 = 2;
+
+/// Quote, artikel published dan item galeri publik dari user yang di-follow,
+/// digabung dan diurutkan dari yang terbaru. Tiap sumber diambil maksimal
+/// `limit + 1` baris setelah cursor, lalu digabung di sini.
+pub async fn get_feed(
+    Extension(pool): Extension<PgPool>,
+    Extension(token): Extension<TokenData<Claims>>,
+    Query(params): Query<PageQuery>,
+) -> Result<Json<Page<FeedItem>>, AppError> {
+    info!("[GET /feed] Received request for home feed");
+
+    let viewer = token.claims.user_id()?;
+    let limit = params.limit();
+    let cursor = params.cursor()?;
+    let (mut items, fetched) = tokio::task::spawn_blocking(move || -> Result<_, AppError> {
+        let mut conn = get_conn(&pool)?;
+        let authors = load_followed(&mut conn, viewer)?;
+        if authors.is_empty() {
+            return Ok((Vec::new(), 0));
+        }
+        let author_ids: Vec<Uuid> = authors.keys().copied().collect();
+        let author = |owner: Uuid| authors.get(&owner).cloned();
+
+        let mut items = Vec::new();
+        for quote in load_quotes(&mut conn, &author_ids, cursor, limit)? {
+            if let Some(user) = quote.user_id.and_then(author) {
+                items.push(FeedItem { posted_at: quote.created_at, user, content: FeedContent::Quote(quote) });
+            }
+        }
+        for article in load_articles(&mut conn, &author_ids, cursor, limit)? {
+            if let (Some(user), Some(posted_at)) = (author(article.author_id), article.published_at) {
+                items.push(FeedItem { posted_at, user, content: FeedContent::Article(article) });
+            }
+        }
+        for item in load_gallery(&mut conn, &author_ids, cursor, limit)? {
+            if let Some(user) = item.owner_id.and_then(author) {
+                items.push(FeedItem { posted_at: item.created_at, user, content: FeedContent::Gallery(Box::new(item)) });
+            }
+        }
+        let fetched = items.len();
+        Ok((items, fetched))
+    })
+    .await
+    .map_err(AppError::AsyncTaskError)?
+    ?;
+
+    items.sort_by(|a, b| compare(&feed_cursor(b), &feed_cursor(a)));
+    items.truncate(limit as usize);
+    // Ada sisa kalau gabungan ketiga sumber lebih dari satu halaman
+    let next_cursor = (fetched as i64 > limit)
+        .then(|| items.last().map(|last| feed_cursor(last).encode()))
+        .flatten();
+    debug!("Feed for {}: {} item(s), more: {}", viewer, items.len(), next_cursor.is_some());
+    Ok(Json(Page { items, next_cursor }))
+}
+
+// User aktif yang di-follow, beserta profil publiknya
+fn load_followed(conn: &mut PgPooledConnection, viewer: Uuid) -> Result<HashMap<Uuid, PublicUser>, AppError> {
+    use quoteyourlife_be::schema::{follows, users};
+    let followed = follows::table
+        .inner_join(users::table.on(users::id.eq(follows::followee_id)))
+        .filter(follows::follower_id.eq(viewer))
+        .filter(users::deleted_at.is_null())
+        .select(User::as_select())
+        .load::<User>(conn)?;
+    Ok(followed.into_iter().map(|user| (user.id, PublicUser::from(user))).collect())
+}
+
+fn load_quotes(
+    conn: &mut PgPooledConnection,
+    author_ids: &[Uuid],
+    cursor: Option<Cursor>,
+    limit: i64
+) -> Result<Vec<Quote>, AppError> {
+    use quoteyourlife_be::schema::quotes::dsl::*;
+    let mut query = quotes.into_boxed().filter(user_id.eq_any(author_ids.iter().copied().map(Some)));
+    if let Some(cursor) = cursor {
+        query = match QUOTE_RANK.cmp(&cursor.rank) {
+            Ordering::Less => query.filter(created_at.le(cursor.at)),
+            Ordering::Equal => query.filter(created_at.lt(cursor.at).or(created_at.eq(cursor.at).and(id.lt(cursor.id)))),
+            Ordering::Greater => query.filter(created_at.lt(cursor.at)),
+        };
+    }
+    Ok(query.order((created_at.desc(), id.desc())).limit(limit + 1).load::<Quote>(conn)?)
+}
+
+fn load_articles(
+    conn: &mut PgPooledConnection,
+    author_ids: &[Uuid],
+    cursor: Option<Cursor>,
+    limit: i64
+) -> Result<Vec<ArticleSummary>, AppError> {
+    use quoteyourlife_be::schema::articles::dsl::*;
+    let mut query = articles.into_boxed()
+        .filter(author_id.eq_any(author_ids))
+        .filter(status.eq(ArticleStatusEnum::Published))
+        .filter(deleted_at.is_null())
+        .filter(published_at.is_not_null());
+    if let Some(cursor) = cursor {
+        query = match ARTICLE_RANK.cmp(&cursor.rank) {
+            Ordering::Less => query.filter(published_at.le(cursor.at)),
+            Ordering::Equal => query.filter(published_at.lt(cursor.at).or(published_at.eq(cursor.at).and(id.lt(cursor.id)))),
+            Ordering::Greater => query.filter(published_at.lt(cursor.at)),
+        };
+    }
+    Ok(query
+        .order((published_at.desc(), id.desc()))
+        .limit(limit + 1)
+        .select(ArticleSummary::as_select())
+        .load(conn)?)
+}
+
+// Hanya item publik; unlisted dan private tidak pernah masuk feed
+fn load_gallery(
+    conn: &mut PgPooledConnection,
+    author_ids: &[Uuid],
+    cursor: Option<Cursor>,
+    limit: i64
+) -> Result<Vec<NFT>, AppError> {
+    use quoteyourlife_be::schema::nft::dsl::*;
+    let mut query = nft.into_boxed()
+        .filter(owner_id.eq_any(author_ids.iter().copied().map(Some)))
+        .filter(visibility.eq(VisibilityEnum::Public));
+    if let Some(cursor) = cursor {
+        query = match GALLERY_RANK.cmp(&cursor.rank) {
+            Ordering::Less => query.filter(created_at.le(cursor.at)),
+            Ordering::Equal => query.filter(created_at.lt(cursor.at).or(created_at.eq(cursor.at).and(id.lt(cursor.id)))),
+            Ordering::Greater => query.filter(created_at.lt(cursor.at)),
+        };
+    }
+    Ok(query.order((created_at.desc(), id.desc())).limit(limit + 1).load::<NFT>(conn)?)
+}
+
+fn feed_cursor(item: &FeedItem) -> Cursor {
+    let (rank, id) = match &item.content {
+        FeedContent::Quote(quote) => (QUOTE_RANK, quote.id),
+        FeedContent::Article(article) => (ARTICLE_RANK, article.id),
+        FeedContent::Gallery(item) => (GALLERY_RANK, item.id),
+    };
+    Cursor { at: item.posted_at, rank, id }
+}
+
+// Urutan naik (waktu, rank, id); feed memakai kebalikannya
+fn compare(a: &Cursor, b: &Cursor) -> Ordering {
+    a.at.cmp(&b.at)
+        .then(a.rank.cmp(&b.rank))
+        .then(a.id.cmp(&b.id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn cursor(second: u32, rank: u8, id: u128) -> Cursor {
+        Cursor {
+            at: NaiveDate::from_ymd_opt(2024, 5, 17).unwrap().and_hms_opt(8, 30, second).unwrap(),
+            rank,
+            id: Uuid::from_u128(id),
+        }
+    }
+
+    #[test]
+    fn compares_time_before_rank_and_id() {
+        assert_eq!(compare(&cursor(1, GALLERY_RANK, 9), &cursor(2, QUOTE_RANK, 1)), Ordering::Less);
+        assert_eq!(compare(&cursor(1, QUOTE_RANK, 9), &cursor(1, ARTICLE_RANK, 1)), Ordering::Less);
+        assert_eq!(compare(&cursor(1, ARTICLE_RANK, 1), &cursor(1, ARTICLE_RANK, 2)), Ordering::Less);
+        assert_eq!(compare(&cursor(1, ARTICLE_RANK, 2), &cursor(1, ARTICLE_RANK, 2)), Ordering::Equal);
+    }
+
+    #[test]
+    fn feed_order_is_newest_first_with_gallery_before_quotes_on_ties() {
+        let mut cursors = [
+            cursor(1, QUOTE_RANK, 1),
+            cursor(2, QUOTE_RANK, 2),
+            cursor(2, GALLERY_RANK, 3),
+            cursor(2, ARTICLE_RANK, 4),
+            cursor(2, QUOTE_RANK, 5),
+        ];
+        cursors.sort_by(|a, b| compare(b, a));
+        let ids: Vec<u128> = cursors.iter().map(|c| c.id.as_u128()).collect();
+        assert_eq!(ids, vec![3, 4, 5, 2, 1]);
+    }
+}
@@ -0,0 +1,159 @@
+use tracing::{info, debug};
+use axum::{
+    extract::{Extension, Path, Query},
+    http::StatusCode,
+    Json,
+};
+use crate::db::{PgPool, PgPooledConnection, get_conn};
+use quoteyourlife_be::models::{FollowEntry, NewFollow, Page, PublicUser, User};
+use super::AppError;
+use diesel::prelude::*;
+use jsonwebtoken::TokenData;
+use uuid::Uuid;
+use crate::utils::cursor::{Cursor, PageQuery};
+use crate::utils::jwt::Claims;
+
+pub async fn follow_user(
+    Extension(pool): Extension<PgPool>,
+    Extension(token): Extension<TokenData<Claims>>,
+    Path(user_name): Path<String>,
+) -> Result<StatusCode, AppError> {
+    info!("[POST /users/{}/follow] Received follow request", user_name);
+
+    let follower = token.claims.user_id()?;
+    tokio::task::spawn_blocking(move || -> Result<_, AppError> {
+        let mut conn = get_conn(&pool)?;
+        let target = find_followable(&mut conn, &user_name)?;
+        if target == follower {
+            return Err(AppError::BadRequest("Tidak bisa mem-follow diri sendiri".to_string()));
+        }
+        use quoteyourlife_be::schema::follows::dsl::*;
+        // Follow ulang tidak mengubah apa pun
+        diesel::insert_into(follows)
+            .values(&NewFollow { follower_id: follower, followee_id: target })
+            .on_conflict_do_nothing()
+            .execute(&mut conn)?;
+        Ok(())
+    })
+    .await
+    .map_err(AppError::AsyncTaskError)?
+    ?;
+
+    debug!("User {} now follows someone", follower);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn unfollow_user(
+    Extension(pool): Extension<PgPool>,
+    Extension(token): Extension<TokenData<Claims>>,
+    Path(user_name): Path<String>,
+) -> Result<StatusCode, AppError> {
+    info!("[DELETE /users/{}/follow] Received unfollow request", user_name);
+
+    let follower = token.claims.user_id()?;
+    tokio::task::spawn_blocking(move || -> Result<_, AppError> {
+        let mut conn = get_conn(&pool)?;
+        let target = find_followable(&mut conn, &user_name)?;
+        use quoteyourlife_be::schema::follows::dsl::*;
+        diesel::delete(follows.find((follower, target)))
+            .execute(&mut conn)?;
+        Ok(())
+    })
+    .await
+    .map_err(AppError::AsyncTaskError)?
+    ?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn get_followers(
+    Extension(pool): Extension<PgPool>,
+    Path(user_name): Path<String>,
+    Query(params): Query<PageQuery>,
+) -> Result<Json<Page<FollowEntry>>, AppError> {
+    info!("[GET /users/{}/followers] Received request for followers", user_name);
+    load_follow_page(pool, user_name, params, Direction::Followers).await
+}
+
+pub async fn get_following(
+    Extension(pool): Extension<PgPool>,
+    Path(user_name): Path<String>,
+    Query(params): Query<PageQuery>,
+) -> Result<Json<Page<FollowEntry>>, AppError> {
+    info!("[GET /users/{}/following] Received request for followed users", user_name);
+    load_follow_page(pool, user_name, params, Direction::Following).await
+}
+
+#[derive(Clone, Copy)]
+enum Direction {
+    Followers,
+    Following,
+}
+
+// Urut dari follow terbaru; akun yang sudah dihapus tidak ikut ditampilkan
+async fn load_follow_page(
+    pool: PgPool,
+    user_name: String,
+    params: PageQuery,
+    direction: Direction,
+) -> Result<Json<Page<FollowEntry>>, AppError> {
+    let limit = params.limit();
+    let cursor = params.cursor()?;
+    let rows = tokio::task::spawn_blocking(move || -> Result<_, AppError> {
+        let mut conn = get_conn(&pool)?;
+        let target = find_followable(&mut conn, &user_name)?;
+        use quoteyourlife_be::schema::follows;
+        use quoteyourlife_be::schema::users;
+        // Kolom join berbeda tipe untuk tiap arah, jadi query-nya dibentuk dua kali
+        macro_rules! page_query {
+            ($own_side:expr, $other_side:expr) => {{
+                let mut query = follows::table
+                    .inner_join(users::table.on(users::id.eq($other_side)))
+                    .filter($own_side.eq(target))
+                    .filter(users::deleted_at.is_null())
+                    .into_boxed();
+                if let Some(cursor) = cursor {
+                    query = query.filter(
+                        follows::created_at.lt(cursor.at)
+                            .or(follows::created_at.eq(cursor.at).and(users::id.lt(cursor.id)))
+                    );
+                }
+                query
+                    .order((follows::created_at.desc(), users::id.desc()))
+                    .limit(limit + 1)
+                    .select((User::as_select(), follows::created_at))
+                    .load::<(User, chrono::NaiveDateTime)>(&mut conn)?
+            }};
+        }
+        let result = match direction {
+            Direction::Followers => page_query!(follows::followee_id, follows::follower_id),
+            Direction::Following => page_query!(follows::follower_id, follows::followee_id),
+        };
+        Ok(result)
+    })
+    .await
+    .map_err(AppError::AsyncTaskError)?
+    ?;
+
+    debug!("Loaded {} follow row(s)", rows.len());
+    let has_more = rows.len() as i64 > limit;
+    let items: Vec<FollowEntry> = rows.into_iter()
+        .take(limit as usize)
+        .map(|(user, followed_at)| FollowEntry { user: PublicUser::from(user), followed_at })
+        .collect();
+    let next_cursor = has_more
+        .then(|| items.last().map(|last| Cursor { at: last.followed_at, rank: 0, id: last.user.id }.encode()))
+        .flatten();
+    Ok(Json(Page { items, next_cursor }))
+}
+
+// User aktif berdasarkan username
+fn find_followable(conn: &mut PgPooledConnection, user_name: &str) -> Result<Uuid, AppError> {
+    use quoteyourlife_be::schema::users::dsl::*;
+    users.filter(username.eq(user_name))
+        .filter(deleted_at.is_null())
+        .select(id)
+        .first::<Uuid>(conn)
+        .optional()?
+        .ok_or(AppError::NotFound(format!("User {} tidak ditemukan", user_name)))
+}
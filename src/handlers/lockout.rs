@@ -0,0 +1,205 @@
+use tracing::{info, debug, warn};
+use axum::{
+    extract::{Extension, Path},
+    http::StatusCode,
+};
+use chrono::{Duration, NaiveDateTime};
+use crate::db::{PgPool, PgPooledConnection, get_conn};
+use quoteyourlife_be::models::{LoginThrottle, LoginThrottleScopeEnum, User};
+use super::AppError;
+use diesel::prelude::*;
+use std::env;
+use crate::utils::mailer::{self, Email};
+
+// Hitungan gagal direset kalau tidak ada percobaan selama ini setelah lock terakhir berakhir
+const FAILURE_WINDOW_MINUTES: i64 = 15;
+// Batas atas durasi lock, seberapa pun banyak percobaan gagalnya
+const MAX_LOCKOUT_SECS: i64 = 60 * 60;
+// Panjang kolom subject
+const MAX_SUBJECT_LENGTH: usize = 255;
+
+/// Hasil mencatat satu login gagal.
+pub struct FailureOutcome {
+    /// Durasi lock (detik) kalau username baru saja terkunci untuk pertama
+    /// kali dalam jendela ini; pemilik akun (kalau ada) perlu diberi tahu.
+    pub username_locked: Option<i64>,
+}
+
+/// Sisa waktu lock (dibulatkan ke atas, dalam detik) untuk username atau IP
+/// ini; `None` kalau keduanya tidak sedang terkunci.
+pub fn retry_after(conn: &mut PgPooledConnection, user_name: &str, ip: &str) -> Result<Option<i64>, AppError> {
+    use quoteyourlife_be::schema::login_throttles::dsl::*;
+    let now = chrono::Utc::now().naive_utc();
+    let until = login_throttles
+        .filter(
+            scope.eq(LoginThrottleScopeEnum::Username).and(subject.eq(throttle_subject(user_name)))
+                .or(scope.eq(LoginThrottleScopeEnum::Ip).and(subject.eq(throttle_subject(ip))))
+        )
+        .filter(locked_until.gt(now))
+        .select(diesel::dsl::max(locked_until))
+        .first::<Option<NaiveDateTime>>(conn)?;
+    Ok(until.map(|until| seconds_until(now, until)))
+}
+
+/// Catat login gagal untuk username dan IP sekaligus. Setelah batasnya
+/// tercapai, subject dikunci dengan durasi yang berlipat dua setiap
+/// kegagalan berikutnya.
+pub fn record_failure(conn: &mut PgPooledConnection, user_name: &str, ip: &str) -> Result<FailureOutcome, AppError> {
+    conn.transaction::<_, AppError, _>(|conn| {
+        prune(conn)?;
+        let by_username = bump(conn, LoginThrottleScopeEnum::Username, user_name, max_failures())?;
+        let by_ip = bump(conn, LoginThrottleScopeEnum::Ip, ip, max_failures_per_ip())?;
+        if by_ip.locked_until.is_some() && by_ip.failures == max_failures_per_ip() {
+            warn!("Login from {} locked after {} failed attempts", ip, by_ip.failures);
+        }
+        let now = chrono::Utc::now().naive_utc();
+        Ok(FailureOutcome {
+            username_locked: by_username.locked_until
+                .filter(|_| by_username.failures == max_failures())
+                .map(|until| seconds_until(now, until)),
+        })
+    })
+}
+
+/// Login berhasil: hitungan untuk username dihapus. Hitungan IP tetap
+/// berjalan, satu akun milik penyerang tidak boleh mereset batas IP-nya.
+pub fn clear_username(conn: &mut PgPooledConnection, user_name: &str) -> Result<usize, AppError> {
+    use quoteyourlife_be::schema::login_throttles::dsl::*;
+    let cleared = diesel::delete(login_throttles.find((LoginThrottleScopeEnum::Username, throttle_subject(user_name))))
+        .execute(conn)?;
+    Ok(cleared)
+}
+
+/// Buka lock login sebuah akun (admin). Hanya hitungan username yang
+/// dihapus; IP yang terkunci tetap menunggu lock-nya habis.
+pub async fn unlock_user(
+    Extension(pool): Extension<PgPool>,
+    Path(user_name): Path<String>,
+) -> Result<StatusCode, AppError> {
+    info!("[DELETE /users/{}/lockout] Received request to unlock login", user_name);
+
+    let _user_name = user_name.clone();
+    let cleared = tokio::task::spawn_blocking(move || -> Result<_, AppError> {
+        let mut conn = get_conn(&pool)?;
+        {
+            use quoteyourlife_be::schema::users::dsl::*;
+            users.filter(username.eq(&_user_name))
+                .filter(deleted_at.is_null())
+                .select(id)
+                .first::<uuid::Uuid>(&mut conn)
+                .optional()?
+                .ok_or(AppError::NotFound(format!("User {} tidak ditemukan", _user_name)))?;
+        }
+        clear_username(&mut conn, &_user_name)
+    })
+    .await
+    .map_err(AppError::AsyncTaskError)?
+    ?;
+
+    info!("[DELETE /users/{}/lockout] Login unlocked ({} record(s) cleared)", user_name, cleared);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Beri tahu pemilik akun bahwa login-nya dikunci. Dipanggil dari thread
+/// blocking; gagal kirim hanya dicatat di log.
+pub fn notify_locked(user: &User, ip: &str, retry_after: i64) {
+    let email = Email {
+        to: user.email.clone(),
+        subject: "Login akun QuoteYourLife dikunci sementara".to_string(),
+        body: format!(
+            "Halo {},\n\nAda {} percobaan login gagal ke akun kamu, terakhir dari IP {}. Login dikunci selama {} detik.\n\nKalau itu bukan kamu, sebaiknya ganti password lewat fitur lupa password.\n",
+            user.username,
+            max_failures(),
+            ip,
+            retry_after
+        ),
+    };
+    match mailer::mailer().and_then(|mailer| mailer.send(&email)) {
+        Ok(()) => info!("Lockout notification sent to user {}", user.id),
+        Err(err) => warn!("Failed to send lockout notification to {}: {:?}", user.id, err),
+    }
+}
+
+// Tambah satu kegagalan; baris dikunci dulu supaya percobaan paralel tidak saling menimpa
+fn bump(
+    conn: &mut PgPooledConnection,
+    throttle_scope: LoginThrottleScopeEnum,
+    raw_subject: &str,
+    limit: i32
+) -> Result<LoginThrottle, AppError> {
+    use quoteyourlife_be::schema::login_throttles::dsl::*;
+    let now = chrono::Utc::now().naive_utc();
+    let key = throttle_subject(raw_subject);
+    diesel::insert_into(login_throttles)
+        .values((scope.eq(throttle_scope), subject.eq(&key), failures.eq(0), last_failed_at.eq(now)))
+        .on_conflict_do_nothing()
+        .execute(conn)?;
+    let current = login_throttles.find((throttle_scope, &key))
+        .for_update()
+        .select(LoginThrottle::as_select())
+        .first(conn)?;
+
+    let last_activity = current.locked_until.map_or(current.last_failed_at, |until| until.max(current.last_failed_at));
+    let stale = now - last_activity > Duration::minutes(FAILURE_WINDOW_MINUTES);
+    let count = if stale { 1 } else { current.failures + 1 };
+    let lock = (count >= limit).then(|| now + lockout_duration(count - limit));
+    let updated = diesel::update(login_throttles.find((throttle_scope, &key)))
+        .set((failures.eq(count), last_failed_at.eq(now), locked_until.eq(lock)))
+        .returning(LoginThrottle::as_returning())
+        .get_result(conn)?;
+    debug!("Login failure {} for {:?} subject (locked: {})", count, throttle_scope, lock.is_some());
+    Ok(updated)
+}
+
+// Catatan yang sudah lewat jendelanya tidak berpengaruh lagi
+fn prune(conn: &mut PgPooledConnection) -> Result<(), AppError> {
+    use quoteyourlife_be::schema::login_throttles::dsl::*;
+    let cutoff = chrono::Utc::now().naive_utc() - Duration::minutes(FAILURE_WINDOW_MINUTES);
+    let pruned = diesel::delete(login_throttles)
+        .filter(last_failed_at.lt(cutoff))
+        .filter(locked_until.is_null().or(locked_until.lt(cutoff)))
+        .execute(conn)?;
+    if pruned > 0 {
+        debug!("Pruned {} stale login throttle record(s)", pruned);
+    }
+    Ok(())
+}
+
+// Kegagalan ke-`extra` setelah batas: LOGIN_LOCKOUT_SECS * 2^extra, maksimal satu jam
+fn lockout_duration(extra: i32) -> Duration {
+    let base = env::var("LOGIN_LOCKOUT_SECS")
+        .ok()
+        .and_then(|raw| raw.trim().parse::<i64>().ok())
+        .filter(|secs| *secs > 0)
+        .unwrap_or(60);
+    let secs = base.saturating_mul(1_i64.checked_shl(extra.clamp(0, 32) as u32).unwrap_or(i64::MAX));
+    Duration::seconds(secs.min(MAX_LOCKOUT_SECS))
+}
+
+fn seconds_until(now: NaiveDateTime, until: NaiveDateTime) -> i64 {
+    let millis = (until - now).num_milliseconds();
+    ((millis + 999) / 1000).max(1)
+}
+
+// Username disimpan apa adanya (sama seperti pencarian user saat login), dipotong sesuai kolom
+fn throttle_subject(raw: &str) -> String {
+    raw.chars().take(MAX_SUBJECT_LENGTH).collect()
+}
+
+// LOGIN_MAX_FAILURES, default 5 percobaan gagal per username
+fn max_failures() -> i32 {
+    limit_from_env("LOGIN_MAX_FAILURES", 5)
+}
+
+// LOGIN_MAX_FAILURES_PER_IP, default 20 percobaan gagal per IP (semua username)
+fn max_failures_per_ip() -> i32 {
+    limit_from_env("LOGIN_MAX_FAILURES_PER_IP", 20)
+}
+
+fn limit_from_env(key: &str, default: i32) -> i32 {
+    env::var(key)
+        .ok()
+        .and_then(|raw| raw.trim().parse::<i32>().ok())
+        .filter(|limit| *limit > 0)
+        .unwrap_or(default)
+}
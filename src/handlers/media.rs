@@ -0,0 +1,182 @@
+use tracing::{info, debug, warn};
+use axum::{
+    body::Body,
+    extract::{Extension, Path, Query, Request},
+    http::{header, HeaderMap, HeaderValue},
+    response::{IntoResponse, Response},
+};
+use crate::db::{PgPool, get_conn};
+use quoteyourlife_be::models::{NFT, VisibilityEnum};
+use quoteyourlife_be::storage::{self, Variant};
+use super::AppError;
+use diesel::prelude::*;
+use serde::Deserialize;
+use tower::ServiceExt;
+use tower_http::services::ServeFile;
+use uuid::Uuid;
+use crate::utils::signed_url;
+
+#[derive(Deserialize)]
+pub struct SignatureQuery {
+    pub exp: Option<i64>,
+    pub sig: Option<String>,
+}
+
+pub async fn get_media(
+    Extension(pool): Extension<PgPool>,
+    Path((nft_id, variant)): Path<(Uuid, String)>,
+    Query(params): Query<SignatureQuery>,
+    request: Request
+) -> Result<Response, AppError> {
+    info!("[GET /media/{}/{}] Received request for media file", nft_id, variant);
+
+    let variant: Variant = variant.parse().map_err(AppError::BadRequest)?;
+    let item = tokio::task::spawn_blocking(move || -> Result<_, AppError> {
+        let mut conn = get_conn(&pool)?;
+        use quoteyourlife_be::schema::nft::dsl::*;
+        nft.find(nft_id)
+            .first::<NFT>(&mut conn)
+            .optional()?
+            .ok_or(AppError::NotFound(format!("NFT {} tidak ditemukan", nft_id)))
+    })
+    .await
+    .map_err(AppError::AsyncTaskError)?
+    ?;
+
+    // Item publik bisa diakses langsung; selain itu wajib URL bertanda tangan.
+    // Original item yang di-watermark juga wajib, URL-nya hanya dibuat untuk pemilik.
+    let now = chrono::Utc::now().timestamp();
+    let protected_original = item.watermark && variant == Variant::Original;
+    let cache_control = if item.visibility == VisibilityEnum::Public && !protected_original {
+        "public, max-age=86400".to_string()
+    } else {
+        let (Some(expires), Some(signature)) = (params.exp, params.sig.as_deref()) else {
+            return Err(AppError::Forbidden("URL media ini memerlukan signature".to_string()));
+        };
+        if !signed_url::verify(item.id, variant, expires, signature) {
+            warn!("[GET /media/{}/{}] Invalid media signature", nft_id, variant);
+            return Err(AppError::Forbidden("Signature URL media tidak valid".to_string()));
+        }
+        if expires < now {
+            return Err(AppError::Forbidden("URL media sudah kedaluwarsa".to_string()));
+        }
+        // Jangan di-cache lebih lama dari masa berlaku URL
+        format!("private, max-age={}", expires - now)
+    };
+
+    // Item lama belum punya file variant; pakai original
+    let public_dir = storage::public_dir();
+    let mut variant = variant;
+    if variant != Variant::Original && !public_dir.join(storage::variant_filename(&item.filename, variant)).exists() {
+        debug!("Variant {} missing for {}, serving original", variant, item.id);
+        variant = Variant::Original;
+    }
+    // Format modern yang diterima client dan tersedia di disk, kalau tidak ada pakai format asli
+    let file_path = preferred_formats(request.headers(), &item.filename)
+        .into_iter()
+        .map(|ext| public_dir.join(storage::rendition_filename(&item.filename, variant, ext)))
+        .find(|path| path.exists())
+        .unwrap_or_else(|| public_dir.join(storage::variant_filename(&item.filename, variant)));
+
+    let mut response = ServeFile::new(&file_path)
+        .oneshot(request)
+        .await
+        .map_err(|err| AppError::GeneralError(format!("Gagal membaca file media: {}", err)))?
+        .map(Body::new)
+        .into_response();
+    if response.status().is_success() {
+        response.headers_mut().insert(
+            header::CACHE_CONTROL,
+            HeaderValue::from_str(&cache_control).expect("Cache-Control selalu ASCII"),
+        );
+    }
+    // Isi response bergantung pada Accept, cache/CDN harus membedakannya
+    response.headers_mut().insert(header::VARY, HeaderValue::from_static("Accept"));
+    debug!("Serving {} with status {}", file_path.display(), response.status());
+    Ok(response)
+}
+
+/// Avatar user, selalu publik. Nama file berubah di setiap upload, jadi
+/// response boleh di-cache tanpa batas.
+pub async fn get_avatar(
+    Path(filename): Path<String>,
+    request: Request
+) -> Result<Response, AppError> {
+    info!("[GET /media/avatars/{}] Received request for avatar", filename);
+
+    // Hanya nama file buatan server (`<uuid>.<ext>`), tidak bisa keluar dari folder avatar
+    let not_found = || AppError::NotFound("Avatar tidak ditemukan".to_string());
+    let (stem, extension) = filename.split_once('.').ok_or_else(not_found)?;
+    if stem.parse::<Uuid>().is_err() || !extension.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return Err(not_found());
+    }
+    let avatar_dir = storage::avatar_dir();
+    let file_path = preferred_formats(request.headers(), &filename)
+        .into_iter()
+        .map(|ext| avatar_dir.join(storage::rendition_filename(&filename, Variant::Original, ext)))
+        .find(|path| path.exists())
+        .unwrap_or_else(|| avatar_dir.join(&filename));
+    if !file_path.exists() {
+        return Err(not_found());
+    }
+
+    let mut response = ServeFile::new(&file_path)
+        .oneshot(request)
+        .await
+        .map_err(|err| AppError::GeneralError(format!("Gagal membaca file avatar: {}", err)))?
+        .map(Body::new)
+        .into_response();
+    if response.status().is_success() {
+        response.headers_mut().insert(
+            header::CACHE_CONTROL,
+            HeaderValue::from_static("public, max-age=31536000, immutable"),
+        );
+    }
+    response.headers_mut().insert(header::VARY, HeaderValue::from_static("Accept"));
+    debug!("Serving {} with status {}", file_path.display(), response.status());
+    Ok(response)
+}
+
+/// Format alternatif (AVIF/WebP) yang boleh dikirim, urut dari yang paling
+/// diinginkan client. Format modern hanya dipakai kalau disebut eksplisit di
+/// `Accept` (`image/*` tidak cukup, banyak browser lama mengirimnya) dan
+/// q-value-nya tidak lebih rendah dari format asli.
+fn preferred_formats(headers: &HeaderMap, filename: &str) -> Vec<&'static str> {
+    let accept = headers.get(header::ACCEPT)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or("");
+    let entries: Vec<(&str, f32)> = accept.split(',')
+        .filter_map(|entry| {
+            let mut parts = entry.split(';');
+            let mime = parts.next()?.trim();
+            let q = parts
+                .filter_map(|param| param.trim().strip_prefix("q="))
+                .find_map(|q| q.trim().parse::<f32>().ok())
+                .unwrap_or(1.0);
+            (!mime.is_empty()).then_some((mime, q))
+        })
+        .collect();
+    let explicit_q = |mime: &str| entries.iter()
+        .find(|(m, _)| m.eq_ignore_ascii_case(mime))
+        .map(|(_, q)| *q);
+
+    let original_mime = match filename.rsplit_once('.').map(|(_, ext)| ext.to_ascii_lowercase()) {
+        Some(ext) if ext == "jpg" => "image/jpeg".to_string(),
+        Some(ext) => format!("image/{}", ext),
+        None => String::new(),
+    };
+    let original_q = explicit_q(&original_mime)
+        .or_else(|| explicit_q("image/*"))
+        .or_else(|| explicit_q("*/*"))
+        .unwrap_or(1.0);
+
+    let mut formats: Vec<(&'static str, f32)> = storage::ALTERNATE_FORMATS.iter()
+        .filter_map(|ext| {
+            let q = explicit_q(&format!("image/{}", ext))?;
+            (q > 0.0 && q >= original_q).then_some((*ext, q))
+        })
+        .collect();
+    // sort_by stabil: q sama -> urutan ALTERNATE_FORMATS (paling kecil dulu)
+    formats.sort_by(|a, b| b.1.total_cmp(&a.1));
+    formats.into_iter().map(|(ext, _)| ext).collect()
+}
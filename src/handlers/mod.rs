@@ -1,8 +1,18 @@
 pub mod user;
+pub mod account;
+pub mod avatar;
 pub mod nft;
 pub mod article;
 pub mod quote;
 pub mod auth;
+pub mod album;
+pub mod media;
+pub mod archive;
+pub mod export;
+pub mod feed;
+pub mod follow;
+pub mod lockout;
+pub mod provenance;
 
 use axum::{
     http::StatusCode,
@@ -11,8 +21,13 @@ use axum::{
     Json,
 };
 
+use diesel::prelude::*;
 use serde_json::json;
 use tracing::{error, warn};
+use uuid::Uuid;
+
+use quoteyourlife_be::db::PgPooledConnection;
+use quoteyourlife_be::models::UserRoleEnum;
 
 
 
@@ -23,6 +38,12 @@ pub enum AppError {
     PoolError(r2d2::Error),
     AsyncTaskError(tokio::task::JoinError),
     JWTValidationError(jsonwebtoken::errors::Error),
+    BadRequest(String),
+    NotFound(String),
+    Forbidden(String),
+    Conflict(String),
+    PayloadTooLarge(String),
+    TooManyRequests(String),
     GeneralError(String)
 }
 
@@ -46,6 +67,30 @@ impl IntoResponse for AppError {
                 error!("JWT Error: {:?}", err);
                 (StatusCode::UNAUTHORIZED, "JWT Validation Error: Unauthorized".to_string())
             },
+            AppError::BadRequest(err) => {
+                error!("Bad request: {:?}", err);
+                (StatusCode::BAD_REQUEST, err)
+            },
+            AppError::NotFound(err) => {
+                warn!("Not found: {:?}", err);
+                (StatusCode::NOT_FOUND, err)
+            },
+            AppError::Forbidden(err) => {
+                warn!("Forbidden: {:?}", err);
+                (StatusCode::FORBIDDEN, err)
+            },
+            AppError::Conflict(err) => {
+                warn!("Conflict: {:?}", err);
+                (StatusCode::CONFLICT, err)
+            },
+            AppError::PayloadTooLarge(err) => {
+                warn!("Payload too large: {:?}", err);
+                (StatusCode::PAYLOAD_TOO_LARGE, err)
+            },
+            AppError::TooManyRequests(err) => {
+                warn!("Too many requests: {:?}", err);
+                (StatusCode::TOO_MANY_REQUESTS, err)
+            },
             AppError::GeneralError(err) => {
                 error!("Error: {:?}", err);
                 (StatusCode::INTERNAL_SERVER_ERROR, err)
@@ -70,7 +115,38 @@ impl From<diesel::result::Error> for AppError {
     }
 }
 
-// 4. Handler yang sudah diperbaiki
+impl From<quoteyourlife_be::provenance::KeyError> for AppError {
+    fn from(err: quoteyourlife_be::provenance::KeyError) -> Self {
+        AppError::GeneralError(err.0)
+    }
+}
+
+// 4. Helper otorisasi yang dipakai beberapa handler
+pub fn is_admin_user(conn: &mut PgPooledConnection, user_id: Uuid) -> Result<bool, AppError> {
+    use quoteyourlife_be::schema::users::dsl::*;
+    let admin = users.find(user_id)
+        .select(role)
+        .first::<UserRoleEnum>(conn)
+        .optional()?
+        == Some(UserRoleEnum::Admin);
+    Ok(admin)
+}
+
+/// Pelanggaran UNIQUE pada tabel users jadi 409 dengan pesan yang jelas,
+/// error database lain tetap 500.
+pub fn map_user_conflict(err: diesel::result::Error) -> AppError {
+    use diesel::result::{DatabaseErrorKind, Error};
+    match &err {
+        Error::DatabaseError(DatabaseErrorKind::UniqueViolation, info) => match info.constraint_name() {
+            Some("users_username_key") => AppError::Conflict("Username sudah dipakai".to_string()),
+            Some("users_email_key") => AppError::Conflict("Email sudah terdaftar".to_string()),
+            _ => AppError::Conflict("Data user sudah ada".to_string()),
+        },
+        _ => AppError::DatabaseError(err),
+    }
+}
+
+// 5. Handler yang sudah diperbaiki
 
 pub async fn handle_404(uri: Uri) -> impl IntoResponse {
     warn!("[404] Requested route not found: {}", uri);
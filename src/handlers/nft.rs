@@ -1,27 +1,86 @@
-use tracing::{info, debug};
+use tracing::{info, debug, warn};
 use axum::{
-    extract::Extension,
+    extract::{Extension, Path, Query},
+    http::{HeaderMap, StatusCode},
     Json,
 };
-use crate::db::{PgPool, get_conn}; 
-use quoteyourlife_be::models::{NFT, NewNFT};
-use super::AppError;
+use crate::db::{PgPool, PgPooledConnection, get_conn};
+use quoteyourlife_be::models::{CreatedNFT, NFT, NewNFT, NftAttribute, SignedMediaUrl, SimilarNFT, TokenMetadata, UpdateNFT, UploadUsage, User, VisibilityEnum, MediaTypeEnum, Permission};
+use super::{AppError, is_admin_user};
 use diesel::prelude::*;
-use std::path::Path;
+use jsonwebtoken::TokenData;
 use std::env;
+use quoteyourlife_be::storage::{self, Variant};
+use quoteyourlife_be::ipfs;
 use tokio::fs;
-use axum_extra::extract::Multipart;
+use axum_extra::extract::{Multipart, multipart::Field};
+use serde::Deserialize;
+use serde_json::Value;
+use uuid::Uuid;
+use crate::utils::signed_url;
+use crate::utils::jwt::{Claims, optional_claims};
+use super::account::{ensure_verified, unverified_error};
+use super::album::{find_viewable_album, load_album_items};
+use super::provenance;
+use crate::utils::media::{self, SanitizedImage, SanitizedUpload};
+use crate::utils::watermark::Watermark;
+
+// display_type yang dikenali marketplace (OpenSea)
+const DISPLAY_TYPES: [&str; 4] = ["number", "boost_number", "boost_percentage", "date"];
+
+// Default jarak Hamming maksimum (dari 64 bit) untuk dianggap gambar yang sama
+const DEFAULT_DUPLICATE_DISTANCE: u32 = 6;
+
+// Masa berlaku URL media bertanda tangan (detik)
+const DEFAULT_MEDIA_URL_TTL: i64 = 3600;
+const MAX_MEDIA_URL_TTL: i64 = 7 * 24 * 3600;
+
+// Batas panjang teks aksesibilitas (karakter)
+const MAX_ALT_TEXT_LENGTH: usize = 1000;
+const MAX_LONG_DESCRIPTION_LENGTH: usize = 10_000;
+
+// Default durasi maksimum video (detik), bisa diubah lewat MAX_VIDEO_DURATION_SECS
+const DEFAULT_MAX_VIDEO_DURATION_SECS: u64 = 60;
+
+#[derive(Deserialize)]
+pub struct GalleryQuery {
+    album: Option<Uuid>,
+}
+
+#[derive(Deserialize)]
+pub struct DuplicatesQuery {
+    max_distance: Option<u32>,
+}
+
+#[derive(Deserialize)]
+pub struct MediaUrlQuery {
+    variant: Option<Variant>,
+    ttl: Option<i64>,
+}
 
 pub async fn get_all_nft(
-    Extension(pool): Extension<PgPool>
+    Extension(pool): Extension<PgPool>,
+    Query(params): Query<GalleryQuery>,
+    headers: HeaderMap
 ) -> Result<Json<Vec<NFT>>, AppError> {
     info!("[GET /gallery] Received request to fetch all NFT");
     debug!("Starting database query for NFT");
-    
+
+    let viewer = optional_claims(&pool, &headers).await?.map(|c| c.user_id()).transpose()?;
     let nfts = tokio::task::spawn_blocking(move || -> Result<_, AppError>{
         let mut conn = get_conn(&pool)?;
+        // ?album=<id>: isi album sesuai urutan kurasi
+        if let Some(album_id) = params.album {
+            let album = find_viewable_album(&mut conn, album_id, viewer)?;
+            return load_album_items(&mut conn, album.id, viewer);
+        }
         use quoteyourlife_be::schema::nft::dsl::*;
-        let results = nft.load::<NFT>(&mut conn)?;
+        // Item unlisted/private tidak muncul di daftar, kecuali milik user sendiri
+        let mut query = nft.into_boxed().filter(visibility.eq(VisibilityEnum::Public));
+        if let Some(viewer_id) = viewer {
+            query = query.or_filter(owner_id.eq(viewer_id));
+        }
+        let results = query.load::<NFT>(&mut conn)?;
 
         Ok(results)
     })
@@ -34,48 +93,129 @@ pub async fn get_all_nft(
     Ok(Json(nfts))
 }
 
+pub async fn get_nft(
+    Extension(pool): Extension<PgPool>,
+    Path(nft_id): Path<Uuid>,
+    headers: HeaderMap
+) -> Result<Json<NFT>, AppError> {
+    info!("[GET /gallery/{}] Received request to fetch NFT", nft_id);
+
+    let viewer = optional_claims(&pool, &headers).await?.map(|c| c.user_id()).transpose()?;
+    let item = tokio::task::spawn_blocking(move || -> Result<_, AppError> {
+        let mut conn = get_conn(&pool)?;
+        find_viewable_nft(&mut conn, nft_id, viewer)
+    })
+    .await
+    .map_err(AppError::AsyncTaskError)?
+    ?;
+
+    debug!("Fetched NFT: {:?}", item);
+    Ok(Json(item))
+}
+
 pub async fn create_new_nft(
     Extension(pool): Extension<PgPool>,
+    Extension(token): Extension<TokenData<Claims>>,
     mut multipart: Multipart
-) -> Result<Json<NFT>, AppError> {
+) -> Result<Json<CreatedNFT>, AppError> {
     info!("[POST /gallery] Received request to create new NFT");
     debug!("Starting multipart data processing");
-    
-    let public_dir = env::var("PUBLIC_DIR").expect("Set the Public Directory in .env");
-    let mut author_: String = String::new();
+
+    let owner_id_ = token.claims.user_id()?;
+    // Kuota jumlah item dicek sebelum gambar diproses
+    let _pool = pool.clone();
+    let owner = tokio::task::spawn_blocking(move || -> Result<_, AppError> {
+        let mut conn = get_conn(&_pool)?;
+        ensure_within_quota(&mut conn, owner_id_, 0)?;
+        use quoteyourlife_be::schema::users::dsl::*;
+        let owner = users.find(owner_id_).first::<User>(&mut conn)?;
+        Ok(owner)
+    })
+    .await
+    .map_err(AppError::AsyncTaskError)?
+    ?;
+
     let mut title_: String = String::new();
     let mut description_: String = String::new();
-    let mut filename_: String = String::new();
+    let mut attributes_: Value = Value::Array(Vec::new());
+    let mut visibility_ = VisibilityEnum::Public;
+    let mut watermark_: Option<bool> = None;
+    let mut alt_text_: Option<String> = None;
+    let mut long_description_: Option<String> = None;
+    let mut upload: Option<SanitizedUpload> = None;
+    let mut poster: Option<SanitizedImage> = None;
 
-    while let Some(field) = multipart.next_field().await.unwrap() {
+    while let Some(field) = multipart.next_field().await
+        .map_err(|err| AppError::BadRequest(format!("Body multipart tidak valid: {}", err)))?
+    {
         // println!("debug\n {:?}",field);
-        let name = field.name().unwrap().to_string();
+        let name = field.name().unwrap_or_default().to_string();
         debug!("Processing multipart field: {}", name);
         match name.as_str() {
-            "author" => {
-                author_ = field.text().await.unwrap();
-                debug!("Author extracted: {}", author_);
-            }
             "title" => {
-                title_ = field.text().await.unwrap();
+                title_ = field_text(field, &name).await?;
                 debug!("Title extracted: {}", title_);
             }
             "description" => {
-                description_ = field.text().await.unwrap();
+                description_ = field_text(field, &name).await?;
                 debug!("Description extracted: {} chars", description_.len());
             }
+            "attributes" => {
+                let raw = field_text(field, &name).await?;
+                let parsed = serde_json::from_str(&raw)
+                    .map_err(|err| AppError::BadRequest(format!("Field 'attributes' bukan JSON yang valid: {}", err)))?;
+                attributes_ = validate_attributes(parsed)?;
+                debug!("Attributes extracted: {}", attributes_);
+            }
+            "visibility" => {
+                let raw = field_text(field, &name).await?;
+                visibility_ = serde_json::from_value(Value::String(raw.clone()))
+                    .map_err(|_| AppError::BadRequest(format!("Visibility '{}' tidak dikenali", raw)))?;
+                debug!("Visibility extracted: {:?}", visibility_);
+            }
+            "alt_text" => {
+                alt_text_ = normalize_text(field_text(field, &name).await?, "alt_text", MAX_ALT_TEXT_LENGTH)?;
+                debug!("Alt text extracted: {:?}", alt_text_);
+            }
+            "long_description" => {
+                long_description_ = normalize_text(field_text(field, &name).await?, "long_description", MAX_LONG_DESCRIPTION_LENGTH)?;
+                debug!("Long description extracted: {} chars", long_description_.as_ref().map_or(0, |d| d.len()));
+            }
+            "watermark" => {
+                let raw = field_text(field, &name).await?;
+                watermark_ = Some(raw.parse()
+                    .map_err(|_| AppError::BadRequest(format!("Watermark harus 'true' atau 'false', bukan '{}'", raw)))?);
+                debug!("Watermark extracted: {:?}", watermark_);
+            }
             "image" => {
-                filename_ = field.file_name().unwrap().to_string();
-                let data = field.bytes().await.unwrap();
-                debug!("File received: {}, size: {} bytes", filename_, data.len());
-                let upload_dir = Path::new(&public_dir);
-                if !upload_dir.exists() {
-                    fs::create_dir_all(upload_dir).await.unwrap();
-                    info!("Created upload directory: {}", public_dir);
+                let original_name = field.file_name().unwrap_or_default().to_string();
+                let data = field.bytes().await
+                    .map_err(|err| AppError::BadRequest(format!("Gagal membaca field '{}': {}", name, err)))?;
+                debug!("File received: {}, size: {} bytes", original_name, data.len());
+                // Jenis file dikenali dari isinya; EXIF/XMP (GPS, serial kamera) dibuang sebelum disimpan
+                let sanitized = tokio::task::spawn_blocking(move || media::sanitize_upload(&data))
+                    .await
+                    .map_err(AppError::AsyncTaskError)?
+                    ?;
+                match &sanitized {
+                    SanitizedUpload::Still(image) => debug!("Image sanitized: {:?}", image.metadata),
+                    SanitizedUpload::Motion(motion) => debug!(
+                        "Motion sanitized: {:?} {}x{}, {}ms",
+                        motion.media_type, motion.width, motion.height, motion.duration_ms
+                    ),
                 }
-                let file_path = upload_dir.join(&filename_);
-                fs::write(&file_path, &data).await.unwrap();
-                info!("[POST /gallery] File uploaded successfully: {} ({} bytes)", filename_, data.len());
+                upload = Some(sanitized);
+            }
+            "poster" => {
+                // Frame sampul untuk video (animasi GIF/WebP memakai frame pertama)
+                let data = field.bytes().await
+                    .map_err(|err| AppError::BadRequest(format!("Gagal membaca field '{}': {}", name, err)))?;
+                debug!("Poster received: {} bytes", data.len());
+                let sanitized = tokio::task::spawn_blocking(move || media::sanitize_image(&data))
+                    .await
+                    .map_err(AppError::AsyncTaskError)?
+                    ?;
+                poster = Some(sanitized);
             }
             _ => {
                 debug!("Unknown field ignored: {}", name);
@@ -83,27 +223,650 @@ pub async fn create_new_nft(
         }
     }
 
+    let upload = upload
+        .ok_or(AppError::BadRequest("Field 'image' wajib diisi".to_string()))?;
+    // Tanpa alt text: ditolak, atau diterima lalu masuk antrean review (GET /gallery/alt-text-queue)
+    if alt_text_.is_none() {
+        if require_alt_text() {
+            return Err(AppError::BadRequest("Field 'alt_text' wajib diisi".to_string()));
+        }
+        warn!("[POST /gallery] Upload without alt text, queued for review");
+    }
+    if owner.email_verified_at.is_none() && visibility_ != VisibilityEnum::Private {
+        return Err(unverified_error(owner.id, "meng-upload item galeri yang tidak private"));
+    }
+
+    let (watermark_, watermark) = match &upload {
+        SanitizedUpload::Still(_) => {
+            // Tanpa field 'watermark', ikut default milik user
+            let enabled = watermark_.unwrap_or(owner.watermark_default);
+            (enabled, enabled.then(|| Watermark::for_user(&owner.username)))
+        }
+        // Watermark ditempel ke pixel rendition, animasi/video disimpan tanpa encode ulang
+        SanitizedUpload::Motion(_) if watermark_ == Some(true) => {
+            return Err(AppError::BadRequest("Watermark hanya didukung untuk gambar diam".to_string()));
+        }
+        SanitizedUpload::Motion(motion) => {
+            let max_duration = max_video_duration_ms();
+            if motion.media_type == MediaTypeEnum::Video && motion.duration_ms > max_duration {
+                return Err(AppError::BadRequest(format!(
+                    "Durasi video {} detik melebihi batas {} detik",
+                    motion.duration_ms.div_ceil(1000),
+                    max_duration / 1000
+                )));
+            }
+            (false, None)
+        }
+    };
+    let upload = tokio::task::spawn_blocking(move || match upload {
+        SanitizedUpload::Still(image) => media::prepare_still(image, watermark.as_ref()),
+        SanitizedUpload::Motion(motion) => media::prepare_motion(motion, poster.map(|p| p.image)),
+    })
+    .await
+    .map_err(AppError::AsyncTaskError)?
+    ?;
+
+    // Cek kuota ukuran dan gambar yang mirip sebelum file disimpan
+    let size_bytes_ = upload.size_bytes();
+    let (max_distance, reject_duplicates) = duplicate_policy();
+    // Video tanpa poster tidak punya pixel untuk dibandingkan
+    let phash_ = upload.metadata.as_ref().map(|m| m.phash);
+    let _pool = pool.clone();
+    let similar_items = tokio::task::spawn_blocking(move || -> Result<_, AppError> {
+        let mut conn = get_conn(&_pool)?;
+        ensure_within_quota(&mut conn, owner_id_, size_bytes_)?;
+        match phash_ {
+            Some(hash) => find_similar(&mut conn, owner_id_, hash, max_distance),
+            None => Ok(Vec::new()),
+        }
+    })
+    .await
+    .map_err(AppError::AsyncTaskError)?
+    ?;
+    if !similar_items.is_empty() {
+        warn!("[POST /gallery] Upload is similar to {} existing item(s): {:?}", similar_items.len(), similar_items.iter().map(|i| i.id).collect::<Vec<_>>());
+        if reject_duplicates {
+            return Err(AppError::Conflict(format!(
+                "Gambar terlalu mirip dengan item yang sudah ada: {}",
+                similar_items.iter().map(|i| i.id.to_string()).collect::<Vec<_>>().join(", ")
+            )));
+        }
+    }
+
+    // Nama file dibuat server supaya tidak bentrok dengan upload lain
+    // (menghapus satu item tidak boleh ikut menghapus file item lain)
+    let filename_ = format!("{}.{}", Uuid::new_v4(), upload.extension);
+    let cid_ = ipfs::cid_v1(&upload.data);
+    let upload_dir = storage::public_dir();
+    if !upload_dir.exists() {
+        fs::create_dir_all(&upload_dir).await
+            .map_err(|err| AppError::GeneralError(format!("Gagal membuat folder upload: {}", err)))?;
+        info!("Created upload directory: {}", upload_dir.display());
+    }
+    let written = async {
+        fs::write(upload_dir.join(&filename_), &upload.data).await?;
+        info!("[POST /gallery] File uploaded successfully: {} ({} bytes)", filename_, upload.data.len());
+        for rendition in &upload.renditions {
+            let rendition_name = storage::rendition_filename(&filename_, rendition.variant, rendition.extension());
+            fs::write(upload_dir.join(&rendition_name), &rendition.data).await?;
+            debug!("Rendition written: {} ({} bytes)", rendition_name, rendition.data.len());
+        }
+        Ok::<_, std::io::Error>(())
+    }
+    .await;
+    // File yang sudah sempat ditulis tidak boleh tertinggal tanpa baris nft
+    if let Err(err) = written {
+        remove_stored_files(&filename_).await;
+        return Err(AppError::GeneralError(format!("Gagal menyimpan file upload: {}", err)));
+    }
+
+    let metadata_ = upload.metadata;
+    let (width_, height_, media_type_) = (upload.width, upload.height, upload.media_type);
+    let duration_ms_ = upload.duration_ms.map(|ms| ms.min(i32::MAX as u64) as i32);
     debug!("Multipart processing complete. Creating NFT record...");
+    let stored_filename = filename_.clone();
     let new_nft = tokio::task::spawn_blocking(move || -> Result<_, AppError> {
-        let payload = NewNFT {
-            title: title_,
-            description: description_,
-            author: author_,
-            filename: filename_,
-        };
+        let mut conn = get_conn(&pool)?;
+        conn.transaction::<_, AppError, _>(|conn| {
+            // Upload paralel dari user yang sama antre di row user-nya, lalu kuota
+            // dicek ulang dengan ukuran sebenarnya sebelum insert
+            {
+                use quoteyourlife_be::schema::users::dsl::*;
+                users.find(owner.id).select(id).for_update().first::<Uuid>(conn)?;
+            }
+            ensure_within_quota(conn, owner.id, size_bytes_)?;
+            // Author diambil dari akun pengupload, bukan dari input client
+            let payload = NewNFT {
+                title: title_,
+                description: description_,
+                author: owner.username,
+                filename: filename_,
+                width: Some(width_ as i32),
+                height: Some(height_ as i32),
+                dominant_colors: metadata_.as_ref()
+                    .map(|m| m.dominant_colors.iter().cloned().map(Some).collect())
+                    .unwrap_or_default(),
+                taken_at: metadata_.as_ref().and_then(|m| m.taken_at),
+                owner_id: Some(owner.id),
+                attributes: attributes_,
+                cid: Some(cid_),
+                phash: phash_.map(|hash| hash as i64),
+                visibility: visibility_,
+                blurhash: metadata_.as_ref().map(|m| m.blurhash.clone()),
+                lqip: metadata_.map(|m| m.lqip),
+                size_bytes: size_bytes_,
+                watermark: watermark_,
+                media_type: media_type_,
+                duration_ms: duration_ms_,
+                alt_text: alt_text_,
+                long_description: long_description_,
+            };
+            use quoteyourlife_be::schema::nft::dsl::*;
+            let result = diesel::insert_into(nft)
+                .values(&payload)
+                .returning(NFT::as_returning())
+                .get_result(conn)?;
+            // Titik awal rantai provenance: pemilik dan waktu upload
+            provenance::append_genesis(conn, &result)?;
+            Ok(result)
+        })
+    })
+    .await
+    .map_err(AppError::AsyncTaskError)
+    .and_then(|result| result);
+    let new_nft = match new_nft {
+        Ok(new_nft) => new_nft,
+        Err(err) => {
+            // Tidak ada row yang menunjuk ke file ini
+            remove_stored_files(&stored_filename).await;
+            return Err(err);
+        }
+    };
+
+    info!("[POST /gallery] Successfully created new NFT with ID: {}", new_nft.id);
+    debug!("Created NFT: {:?}", new_nft);
+    Ok(Json(CreatedNFT { item: new_nft, similar_items }))
+}
+
+pub async fn update_nft(
+    Extension(pool): Extension<PgPool>,
+    Extension(token): Extension<TokenData<Claims>>,
+    Path(nft_id): Path<Uuid>,
+    Json(mut payload): Json<UpdateNFT>
+) -> Result<Json<NFT>, AppError> {
+    info!("[PATCH /gallery/{}] Received request to update NFT", nft_id);
+    debug!("Request payload: {:?}", payload);
+
+    if payload.title.is_none()
+        && payload.description.is_none()
+        && payload.attributes.is_none()
+        && payload.visibility.is_none()
+        && payload.watermark.is_none()
+        && payload.alt_text.is_none()
+        && payload.long_description.is_none()
+    {
+        return Err(AppError::BadRequest("Tidak ada field yang diubah".to_string()));
+    }
+    payload.attributes = payload.attributes.map(validate_attributes).transpose()?;
+    // String kosong tidak menghapus alt text, item tetap keluar dari antrean review
+    payload.alt_text = payload.alt_text
+        .map(|text| normalize_text(text, "alt_text", MAX_ALT_TEXT_LENGTH))
+        .transpose()?
+        .flatten();
+    payload.long_description = payload.long_description
+        .map(|text| normalize_text(text, "long_description", MAX_LONG_DESCRIPTION_LENGTH))
+        .transpose()?
+        .flatten();
+
+    let user_id = token.claims.user_id()?;
+    let _pool = pool.clone();
+    let (updated, watermark_changed) = tokio::task::spawn_blocking(move || -> Result<_, AppError> {
+        let mut conn = get_conn(&_pool)?;
+        let item = find_nft(&mut conn, nft_id)?;
+        ensure_can_modify(&mut conn, &item, user_id)?;
+        if payload.watermark == Some(true) && item.media_type != MediaTypeEnum::Image {
+            return Err(AppError::BadRequest("Watermark hanya didukung untuk gambar diam".to_string()));
+        }
+        if payload.visibility.is_some_and(|v| v != VisibilityEnum::Private) {
+            ensure_verified(&mut conn, user_id, "membuka item galeri ke publik")?;
+        }
+
+        use quoteyourlife_be::schema::nft::dsl::*;
+        let result = diesel::update(nft.find(item.id))
+            .set(&payload)
+            .returning(NFT::as_returning())
+            .get_result(&mut conn)?;
+        Ok((result, payload.watermark.is_some_and(|w| w != item.watermark)))
+    })
+    .await
+    .map_err(AppError::AsyncTaskError)?
+    ?;
+
+    // Watermark dipasang/dilepas: Large/Medium dibuat ulang dari original
+    let updated = if watermark_changed {
+        regenerate_renditions(&pool, updated).await?
+    } else {
+        updated
+    };
+
+    info!("[PATCH /gallery/{}] Successfully updated NFT", nft_id);
+    Ok(Json(updated))
+}
+
+pub async fn delete_nft(
+    Extension(pool): Extension<PgPool>,
+    Extension(token): Extension<TokenData<Claims>>,
+    Path(nft_id): Path<Uuid>
+) -> Result<StatusCode, AppError> {
+    info!("[DELETE /gallery/{}] Received request to delete NFT", nft_id);
+
+    let user_id = token.claims.user_id()?;
+    let deleted = tokio::task::spawn_blocking(move || -> Result<_, AppError> {
+        let mut conn = get_conn(&pool)?;
+        let item = find_nft(&mut conn, nft_id)?;
+        ensure_can_modify(&mut conn, &item, user_id)?;
+
+        use quoteyourlife_be::schema::nft::dsl::*;
+        diesel::delete(nft.find(item.id)).execute(&mut conn)?;
+        Ok(item)
+    })
+    .await
+    .map_err(AppError::AsyncTaskError)?
+    ?;
+
+    remove_stored_files(&deleted.filename).await;
+
+    info!("[DELETE /gallery/{}] Successfully deleted NFT", nft_id);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn get_nft_metadata(
+    Extension(pool): Extension<PgPool>,
+    Path(nft_id): Path<Uuid>,
+    headers: HeaderMap
+) -> Result<Json<TokenMetadata>, AppError> {
+    info!("[GET /gallery/{}/metadata.json] Received request for token metadata", nft_id);
+
+    let viewer = optional_claims(&pool, &headers).await?.map(|c| c.user_id()).transpose()?;
+    let _pool = pool.clone();
+    let item = tokio::task::spawn_blocking(move || -> Result<_, AppError> {
+        let mut conn = get_conn(&_pool)?;
+        find_viewable_nft(&mut conn, nft_id, viewer)
+    })
+    .await
+    .map_err(AppError::AsyncTaskError)?
+    ?;
+
+    // CID diisi saat upload; item lama diisi lewat `cargo run --bin backfill_cid`.
+    // Sampai itu terjadi, image menunjuk ke file original di server ini.
+    let image = match &item.cid {
+        Some(item_cid) => format!("ipfs://{}", item_cid),
+        None => format!("/media/{}/{}", item.id, Variant::Original),
+    };
+
+    let external_url = env::var("APP_BASE_URL")
+        .ok()
+        .map(|base| format!("{}/gallery/{}", base.trim_end_matches('/'), item.id));
+
+    let metadata = TokenMetadata {
+        name: item.title,
+        description: item.description,
+        image,
+        image_alt: item.alt_text,
+        external_url,
+        attributes: item.attributes,
+    };
+    debug!("Token metadata: {:?}", metadata);
+    Ok(Json(metadata))
+}
+
+pub async fn get_media_url(
+    Extension(pool): Extension<PgPool>,
+    Path(nft_id): Path<Uuid>,
+    Query(params): Query<MediaUrlQuery>,
+    headers: HeaderMap
+) -> Result<Json<SignedMediaUrl>, AppError> {
+    info!("[GET /gallery/{}/url] Received request for signed media URL", nft_id);
+
+    let variant = params.variant.unwrap_or(Variant::Original);
+    let ttl = params.ttl.unwrap_or(DEFAULT_MEDIA_URL_TTL);
+    if !(1..=MAX_MEDIA_URL_TTL).contains(&ttl) {
+        return Err(AppError::BadRequest(format!("ttl harus antara 1 dan {} detik", MAX_MEDIA_URL_TTL)));
+    }
+
+    // Hanya yang boleh melihat item ini yang bisa membuat URL-nya
+    let viewer = optional_claims(&pool, &headers).await?.map(|c| c.user_id()).transpose()?;
+    let item = tokio::task::spawn_blocking(move || -> Result<_, AppError> {
+        let mut conn = get_conn(&pool)?;
+        let item = find_viewable_nft(&mut conn, nft_id, viewer)?;
+        // Original tanpa watermark hanya untuk pemilik
+        if item.watermark && variant == Variant::Original {
+            let allowed = match viewer {
+                Some(viewer_id) => item.owner_id == Some(viewer_id) || is_admin_user(&mut conn, viewer_id)?,
+                None => false,
+            };
+            if !allowed {
+                return Err(AppError::Forbidden("Original item ini hanya untuk pemiliknya".to_string()));
+            }
+        }
+        Ok(item)
+    })
+    .await
+    .map_err(AppError::AsyncTaskError)?
+    ?;
+
+    let expires_at = chrono::Utc::now().timestamp() + ttl;
+    let signed = SignedMediaUrl {
+        url: signed_url::media_path(item.id, variant, expires_at),
+        variant: variant.to_string(),
+        expires_at,
+    };
+    debug!("Signed media URL for {} ({}) expires at {}", item.id, variant, expires_at);
+    Ok(Json(signed))
+}
+
+/// Buat ulang semua rendition dari file original yang tersimpan, lalu
+/// perbarui `size_bytes`. Original sendiri tidak pernah diubah.
+async fn regenerate_renditions(pool: &PgPool, item: NFT) -> Result<NFT, AppError> {
+    let upload_dir = storage::public_dir();
+    let original_path = upload_dir.join(&item.filename);
+    let original = fs::read(&original_path).await.map_err(|err| {
+        AppError::GeneralError(format!("Gagal membaca file {}: {}", original_path.display(), err))
+    })?;
+
+    let watermark = item.watermark.then(|| Watermark::for_user(&item.author));
+    let (original_len, renditions) = tokio::task::spawn_blocking(move || -> Result<_, AppError> {
+        let (img, format) = media::decode_stored(&original)?;
+        let renditions = media::build_renditions(&img, format, &original, watermark.as_ref())?;
+        Ok((original.len(), renditions))
+    })
+    .await
+    .map_err(AppError::AsyncTaskError)?
+    ?;
+
+    // Rendition lama dihapus dulu, AVIF/WebP yang sekarang tidak lebih kecil tidak boleh tertinggal
+    for stored in storage::stored_files(&item.filename).into_iter().filter(|f| *f != item.filename) {
+        if let Err(err) = fs::remove_file(upload_dir.join(&stored)).await
+            && err.kind() != std::io::ErrorKind::NotFound
+        {
+            warn!("Failed to remove old rendition {}: {}", stored, err);
+        }
+    }
+    for rendition in &renditions {
+        let rendition_name = storage::rendition_filename(&item.filename, rendition.variant, rendition.extension());
+        fs::write(upload_dir.join(&rendition_name), &rendition.data).await.map_err(|err| {
+            AppError::GeneralError(format!("Gagal menulis file {}: {}", rendition_name, err))
+        })?;
+    }
+    info!("Regenerated {} renditions for NFT {} (watermark={})", renditions.len(), item.id, item.watermark);
+
+    let total_bytes = (original_len + renditions.iter().map(|r| r.data.len()).sum::<usize>()) as i64;
+    let pool = pool.clone();
+    tokio::task::spawn_blocking(move || -> Result<_, AppError> {
         let mut conn = get_conn(&pool)?;
         use quoteyourlife_be::schema::nft::dsl::*;
-        let result = diesel::insert_into(nft)
-            .values(&payload)
+        let result = diesel::update(nft.find(item.id))
+            .set(size_bytes.eq(total_bytes))
             .returning(NFT::as_returning())
             .get_result(&mut conn)?;
         Ok(result)
     })
     .await
     .map_err(AppError::AsyncTaskError)?
+}
+
+// Pastikan attributes berupa array trait yang valid, lalu normalisasi
+fn validate_attributes(raw: Value) -> Result<Value, AppError> {
+    let traits: Vec<NftAttribute> = serde_json::from_value(raw)
+        .map_err(|err| AppError::BadRequest(format!("Format attributes tidak valid: {}", err)))?;
+    for attribute in &traits {
+        if attribute.trait_type.trim().is_empty() {
+            return Err(AppError::BadRequest("trait_type tidak boleh kosong".to_string()));
+        }
+        if !(attribute.value.is_string() || attribute.value.is_number()) {
+            return Err(AppError::BadRequest(format!("Value untuk trait '{}' harus string atau angka", attribute.trait_type)));
+        }
+        if let Some(display_type) = &attribute.display_type {
+            if !DISPLAY_TYPES.contains(&display_type.as_str()) {
+                return Err(AppError::BadRequest(format!("display_type '{}' tidak dikenali", display_type)));
+            }
+            if !attribute.value.is_number() {
+                return Err(AppError::BadRequest(format!("Trait '{}' dengan display_type harus berupa angka", attribute.trait_type)));
+            }
+        }
+    }
+    serde_json::to_value(traits).map_err(|err| AppError::GeneralError(err.to_string()))
+}
+
+pub async fn get_duplicate_clusters(
+    Extension(pool): Extension<PgPool>,
+    Extension(token): Extension<TokenData<Claims>>,
+    Query(params): Query<DuplicatesQuery>
+) -> Result<Json<Vec<Vec<NFT>>>, AppError> {
+    info!("[GET /gallery/duplicates] Received request to list similar image clusters");
+
+    // Route sudah dibatasi Permission::ModerateGallery
+    debug!("Duplicate clusters requested by {}", token.claims.sub);
+    let max_distance = params.max_distance.unwrap_or(duplicate_policy().0);
+    let clusters = tokio::task::spawn_blocking(move || -> Result<_, AppError> {
+        let mut conn = get_conn(&pool)?;
+        use quoteyourlife_be::schema::nft::dsl::*;
+        let items = nft.filter(phash.is_not_null())
+            .order(created_at.asc())
+            .load::<NFT>(&mut conn)?;
+        let hashes: Vec<(usize, u64)> = items.iter()
+            .enumerate()
+            .filter_map(|(i, item)| item.phash.map(|h| (i, h as u64)))
+            .collect();
+
+        let mut items: Vec<Option<NFT>> = items.into_iter().map(Some).collect();
+        let mut clusters: Vec<Vec<NFT>> = media::cluster_by_hash(&hashes, max_distance)
+            .into_iter()
+            .map(|mut members| {
+                // Item paling lama di depan, kemungkinan besar itu karya aslinya
+                members.sort_unstable();
+                members.into_iter().filter_map(|i| items[i].take()).collect()
+            })
+            .collect();
+        clusters.sort_by(|a: &Vec<NFT>, b: &Vec<NFT>| a[0].created_at.cmp(&b[0].created_at));
+        Ok(clusters)
+    })
+    .await
+    .map_err(AppError::AsyncTaskError)?
     ?;
 
-    info!("[POST /gallery] Successfully created new NFT with ID: {}", new_nft.id);
-    debug!("Created NFT: {:?}", new_nft);
-    Ok(Json(new_nft))
-} 
+    info!("[GET /gallery/duplicates] Found {} clusters (max_distance={})", clusters.len(), max_distance);
+    Ok(Json(clusters))
+}
+
+/// Antrean review alt text: item tanpa `alt_text`, paling lama di depan.
+/// Editor dan admin melihat semua item, user lain hanya item miliknya.
+pub async fn get_alt_text_queue(
+    Extension(pool): Extension<PgPool>,
+    Extension(token): Extension<TokenData<Claims>>
+) -> Result<Json<Vec<NFT>>, AppError> {
+    info!("[GET /gallery/alt-text-queue] Received request for alt text review queue");
+
+    let user_id = token.claims.user_id()?;
+    let moderator = token.claims.role.can(Permission::ModerateGallery);
+    let items = tokio::task::spawn_blocking(move || -> Result<_, AppError> {
+        let mut conn = get_conn(&pool)?;
+        use quoteyourlife_be::schema::nft::dsl::*;
+        let mut query = nft.into_boxed().filter(alt_text.is_null());
+        if !moderator {
+            query = query.filter(owner_id.eq(user_id));
+        }
+        let results = query.order(created_at.asc()).load::<NFT>(&mut conn)?;
+        Ok(results)
+    })
+    .await
+    .map_err(AppError::AsyncTaskError)?
+    ?;
+
+    info!("[GET /gallery/alt-text-queue] {} item(s) need alt text", items.len());
+    Ok(Json(items))
+}
+
+// DUPLICATE_MAX_DISTANCE: jarak Hamming maksimum, DUPLICATE_POLICY: "warn" atau "reject"
+fn duplicate_policy() -> (u32, bool) {
+    let max_distance = env::var("DUPLICATE_MAX_DISTANCE")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_DUPLICATE_DISTANCE);
+    let reject = env::var("DUPLICATE_POLICY")
+        .map(|v| v.eq_ignore_ascii_case("reject"))
+        .unwrap_or(false);
+    (max_distance, reject)
+}
+
+// ALT_TEXT_POLICY: "reject" menolak upload tanpa alt text, selain itu ("flag") masuk antrean review
+fn require_alt_text() -> bool {
+    env::var("ALT_TEXT_POLICY")
+        .map(|v| v.eq_ignore_ascii_case("reject"))
+        .unwrap_or(false)
+}
+
+// Trim, string kosong = None, tolak yang terlalu panjang
+fn normalize_text(raw: String, field: &str, max_length: usize) -> Result<Option<String>, AppError> {
+    let text = raw.trim();
+    if text.chars().count() > max_length {
+        return Err(AppError::BadRequest(format!("Field '{}' maksimal {} karakter", field, max_length)));
+    }
+    Ok((!text.is_empty()).then(|| text.to_string()))
+}
+
+fn max_video_duration_ms() -> u64 {
+    env::var("MAX_VIDEO_DURATION_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(DEFAULT_MAX_VIDEO_DURATION_SECS)
+        .saturating_mul(1000)
+}
+
+// UPLOAD_QUOTA_MAX_ITEMS / UPLOAD_QUOTA_MAX_BYTES per user; kosong = tidak dibatasi
+fn upload_quota() -> (Option<i64>, Option<i64>) {
+    let limit = |key: &str| env::var(key).ok().and_then(|v| v.parse().ok());
+    (limit("UPLOAD_QUOTA_MAX_ITEMS"), limit("UPLOAD_QUOTA_MAX_BYTES"))
+}
+
+/// Jumlah item dan total byte yang tersimpan atas nama `user_id`.
+pub fn upload_usage(conn: &mut PgPooledConnection, user_id: Uuid) -> Result<UploadUsage, AppError> {
+    use quoteyourlife_be::schema::nft::dsl::*;
+    // SUM(BIGINT) di Postgres bertipe NUMERIC, di-cast supaya tidak perlu bigdecimal
+    let (item_count, bytes_used) = nft.filter(owner_id.eq(user_id))
+        .select((
+            diesel::dsl::count_star(),
+            diesel::dsl::sql::<diesel::sql_types::BigInt>("COALESCE(SUM(size_bytes), 0)::BIGINT"),
+        ))
+        .first::<(i64, i64)>(conn)?;
+    let (max_items, max_bytes) = upload_quota();
+    Ok(UploadUsage { item_count, bytes_used, max_items, max_bytes })
+}
+
+/// Tolak upload kalau item baru (`incoming_bytes`) membuat user melewati kuota.
+pub fn ensure_within_quota(conn: &mut PgPooledConnection, user_id: Uuid, incoming_bytes: i64) -> Result<(), AppError> {
+    let usage = upload_usage(conn, user_id)?;
+    if let Some(max_items) = usage.max_items
+        && usage.item_count >= max_items
+    {
+        return Err(AppError::TooManyRequests(format!(
+            "Kuota upload penuh: maksimal {} item per user", max_items
+        )));
+    }
+    if let Some(max_bytes) = usage.max_bytes
+        && usage.bytes_used + incoming_bytes > max_bytes
+    {
+        return Err(AppError::PayloadTooLarge(format!(
+            "Kuota penyimpanan terlampaui: terpakai {} dari {} byte, upload ini {} byte",
+            usage.bytes_used, max_bytes, incoming_bytes
+        )));
+    }
+    Ok(())
+}
+
+/// Hapus file original beserta rendition-nya. File yang gagal dihapus hanya
+/// dicatat di log (sisanya bisa dibersihkan dengan binary reconcile_media).
+// Body multipart yang terpotong/rusak adalah kesalahan client
+async fn field_text(field: Field, name: &str) -> Result<String, AppError> {
+    field.text().await
+        .map_err(|err| AppError::BadRequest(format!("Gagal membaca field '{}': {}", name, err)))
+}
+
+async fn remove_stored_files(stored_filename: &str) {
+    for stored in storage::stored_files(stored_filename) {
+        let file_path = storage::public_dir().join(&stored);
+        match fs::remove_file(&file_path).await {
+            Ok(()) => debug!("Removed stored file: {}", file_path.display()),
+            // Rendition AVIF/WebP hanya ada kalau lebih kecil dari aslinya
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                debug!("Stored file not present: {}", file_path.display());
+            }
+            Err(err) => warn!("Failed to remove stored file {}: {}", file_path.display(), err),
+        }
+    }
+}
+
+// Hanya item publik dan milik uploader sendiri; item private/unlisted orang lain
+// tidak boleh bisa ditebak lewat response upload
+fn find_similar(
+    conn: &mut PgPooledConnection,
+    uploader: Uuid,
+    hash: u64,
+    max_distance: u32
+) -> Result<Vec<SimilarNFT>, AppError> {
+    use quoteyourlife_be::schema::nft::dsl::*;
+    let candidates = nft.filter(phash.is_not_null())
+        .filter(visibility.eq(VisibilityEnum::Public).or(owner_id.eq(uploader)))
+        .select((id, title, author, filename, alt_text, phash))
+        .load::<(Uuid, String, String, String, Option<String>, Option<i64>)>(conn)?;
+
+    let mut similar: Vec<SimilarNFT> = candidates.into_iter()
+        .filter_map(|(item_id, item_title, item_author, item_filename, item_alt_text, item_hash)| {
+            let distance = media::hamming_distance(hash, item_hash? as u64);
+            (distance <= max_distance).then_some(SimilarNFT {
+                id: item_id,
+                title: item_title,
+                author: item_author,
+                filename: item_filename,
+                alt_text: item_alt_text,
+                distance,
+            })
+        })
+        .collect();
+    similar.sort_by_key(|item| item.distance);
+    Ok(similar)
+}
+
+fn find_nft(conn: &mut PgPooledConnection, nft_id: Uuid) -> Result<NFT, AppError> {
+    use quoteyourlife_be::schema::nft::dsl::*;
+    nft.find(nft_id)
+        .first::<NFT>(conn)
+        .optional()?
+        .ok_or(AppError::NotFound(format!("NFT {} tidak ditemukan", nft_id)))
+}
+
+/// Item yang boleh dilihat `viewer`. Item private milik orang lain
+/// dilaporkan sebagai 404, sama seperti album private.
+pub fn find_viewable_nft(
+    conn: &mut PgPooledConnection,
+    nft_id: Uuid,
+    viewer: Option<Uuid>
+) -> Result<NFT, AppError> {
+    let item = find_nft(conn, nft_id)?;
+    if item.visibility != VisibilityEnum::Private {
+        return Ok(item);
+    }
+    match viewer {
+        Some(viewer_id) if item.owner_id == Some(viewer_id) || is_admin_user(conn, viewer_id)? => Ok(item),
+        _ => Err(AppError::NotFound(format!("NFT {} tidak ditemukan", nft_id))),
+    }
+}
+
+// Hanya pemilik item atau admin yang boleh mengubah/menghapus
+pub fn ensure_can_modify(conn: &mut PgPooledConnection, item: &NFT, user_id: Uuid) -> Result<(), AppError> {
+    if item.owner_id == Some(user_id) || is_admin_user(conn, user_id)? {
+        Ok(())
+    } else {
+        Err(AppError::Forbidden("Hanya pemilik atau admin yang boleh mengubah item ini".to_string()))
+    }
+}
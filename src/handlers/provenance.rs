@@ -0,0 +1,237 @@
+use tracing::{info, debug, warn};
+use axum::{
+    extract::{Extension, Path},
+    http::HeaderMap,
+    Json,
+};
+use chrono::{NaiveDateTime, SubsecRound};
+use crate::db::{PgPool, PgPooledConnection, get_conn};
+use quoteyourlife_be::models::{
+    NFT, NewNftTransfer, NftTransfer, ProvenanceChain, ProvenanceProblem, ProvenanceVerification, TransferPayload, User
+};
+use super::AppError;
+use super::nft::{ensure_can_modify, ensure_within_quota, find_viewable_nft};
+use diesel::prelude::*;
+use jsonwebtoken::TokenData;
+use uuid::Uuid;
+use crate::utils::jwt::{Claims, optional_claims};
+use quoteyourlife_be::provenance;
+
+pub async fn get_provenance(
+    Extension(pool): Extension<PgPool>,
+    Path(nft_id): Path<Uuid>,
+    headers: HeaderMap
+) -> Result<Json<ProvenanceChain>, AppError> {
+    info!("[GET /gallery/{}/provenance] Received request for provenance chain", nft_id);
+
+    let viewer = optional_claims(&pool, &headers).await?.map(|c| c.user_id()).transpose()?;
+    let records = tokio::task::spawn_blocking(move || -> Result<_, AppError> {
+        let mut conn = get_conn(&pool)?;
+        let item = find_viewable_nft(&mut conn, nft_id, viewer)?;
+        load_chain(&mut conn, &item)
+    })
+    .await
+    .map_err(AppError::AsyncTaskError)?
+    ?;
+
+    debug!("Provenance chain for {} has {} record(s)", nft_id, records.len());
+    Ok(Json(ProvenanceChain { nft_id, public_key: provenance::public_key()?, records }))
+}
+
+/// Periksa seluruh rantai: urutan, hash record sebelumnya, isi record
+/// terhadap hash-nya, signature server, dan kesinambungan pemilik.
+pub async fn verify_provenance(
+    Extension(pool): Extension<PgPool>,
+    Path(nft_id): Path<Uuid>,
+    headers: HeaderMap
+) -> Result<Json<ProvenanceVerification>, AppError> {
+    info!("[GET /gallery/{}/provenance/verify] Received request to verify provenance", nft_id);
+
+    let viewer = optional_claims(&pool, &headers).await?.map(|c| c.user_id()).transpose()?;
+    let (item, records) = tokio::task::spawn_blocking(move || -> Result<_, AppError> {
+        let mut conn = get_conn(&pool)?;
+        let item = find_viewable_nft(&mut conn, nft_id, viewer)?;
+        let records = load_chain(&mut conn, &item)?;
+        Ok((item, records))
+    })
+    .await
+    .map_err(AppError::AsyncTaskError)?
+    ?;
+
+    let mut problems = Vec::new();
+    let mut previous: Option<&NftTransfer> = None;
+    for (index, record) in records.iter().enumerate() {
+        let mut problem = |reason: &str| problems.push(ProvenanceProblem { sequence: record.sequence, reason: reason.to_string() });
+        if record.sequence != index as i32 {
+            problem("Nomor urut tidak berurutan");
+        }
+        if record.prev_hash.as_deref() != previous.map(|p| p.record_hash.as_str()) {
+            problem("prev_hash tidak cocok dengan hash record sebelumnya");
+        }
+        if record.from_user_id != previous.map(|p| p.to_user_id) {
+            problem("Pengirim bukan pemilik pada record sebelumnya");
+        }
+        let expected = provenance::record_hash(
+            record.nft_id,
+            record.sequence,
+            record.from_user_id,
+            record.to_user_id,
+            record.transferred_at,
+            record.prev_hash.as_deref(),
+        );
+        if expected != record.record_hash {
+            problem("Isi record tidak cocok dengan hash-nya");
+        }
+        if record.signature.is_empty() {
+            problem("Record belum ditandatangani server");
+        } else if !provenance::verify(&record.record_hash, &record.signature)? {
+            problem("Signature server tidak valid");
+        }
+        previous = Some(record);
+    }
+    if let Some(last) = records.last()
+        && item.owner_id != Some(last.to_user_id)
+    {
+        problems.push(ProvenanceProblem {
+            sequence: last.sequence,
+            reason: "Pemilik saat ini tidak sesuai dengan record terakhir".to_string(),
+        });
+    }
+
+    if !problems.is_empty() {
+        warn!("[GET /gallery/{}/provenance/verify] Provenance chain has {} problem(s)", nft_id, problems.len());
+    }
+    Ok(Json(ProvenanceVerification {
+        nft_id,
+        valid: problems.is_empty(),
+        record_count: records.len(),
+        problems,
+    }))
+}
+
+pub async fn transfer_nft(
+    Extension(pool): Extension<PgPool>,
+    Extension(token): Extension<TokenData<Claims>>,
+    Path(nft_id): Path<Uuid>,
+    Json(payload): Json<TransferPayload>
+) -> Result<Json<NftTransfer>, AppError> {
+    info!("[POST /gallery/{}/transfer] Received request to transfer NFT", nft_id);
+    debug!("Transfer recipient: {}", payload.to);
+
+    let user_id = token.claims.user_id()?;
+    let record = tokio::task::spawn_blocking(move || -> Result<_, AppError> {
+        let mut conn = get_conn(&pool)?;
+        conn.transaction::<_, AppError, _>(|conn| {
+            // Row item dikunci supaya dua transfer bersamaan tidak membuat cabang di rantai
+            let item = {
+                use quoteyourlife_be::schema::nft::dsl::*;
+                nft.find(nft_id)
+                    .for_update()
+                    .first::<NFT>(conn)
+                    .optional()?
+                    .ok_or(AppError::NotFound(format!("NFT {} tidak ditemukan", nft_id)))?
+            };
+            ensure_can_modify(conn, &item, user_id)?;
+
+            let recipient = {
+                use quoteyourlife_be::schema::users::dsl::*;
+                users.filter(username.eq(&payload.to))
+                    .filter(deleted_at.is_null())
+                    .first::<User>(conn)
+                    .optional()?
+                    .ok_or(AppError::NotFound(format!("User {} tidak ditemukan", payload.to)))?
+            };
+            if item.owner_id == Some(recipient.id) {
+                return Err(AppError::BadRequest("Item sudah dimiliki user tersebut".to_string()));
+            }
+            // Item ikut dihitung ke kuota penerima
+            ensure_within_quota(conn, recipient.id, item.size_bytes)?;
+
+            let chain = load_chain(conn, &item)?;
+            let previous = chain.last()
+                .ok_or(AppError::BadRequest("Item tanpa pemilik tidak bisa ditransfer".to_string()))?;
+            let record = append_record(
+                conn,
+                item.id,
+                previous.sequence + 1,
+                Some(previous.to_user_id),
+                recipient.id,
+                Some(previous.record_hash.clone()),
+                chrono::Utc::now().naive_utc(),
+            )?;
+
+            use quoteyourlife_be::schema::nft::dsl::*;
+            diesel::update(nft.find(item.id))
+                .set(owner_id.eq(recipient.id))
+                .execute(conn)?;
+            Ok(record)
+        })
+    })
+    .await
+    .map_err(AppError::AsyncTaskError)?
+    ?;
+
+    info!("[POST /gallery/{}/transfer] Transferred to user {} (sequence {})", nft_id, record.to_user_id, record.sequence);
+    Ok(Json(record))
+}
+
+/// Rantai provenance satu item, urut dari genesis. Genesis ditulis saat
+/// upload (item lama diisi oleh migrasi), jadi fungsi ini hanya membaca.
+fn load_chain(conn: &mut PgPooledConnection, item: &NFT) -> Result<Vec<NftTransfer>, AppError> {
+    use quoteyourlife_be::schema::nft_transfers::dsl::*;
+    let records = nft_transfers
+        .filter(nft_id.eq(item.id))
+        .order(sequence.asc())
+        .select(NftTransfer::as_select())
+        .load(conn)?;
+    Ok(records)
+}
+
+/// Record genesis untuk item yang baru di-upload; dipanggil di transaksi insert-nya.
+/// Tanpa PROVENANCE_SIGNING_KEY record-nya ditulis tanpa signature.
+pub(super) fn append_genesis(conn: &mut PgPooledConnection, item: &NFT) -> Result<Option<NftTransfer>, AppError> {
+    let Some(owner) = item.owner_id else {
+        return Ok(None);
+    };
+    append_record(conn, item.id, 0, None, owner, None, item.created_at).map(Some)
+}
+
+fn append_record(
+    conn: &mut PgPooledConnection,
+    item_id: Uuid,
+    next_sequence: i32,
+    from: Option<Uuid>,
+    to: Uuid,
+    previous_hash: Option<String>,
+    at: NaiveDateTime
+) -> Result<NftTransfer, AppError> {
+    use quoteyourlife_be::schema::nft_transfers::dsl::*;
+    // Presisi disamakan dengan kolom TIMESTAMP, hash harus tetap cocok setelah dibaca ulang
+    let timestamp = at.trunc_subsecs(6);
+    let hash = provenance::record_hash(item_id, next_sequence, from, to, timestamp, previous_hash.as_deref());
+    let record_signature = match provenance::sign(&hash) {
+        Ok(signed) => signed,
+        // Upload tidak boleh gagal karena kunci signing; genesis ditulis tanpa
+        // signature lalu ditandatangani belakangan dengan `sign_provenance`
+        Err(err) if next_sequence == 0 => {
+            warn!("Genesis provenance record for {} left unsigned: {}", item_id, err);
+            String::new()
+        }
+        Err(err) => return Err(err.into()),
+    };
+    let new_record = NewNftTransfer {
+        nft_id: item_id,
+        sequence: next_sequence,
+        from_user_id: from,
+        to_user_id: to,
+        transferred_at: timestamp,
+        signature: record_signature,
+        prev_hash: previous_hash,
+        record_hash: hash,
+    };
+    let record = diesel::insert_into(nft_transfers)
+        .values(&new_record)
+        .returning(NftTransfer::as_returning())
+        .get_result(conn)?;
+    Ok(record)
+}
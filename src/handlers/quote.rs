@@ -6,7 +6,10 @@ use axum::{
 use crate::db::{PgPool, get_conn}; 
 use quoteyourlife_be::models::{Quote, NewQuote};
 use super::AppError;
+use super::account::ensure_verified;
 use diesel::prelude::*;
+use jsonwebtoken::TokenData;
+use crate::utils::jwt::Claims;
 
 pub async fn get_all_quotes(
     Extension(pool): Extension<PgPool>,
@@ -31,13 +34,17 @@ pub async fn get_all_quotes(
 
 pub async fn create_new_quote(
     Extension(pool): Extension<PgPool>,
-    Json(payload): Json<NewQuote>,
+    Extension(token): Extension<TokenData<Claims>>,
+    Json(mut payload): Json<NewQuote>,
 ) -> Result<Json<Quote>, AppError> {
     info!("[POST /quotes] Received request to create new quote");
     debug!("Request payload - author: {}, text length: {}", payload.author, payload.text.len());
     
+    let author_id = token.claims.user_id()?;
+    payload.user_id = Some(author_id);
     let new_quote = tokio::task::spawn_blocking(move || -> Result<_, AppError> {
         let mut conn = get_conn(&pool)?; // '?' sekarang berfungsi!
+        ensure_verified(&mut conn, author_id, "menulis quote")?;
         use quoteyourlife_be::schema::quotes::dsl::*;
         let result = diesel::insert_into(quotes)
             .values(&payload)
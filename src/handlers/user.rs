@@ -1,29 +1,60 @@
-use tracing::{info, debug};
+use tracing::{info, debug, warn};
 use axum::{
-    extract::Extension,
+    extract::{Extension, Path},
+    http::StatusCode,
     Json,
 };
-use crate::db::{PgPool, get_conn}; 
-use quoteyourlife_be::models::{User, NewUser};
-use super::AppError;
+use crate::db::{PgPool, PgPooledConnection, get_conn}; 
+use quoteyourlife_be::models::{
+    AdminUser, ChangePasswordPayload, CreateUserPayload, DeleteAccountPayload, User, NewUser, PublicUser, RolePayload, SelfUser,
+    UpdateProfilePayload, UploadUsage, UserPreferences, UserRoleEnum, UserTokenPurposeEnum, VisibilityEnum
+};
+use super::{AppError, map_user_conflict};
+use super::account::{issue_token, revoke_tokens, send_verification_email, unverified_error, verification_ttl};
+use super::avatar::remove_avatar_files;
+use super::nft::upload_usage;
+use bcrypt::{hash, verify, DEFAULT_COST};
 use diesel::prelude::*;
+use jsonwebtoken::TokenData;
+use serde_json::json;
+use uuid::Uuid;
+use crate::utils::jwt::{Claims, create_jwt};
+use crate::utils::{user_token, validation};
 
 
 
 pub async fn create_new_user(
     Extension(pool): Extension<PgPool>,
-    Json(payload): Json<NewUser>
-) -> Result<Json<User>, AppError> {
+    Json(payload): Json<CreateUserPayload>
+) -> Result<Json<AdminUser>, AppError> {
     info!("[POST /users] Received request to create new user");
     debug!("Request payload - username: {}, email: {}", payload.username, payload.email);
-    
+
+    // Aturan sama dengan POST /auth/register
+    let user_name = validation::validate_username(&payload.username)?;
+    let user_email = validation::validate_email(&payload.email)?;
+    let name = validation::validate_full_name(payload.full_name.as_deref())?;
+    validation::validate_password(&payload.password, &user_name)?;
+    let password = payload.password;
+    let hashed = tokio::task::spawn_blocking(move || hash(password, DEFAULT_COST))
+        .await
+        .map_err(AppError::AsyncTaskError)?
+        .map_err(|err| AppError::GeneralError(format!("Gagal memproses password: {}", err)))?;
+
+    let new_user = NewUser {
+        username: user_name,
+        email: user_email,
+        password_hash: hashed,
+        full_name: name,
+    };
     let new_user = tokio::task::spawn_blocking(move || -> Result<_, AppError> {
         let mut conn = get_conn(&pool)?;
         use quoteyourlife_be::schema::users::dsl::*;
         let result = diesel::insert_into(users)
-            .values(&payload)
+            .values(&new_user)
             .returning(User::as_returning())
-            .get_result(&mut conn)?;
+            .get_result(&mut conn)
+            .map_err(map_user_conflict)?;
         Ok(result)
     })
     .await
@@ -32,19 +63,19 @@ pub async fn create_new_user(
 
     info!("[POST /users] Successfully created new user with ID: {}", new_user.id);
     debug!("Created user: username={}", new_user.username);
-    Ok(Json(new_user))
+    Ok(Json(new_user.into()))
 }
 
 pub async fn get_all_users(
     Extension(pool): Extension<PgPool>,
-) -> Result<Json<Vec<User>>, AppError> {
+) -> Result<Json<Vec<AdminUser>>, AppError> {
     info!("[GET /users] Received request to fetch all users");
     debug!("Starting database query for users");
     
     let users = tokio::task::spawn_blocking(move || -> Result<_, AppError> {
         let mut conn = get_conn(&pool)?;
         use quoteyourlife_be::schema::users::dsl::*;
-        let results = users.load::<User>(&mut conn)?;
+        let results = users.order(created_at.asc()).load::<User>(&mut conn)?;
         Ok(results)
     })
     .await
@@ -53,5 +84,408 @@ pub async fn get_all_users(
 
     info!("[GET /users] Successfully fetched {} users", users.len());
     debug!("Response payload size: {} items", users.len());
-    Ok(Json(users))
-}
\ No newline at end of file
+    Ok(Json(users.into_iter().map(AdminUser::from).collect()))
+}
+
+/// Ganti role user. Token milik user itu dicabut supaya role baru langsung
+/// berlaku (klaim role ada di JWT), dan admin terakhir tidak bisa diturunkan.
+pub async fn update_user_role(
+    Extension(pool): Extension<PgPool>,
+    Path(user_name): Path<String>,
+    Json(payload): Json<RolePayload>
+) -> Result<Json<AdminUser>, AppError> {
+    info!("[PUT /users/{}/role] Received request to change role", user_name);
+    debug!("Request payload: {:?}", payload);
+
+    let updated = tokio::task::spawn_blocking(move || -> Result<_, AppError> {
+        let mut conn = get_conn(&pool)?;
+        conn.transaction::<_, AppError, _>(|conn| {
+            use quoteyourlife_be::schema::users::dsl::*;
+            let target = users.filter(username.eq(&user_name))
+                .for_update()
+                .first::<User>(conn)
+                .optional()?
+                .ok_or(AppError::NotFound(format!("User {} tidak ditemukan", user_name)))?;
+            if target.role == payload.role {
+                return Ok(target);
+            }
+            ensure_not_last_admin(conn, &target, "diturunkan")?;
+            let result = diesel::update(users.find(target.id))
+                .set((
+                    role.eq(payload.role),
+                    tokens_revoked_at.eq(chrono::Utc::now().naive_utc()),
+                    token_version.eq(token_version + 1),
+                ))
+                .returning(User::as_returning())
+                .get_result(conn)?;
+            Ok(result)
+        })
+    })
+    .await
+    .map_err(AppError::AsyncTaskError)?
+    ?;
+
+    info!("[PUT /users/{}/role] Role is now {:?}", updated.username, updated.role);
+    Ok(Json(updated.into()))
+}
+
+pub async fn get_me(
+    Extension(pool): Extension<PgPool>,
+    Extension(token): Extension<TokenData<Claims>>,
+) -> Result<Json<SelfUser>, AppError> {
+    info!("[GET /users/me] Received request for current user");
+
+    let user_id = token.claims.user_id()?;
+    let user = tokio::task::spawn_blocking(move || -> Result<_, AppError> {
+        let mut conn = get_conn(&pool)?;
+        use quoteyourlife_be::schema::users::dsl::*;
+        // Token masih valid tapi user-nya bisa saja sudah dihapus
+        let result = users.find(user_id)
+            .first::<User>(&mut conn)
+            .optional()?
+            .ok_or(AppError::NotFound("User tidak ditemukan".to_string()))?;
+        Ok(result)
+    })
+    .await
+    .map_err(AppError::AsyncTaskError)?
+    ?;
+
+    debug!("Current user: {}", user.username);
+    Ok(Json(user.into()))
+}
+
+pub async fn get_user_profile(
+    Extension(pool): Extension<PgPool>,
+    Path(user_name): Path<String>,
+) -> Result<Json<PublicUser>, AppError> {
+    info!("[GET /users/{}] Received request for public profile", user_name);
+
+    let user = tokio::task::spawn_blocking(move || -> Result<_, AppError> {
+        let mut conn = get_conn(&pool)?;
+        use quoteyourlife_be::schema::users::dsl::*;
+        let result = users.filter(username.eq(&user_name))
+            .filter(deleted_at.is_null())
+            .first::<User>(&mut conn)
+            .optional()?
+            .ok_or(AppError::NotFound(format!("User {} tidak ditemukan", user_name)))?;
+        Ok(result)
+    })
+    .await
+    .map_err(AppError::AsyncTaskError)?
+    ?;
+
+    debug!("Found profile for {}", user.username);
+    Ok(Json(user.into()))
+}
+
+pub async fn get_my_usage(
+    Extension(pool): Extension<PgPool>,
+    Extension(token): Extension<TokenData<Claims>>,
+) -> Result<Json<UploadUsage>, AppError> {
+    info!("[GET /users/me/usage] Received request for upload usage");
+
+    let user_id = token.claims.user_id()?;
+    let usage = tokio::task::spawn_blocking(move || -> Result<_, AppError> {
+        let mut conn = get_conn(&pool)?;
+        upload_usage(&mut conn, user_id)
+    })
+    .await
+    .map_err(AppError::AsyncTaskError)?
+    ?;
+
+    debug!("Usage for {}: {:?}", user_id, usage);
+    Ok(Json(usage))
+}
+
+pub async fn update_my_preferences(
+    Extension(pool): Extension<PgPool>,
+    Extension(token): Extension<TokenData<Claims>>,
+    Json(payload): Json<UserPreferences>
+) -> Result<Json<UserPreferences>, AppError> {
+    info!("[PATCH /users/me/preferences] Received request to update preferences");
+    debug!("Request payload: {:?}", payload);
+
+    if payload.watermark_default.is_none() {
+        return Err(AppError::BadRequest("Tidak ada field yang diubah".to_string()));
+    }
+
+    let user_id = token.claims.user_id()?;
+    let updated = tokio::task::spawn_blocking(move || -> Result<_, AppError> {
+        let mut conn = get_conn(&pool)?;
+        use quoteyourlife_be::schema::users::dsl::*;
+        // Hanya berlaku untuk upload berikutnya, item lama tidak diubah
+        let value = diesel::update(users.find(user_id))
+            .set(&payload)
+            .returning(watermark_default)
+            .get_result::<bool>(&mut conn)?;
+        Ok(UserPreferences { watermark_default: Some(value) })
+    })
+    .await
+    .map_err(AppError::AsyncTaskError)?
+    ?;
+
+    info!("[PATCH /users/me/preferences] Successfully updated preferences");
+    Ok(Json(updated))
+}
+
+pub async fn update_me(
+    Extension(pool): Extension<PgPool>,
+    Extension(token): Extension<TokenData<Claims>>,
+    Json(payload): Json<UpdateProfilePayload>
+) -> Result<Json<SelfUser>, AppError> {
+    info!("[PATCH /users/me] Received request to update profile");
+    debug!("Request payload: {:?}", payload);
+
+    if payload.username.is_none()
+        && payload.email.is_none()
+        && payload.full_name.is_none()
+        && payload.bio.is_none()
+        && payload.website.is_none()
+        && payload.location.is_none()
+    {
+        return Err(AppError::BadRequest("Tidak ada field yang diubah".to_string()));
+    }
+    let new_username = payload.username.as_deref().map(validation::validate_username).transpose()?;
+    let new_email = payload.email.as_deref().map(validation::validate_email).transpose()?;
+    let new_full_name = payload.full_name.as_deref().map(|name| validation::validate_full_name(Some(name))).transpose()?;
+    let new_bio = payload.bio.as_deref().map(validation::validate_bio).transpose()?;
+    let new_website = payload.website.as_deref().map(validation::validate_website).transpose()?;
+    let new_location = payload.location.as_deref().map(validation::validate_location).transpose()?;
+
+    let user_id = token.claims.user_id()?;
+    let (user, verification_token) = tokio::task::spawn_blocking(move || -> Result<_, AppError> {
+        let mut conn = get_conn(&pool)?;
+        conn.transaction::<_, AppError, _>(|conn| {
+            let current = find_active_user(conn, user_id)?;
+            let mut verification_token = None;
+            {
+                use quoteyourlife_be::schema::users::dsl::*;
+                if let Some(name) = new_full_name {
+                    diesel::update(users.find(current.id))
+                        .set(full_name.eq(name))
+                        .execute(conn)?;
+                }
+                if let Some(text) = new_bio {
+                    diesel::update(users.find(current.id))
+                        .set(bio.eq(text))
+                        .execute(conn)?;
+                }
+                if let Some(url) = new_website {
+                    // Link di profil publik, rawan spam dari akun baru
+                    if url.is_some() && current.email_verified_at.is_none() {
+                        return Err(unverified_error(current.id, "memasang website di profil"));
+                    }
+                    diesel::update(users.find(current.id))
+                        .set(website.eq(url))
+                        .execute(conn)?;
+                }
+                if let Some(place) = new_location {
+                    diesel::update(users.find(current.id))
+                        .set(location.eq(place))
+                        .execute(conn)?;
+                }
+                if let Some(name) = new_username.filter(|name| *name != current.username) {
+                    diesel::update(users.find(current.id))
+                        .set(username.eq(&name))
+                        .execute(conn)
+                        .map_err(map_user_conflict)?;
+                    // Kolom author item galeri menyimpan username saat upload
+                    use quoteyourlife_be::schema::nft::dsl as gallery;
+                    diesel::update(gallery::nft.filter(gallery::owner_id.eq(current.id)))
+                        .set(gallery::author.eq(&name))
+                        .execute(conn)?;
+                }
+                // Email baru harus diverifikasi ulang
+                if let Some(address) = new_email.filter(|address| *address != current.email) {
+                    diesel::update(users.find(current.id))
+                        .set((email.eq(address), email_verified_at.eq(None::<chrono::NaiveDateTime>)))
+                        .execute(conn)
+                        .map_err(map_user_conflict)?;
+                    verification_token = Some(issue_token(conn, current.id, UserTokenPurposeEnum::EmailVerification, verification_ttl())?);
+                }
+            }
+            let updated = find_active_user(conn, user_id)?;
+            Ok((updated, verification_token))
+        })
+    })
+    .await
+    .map_err(AppError::AsyncTaskError)?
+    ?;
+
+    if let Some(raw_token) = verification_token
+        && let Err(err) = send_verification_email(&user, raw_token).await
+    {
+        warn!("[PATCH /users/me] Failed to send verification email to {}: {:?}", user.id, err);
+    }
+    info!("[PATCH /users/me] Successfully updated profile of {}", user.id);
+    Ok(Json(user.into()))
+}
+
+/// Ganti password. Semua sesi lain ikut dicabut; token baru dikembalikan
+/// supaya sesi yang sedang dipakai tetap jalan.
+pub async fn change_my_password(
+    Extension(pool): Extension<PgPool>,
+    Extension(token): Extension<TokenData<Claims>>,
+    Json(payload): Json<ChangePasswordPayload>
+) -> Result<Json<serde_json::Value>, AppError> {
+    info!("[POST /users/me/password] Received request to change password");
+
+    if payload.current_password == payload.new_password {
+        return Err(AppError::BadRequest("Password baru harus berbeda dari password lama".to_string()));
+    }
+    let current_user = token.claims.user_id()?;
+    let (user, version) = tokio::task::spawn_blocking(move || -> Result<_, AppError> {
+        let mut conn = get_conn(&pool)?;
+        conn.transaction::<_, AppError, _>(|conn| {
+            let current = find_active_user(conn, current_user)?;
+            ensure_password(&payload.current_password, &current)?;
+            validation::validate_password(&payload.new_password, &current.username)?;
+            let hashed = hash(&payload.new_password, DEFAULT_COST)
+                .map_err(|err| AppError::GeneralError(format!("Gagal memproses password: {}", err)))?;
+
+            let now = chrono::Utc::now().naive_utc();
+            // Semua sesi lama dicabut; token baru di response memakai versi yang baru
+            let version = {
+                use quoteyourlife_be::schema::users::dsl::*;
+                diesel::update(users.find(current.id))
+                    .set((password_hash.eq(hashed), tokens_revoked_at.eq(now), token_version.eq(token_version + 1)))
+                    .returning(token_version)
+                    .get_result::<i32>(conn)?
+            };
+            // Link reset password yang masih beredar tidak berlaku lagi
+            use quoteyourlife_be::schema::user_tokens::dsl::*;
+            diesel::update(user_tokens)
+                .filter(user_id.eq(current.id))
+                .filter(purpose.eq(UserTokenPurposeEnum::PasswordReset))
+                .filter(used_at.is_null())
+                .set(used_at.eq(now))
+                .execute(conn)?;
+            Ok((current, version))
+        })
+    })
+    .await
+    .map_err(AppError::AsyncTaskError)?
+    ?;
+
+    let new_token = create_jwt(&user.id.to_string(), user.role, version)?;
+    info!("[POST /users/me/password] Password changed for user {}", user.id);
+    Ok(Json(json!({
+        "status": "success",
+        "message": "Password berhasil diganti",
+        "token": new_token
+    })))
+}
+
+/// Hapus akun: soft delete dan anonimisasi. Row user tetap ada (artikel,
+/// item galeri dan ledger provenance masih menunjuk ke sana), data pribadi
+/// dihapus, artikel ikut di-soft delete dan item galeri/album dijadikan
+/// private.
+pub async fn delete_me(
+    Extension(pool): Extension<PgPool>,
+    Extension(token): Extension<TokenData<Claims>>,
+    Json(payload): Json<DeleteAccountPayload>
+) -> Result<StatusCode, AppError> {
+    info!("[DELETE /users/me] Received request to delete account");
+
+    let user_id = token.claims.user_id()?;
+    let previous_avatar = tokio::task::spawn_blocking(move || -> Result<_, AppError> {
+        let mut conn = get_conn(&pool)?;
+        conn.transaction::<_, AppError, _>(|conn| {
+            let current = find_active_user(conn, user_id)?;
+            ensure_password(&payload.password, &current)?;
+            ensure_not_last_admin(conn, &current, "dihapus")?;
+
+            let now = chrono::Utc::now().naive_utc();
+            let anonymous = format!("{}{}", validation::DELETED_USERNAME_PREFIX, current.id.simple());
+            // Password acak yang tidak pernah diketahui siapa pun
+            let unusable = hash(user_token::generate().0, DEFAULT_COST)
+                .map_err(|err| AppError::GeneralError(format!("Gagal memproses password: {}", err)))?;
+            {
+                use quoteyourlife_be::schema::users::dsl::*;
+                diesel::update(users.find(current.id))
+                    .set((
+                        username.eq(&anonymous),
+                        email.eq(format!("{}@invalid", anonymous)),
+                        full_name.eq(None::<String>),
+                        avatar.eq(None::<String>),
+                        bio.eq(None::<String>),
+                        website.eq(None::<String>),
+                        location.eq(None::<String>),
+                        password_hash.eq(unusable),
+                        role.eq(UserRoleEnum::Reader),
+                        email_verified_at.eq(None::<chrono::NaiveDateTime>),
+                        tokens_revoked_at.eq(now),
+                        token_version.eq(token_version + 1),
+                        deleted_at.eq(now),
+                    ))
+                    .execute(conn)
+                    .map_err(map_user_conflict)?;
+            }
+            {
+                use quoteyourlife_be::schema::articles::dsl::*;
+                diesel::update(articles.filter(author_id.eq(current.id)).filter(deleted_at.is_null()))
+                    .set(deleted_at.eq(now))
+                    .execute(conn)?;
+            }
+            {
+                use quoteyourlife_be::schema::nft::dsl::*;
+                diesel::update(nft.filter(owner_id.eq(current.id)))
+                    .set((visibility.eq(VisibilityEnum::Private), author.eq(&anonymous)))
+                    .execute(conn)?;
+            }
+            {
+                use quoteyourlife_be::schema::albums::dsl::*;
+                diesel::update(albums.filter(owner_id.eq(current.id)))
+                    .set(visibility.eq(VisibilityEnum::Private))
+                    .execute(conn)?;
+            }
+            revoke_tokens(conn, current.id)?;
+            Ok(current.avatar)
+        })
+    })
+    .await
+    .map_err(AppError::AsyncTaskError)?
+    ?;
+
+    if let Some(previous) = previous_avatar {
+        remove_avatar_files(&previous).await;
+    }
+    info!("[DELETE /users/me] Account {} deleted", user_id);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// User yang belum dihapus; token milik akun yang sudah dihapus -> 404
+fn find_active_user(conn: &mut PgPooledConnection, user_id: Uuid) -> Result<User, AppError> {
+    use quoteyourlife_be::schema::users::dsl::*;
+    users.find(user_id)
+        .filter(deleted_at.is_null())
+        .for_update()
+        .first::<User>(conn)
+        .optional()?
+        .ok_or(AppError::NotFound("User tidak ditemukan".to_string()))
+}
+
+fn ensure_password(password: &str, user: &User) -> Result<(), AppError> {
+    let valid = verify(password, &user.password_hash)
+        .map_err(|err| AppError::GeneralError(format!("Gagal memeriksa password: {}", err)))?;
+    if !valid {
+        return Err(AppError::Forbidden("Password salah".to_string()));
+    }
+    Ok(())
+}
+
+// Selalu harus ada minimal satu admin
+fn ensure_not_last_admin(conn: &mut PgPooledConnection, user: &User, action: &str) -> Result<(), AppError> {
+    if user.role != UserRoleEnum::Admin {
+        return Ok(());
+    }
+    use quoteyourlife_be::schema::users::dsl::*;
+    let admins = users.filter(role.eq(UserRoleEnum::Admin))
+        .filter(deleted_at.is_null())
+        .count()
+        .get_result::<i64>(conn)?;
+    if admins <= 1 {
+        return Err(AppError::Conflict(format!("Admin terakhir tidak bisa {}", action)));
+    }
+    Ok(())
+}
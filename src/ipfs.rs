@@ -0,0 +1,186 @@
+use sha2::{Digest, Sha256};
+
+// Multicodec/multihash code, lihat https://github.com/multiformats/multicodec
+const CID_VERSION_1: u8 = 0x01;
+const CODEC_RAW: u8 = 0x55;
+const CODEC_DAG_PB: u8 = 0x70;
+const MULTIHASH_SHA2_256: u8 = 0x12;
+const SHA2_256_LENGTH: u8 = 0x20;
+
+// Default `ipfs add`: chunker size-262144, layout balanced, maksimal 174 link per node
+const CHUNK_SIZE: usize = 256 * 1024;
+const MAX_LINKS: usize = 174;
+// UnixFS Data.Type
+const UNIXFS_FILE: u64 = 2;
+
+const BASE32_ALPHABET: &[u8; 32] = b"abcdefghijklmnopqrstuvwxyz234567";
+
+/// Hitung CIDv1 (multibase base32) dari isi file, sama dengan hasil
+/// `ipfs add --cid-version 1` dengan pengaturan default: file dipotong per
+/// 256 KiB sebagai raw leaf, lalu disusun jadi pohon UnixFS (dag-pb)
+/// balanced. File yang muat dalam satu chunk langsung jadi CID `raw`.
+/// Dihitung lokal tanpa akses jaringan.
+pub fn cid_v1(data: &[u8]) -> String {
+    let mut level: Vec<Block> = if data.is_empty() {
+        vec![raw_leaf(data)]
+    } else {
+        data.chunks(CHUNK_SIZE).map(raw_leaf).collect()
+    };
+    while level.len() > 1 {
+        level = level.chunks(MAX_LINKS).map(file_node).collect();
+    }
+    // Prefix multibase 'b' = base32 lowercase tanpa padding
+    format!("b{}", base32_lower(&level[0].cid))
+}
+
+// Satu block dalam DAG: CID biner, ukuran block beserta turunannya, dan ukuran isi file
+struct Block {
+    cid: Vec<u8>,
+    cumulative_size: u64,
+    file_size: u64,
+}
+
+fn raw_leaf(chunk: &[u8]) -> Block {
+    Block {
+        cid: cid_bytes(CODEC_RAW, chunk),
+        cumulative_size: chunk.len() as u64,
+        file_size: chunk.len() as u64,
+    }
+}
+
+// Node UnixFS File tanpa data sendiri; isinya ada di link-link-nya
+fn file_node(children: &[Block]) -> Block {
+    let file_size: u64 = children.iter().map(|child| child.file_size).sum();
+
+    let mut unixfs = Vec::new();
+    put_varint_field(&mut unixfs, 1, UNIXFS_FILE);
+    put_varint_field(&mut unixfs, 3, file_size);
+    // blocksizes: proto2 repeated tanpa packed, satu field per child
+    for child in children {
+        put_varint_field(&mut unixfs, 4, child.file_size);
+    }
+
+    // dag-pb kanonik: semua Links dulu, baru Data
+    let mut node = Vec::new();
+    for child in children {
+        let mut link = Vec::new();
+        put_bytes_field(&mut link, 1, &child.cid);
+        put_bytes_field(&mut link, 2, b"");
+        put_varint_field(&mut link, 3, child.cumulative_size);
+        put_bytes_field(&mut node, 2, &link);
+    }
+    put_bytes_field(&mut node, 1, &unixfs);
+
+    let children_size: u64 = children.iter().map(|child| child.cumulative_size).sum();
+    Block {
+        cid: cid_bytes(CODEC_DAG_PB, &node),
+        cumulative_size: node.len() as u64 + children_size,
+        file_size,
+    }
+}
+
+fn cid_bytes(codec: u8, block: &[u8]) -> Vec<u8> {
+    let digest = Sha256::digest(block);
+    let mut bytes = Vec::with_capacity(4 + digest.len());
+    bytes.extend_from_slice(&[CID_VERSION_1, codec, MULTIHASH_SHA2_256, SHA2_256_LENGTH]);
+    bytes.extend_from_slice(&digest);
+    bytes
+}
+
+fn put_varint_field(out: &mut Vec<u8>, field: u64, value: u64) {
+    put_varint(out, field << 3);
+    put_varint(out, value);
+}
+
+fn put_bytes_field(out: &mut Vec<u8>, field: u64, value: &[u8]) {
+    put_varint(out, (field << 3) | 2);
+    put_varint(out, value.len() as u64);
+    out.extend_from_slice(value);
+}
+
+fn put_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn base32_lower(data: &[u8]) -> String {
+    let mut out = String::with_capacity((data.len() * 8).div_ceil(5));
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+    for &byte in data {
+        buffer = (buffer << 8) | byte as u32;
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            out.push(BASE32_ALPHABET[((buffer >> bits) & 0x1f) as usize] as char);
+        }
+    }
+    if bits > 0 {
+        out.push(BASE32_ALPHABET[((buffer << (5 - bits)) & 0x1f) as usize] as char);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // CID biner -> string, dihitung terpisah dari `cid_v1`
+    fn encoded(codec: u8, block: &[u8]) -> String {
+        format!("b{}", base32_lower(&cid_bytes(codec, block)))
+    }
+
+    #[test]
+    fn single_chunk_matches_known_raw_cids() {
+        // Nilai yang sama dengan `ipfs add --cid-version 1` untuk file kecil
+        assert_eq!(cid_v1(b""), "bafkreihdwdcefgh4dqkjv67uzcmw7ojee6xedzdetojuzjevtenxquvyku");
+        assert_eq!(cid_v1(b"hello world"), "bafkreifzjut3te2nhyekklss27nh3k72ysco7y32koao5eei66wof36n5e");
+    }
+
+    #[test]
+    fn exactly_one_chunk_stays_raw() {
+        let data = vec![7u8; CHUNK_SIZE];
+        assert_eq!(cid_v1(&data), encoded(CODEC_RAW, &data));
+    }
+
+    #[test]
+    fn two_chunks_build_a_unixfs_file_node() {
+        let mut data = vec![0u8; CHUNK_SIZE];
+        data.extend_from_slice(b"tail");
+        let first = cid_bytes(CODEC_RAW, &data[..CHUNK_SIZE]);
+        let second = cid_bytes(CODEC_RAW, b"tail");
+
+        // PBLink { Hash, Name: "", Tsize }; 262144 = varint 80 80 10
+        let mut node = vec![0x12, 0x2c, 0x0a, 0x24];
+        node.extend_from_slice(&first);
+        node.extend_from_slice(&[0x12, 0x00, 0x18, 0x80, 0x80, 0x10]);
+        node.extend_from_slice(&[0x12, 0x2a, 0x0a, 0x24]);
+        node.extend_from_slice(&second);
+        node.extend_from_slice(&[0x12, 0x00, 0x18, 0x04]);
+        // UnixFS Data { Type: File, filesize: 262148, blocksizes: [262144, 4] }
+        node.extend_from_slice(&[0x0a, 0x0c, 0x08, 0x02, 0x18, 0x84, 0x80, 0x10, 0x20, 0x80, 0x80, 0x10, 0x20, 0x04]);
+
+        let cid = cid_v1(&data);
+        assert!(cid.starts_with("bafybei"), "dag-pb sha2-256 CID, got {}", cid);
+        assert_eq!(cid, encoded(CODEC_DAG_PB, &node));
+    }
+
+    #[test]
+    fn varint_encoding() {
+        let mut out = Vec::new();
+        put_varint(&mut out, 1);
+        put_varint(&mut out, 300);
+        put_varint(&mut out, CHUNK_SIZE as u64);
+        assert_eq!(out, [0x01, 0xac, 0x02, 0x80, 0x80, 0x10]);
+    }
+
+    #[test]
+    fn base32_without_padding() {
+        // RFC 4648 test vector, huruf kecil tanpa '='
+        assert_eq!(base32_lower(b"foobar"), "mzxw6ytboi");
+        assert_eq!(base32_lower(b"f"), "my");
+    }
+}
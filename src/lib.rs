@@ -1,4 +1,7 @@
 pub mod db;
 pub mod seed;
 pub mod schema;
-pub mod models;
\ No newline at end of file
+pub mod models;
+pub mod storage;
+pub mod ipfs;
+pub mod provenance;
\ No newline at end of file
@@ -11,6 +11,7 @@ use axum::{
 };
 
 use dotenv::dotenv;
+use std::net::SocketAddr;
 use tokio::net::TcpListener;
 use tower_http::{
     cors::CorsLayer,
@@ -40,6 +41,11 @@ async fn main() {
     let pool = db::establish_connection();
     tracing::info!("✅ Database connection pool established");
 
+    // Upload tetap jalan tanpa kunci ini, tapi record provenance baru tidak ditandatangani
+    if let Err(err) = quoteyourlife_be::provenance::public_key() {
+        tracing::warn!("⚠️  Provenance signing disabled: {} (run sign_provenance after setting it)", err);
+    }
+
     let cors = CorsLayer::new()
         .allow_origin(tower_http::cors::Any)
         .allow_methods(tower_http::cors::Any)
@@ -54,6 +60,10 @@ async fn main() {
         .route("/health", get(|| async {"Health: Good"}))
         .nest("/quotes", routes::quote::router())
         .nest("/gallery", routes::nft::router())
+        .nest("/albums", routes::album::router())
+        .nest("/media", routes::media::router())
+        .nest("/exports", routes::export::router())
+        .nest("/feed", routes::feed::router())
         .nest("/users", routes::user::router())
         .nest("/article", routes::article::router())
         .nest("/auth", routes::auth::router())
@@ -74,7 +84,8 @@ async fn main() {
     tracing::info!("✅ QuoteYourLife Backend is ready to accept requests!\n");
 
     // Jalankan server
-    axum::serve(listener, app)
+    // ConnectInfo dipakai untuk IP client (riwayat login)
+    axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>())
         .await
         .unwrap();
 }
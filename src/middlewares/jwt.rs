@@ -1,23 +1,42 @@
-use std::env;
 use axum::{
-    http::Request, 
-    middleware::Next, 
+    extract::State,
+    http::{HeaderMap, Request},
+    middleware::Next,
     response::Response,
     body::Body
 };
-use jsonwebtoken::{
-    decode,
-    DecodingKey,
-    Validation
-};
-use crate::{handlers::AppError, utils::jwt::Claims};
+use jsonwebtoken::TokenData;
+use crate::{handlers::AppError, utils::jwt::{self, Claims}};
+use crate::db::PgPool;
+use quoteyourlife_be::models::Permission;
 
 pub async fn jwt_validation(
     mut request: Request<Body>,
     next: Next
 ) -> Result<Response, AppError> {
-    let secret_key = env::var("JWT_KEY").expect("Gagal membaca environment variable");
-    let auth_header = request.headers()
+    let pool = request.extensions().get::<PgPool>().cloned();
+    let token_data = authenticate(request.headers(), pool).await?;
+    request.extensions_mut().insert(token_data);
+    Ok(next.run(request).await)
+}
+
+/// Seperti `jwt_validation`, ditambah cek izin dari role di token:
+/// `.layer(middleware::from_fn_with_state(Permission::WriteQuote, require_permission))`.
+/// Tanpa izin -> 403.
+pub async fn require_permission(
+    State(permission): State<Permission>,
+    mut request: Request<Body>,
+    next: Next
+) -> Result<Response, AppError> {
+    let pool = request.extensions().get::<PgPool>().cloned();
+    let token_data = authenticate(request.headers(), pool).await?;
+    token_data.claims.require(permission)?;
+    request.extensions_mut().insert(token_data);
+    Ok(next.run(request).await)
+}
+
+async fn authenticate(headers: &HeaderMap, pool: Option<PgPool>) -> Result<TokenData<Claims>, AppError> {
+    let auth_header = headers
         .get("Authorization")
         .ok_or(AppError::GeneralError("Authorizaiton header needed!".to_string()))?
         .to_str().unwrap();
@@ -27,13 +46,6 @@ pub async fn jwt_validation(
         .strip_prefix("Bearer ")
         .ok_or(AppError::GeneralError("Internal Server Error".to_string()))?;
 
-    let token_data = decode::<Claims>(
-        &token,
-        &DecodingKey::from_secret(secret_key.as_ref()),
-        &Validation::default()
-    )
-    .map_err(|err| AppError::JWTValidationError(err))?;
-
-    request.extensions_mut().insert(token_data);
-    Ok(next.run(request).await)
+    let pool = pool.ok_or(AppError::GeneralError("Database pool tidak tersedia".to_string()))?;
+    jwt::authenticate(&pool, token).await
 }
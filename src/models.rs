@@ -5,8 +5,10 @@ use serde_json::Value;
 use uuid::Uuid;
 use chrono::NaiveDateTime;
 
+use crate::storage;
+
 // Ini adalah import dari schema.rs yang dihasilkan Diesel
-use crate::schema::{quotes, nft, users, articles};
+use crate::schema::{quotes, nft, nft_transfers, users, user_tokens, login_history, login_throttles, data_exports, follows, articles, albums, album_items};
 
 #[derive(Debug, Serialize, Deserialize, Queryable, Selectable, Insertable)]
 #[diesel(table_name = quotes)]
@@ -16,6 +18,7 @@ pub struct Quote {
     pub text: String,
     pub author: String,
     pub created_at: NaiveDateTime, // Gunakan NaiveDateTime untuk TIMESTAMP tanpa timezone
+    pub user_id: Option<Uuid>,
 }
 
 // Struct untuk data yang diterima saat membuat quote baru
@@ -24,6 +27,9 @@ pub struct Quote {
 pub struct NewQuote {
     pub text: String,
     pub author: String,
+    // Diisi dari token, bukan dari body request
+    #[serde(skip_deserializing)]
+    pub user_id: Option<Uuid>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Queryable, Selectable, Insertable)]
@@ -34,7 +40,24 @@ pub struct NFT {
     pub description: String,
     pub author: String,
     pub filename: String,
-    pub created_at: NaiveDateTime
+    pub created_at: NaiveDateTime,
+    pub width: Option<i32>,
+    pub height: Option<i32>,
+    pub dominant_colors: Vec<Option<String>>,
+    pub taken_at: Option<NaiveDateTime>,
+    pub owner_id: Option<Uuid>,
+    pub attributes: Value,
+    pub cid: Option<String>,
+    pub phash: Option<i64>,
+    pub visibility: VisibilityEnum,
+    pub blurhash: Option<String>,
+    pub lqip: Option<String>,
+    pub size_bytes: i64,
+    pub watermark: bool,
+    pub media_type: MediaTypeEnum,
+    pub duration_ms: Option<i32>,
+    pub alt_text: Option<String>,
+    pub long_description: Option<String>
 }
 
 #[derive(Debug, Deserialize, Insertable)]
@@ -44,9 +67,178 @@ pub struct NewNFT {
     pub description: String,
     pub author: String,
     pub filename: String,
+    pub width: Option<i32>,
+    pub height: Option<i32>,
+    pub dominant_colors: Vec<Option<String>>,
+    pub taken_at: Option<NaiveDateTime>,
+    pub owner_id: Option<Uuid>,
+    pub attributes: Value,
+    pub cid: Option<String>,
+    pub phash: Option<i64>,
+    pub visibility: VisibilityEnum,
+    pub blurhash: Option<String>,
+    pub lqip: Option<String>,
+    pub size_bytes: i64,
+    pub watermark: bool,
+    pub media_type: MediaTypeEnum,
+    pub duration_ms: Option<i32>,
+    pub alt_text: Option<String>,
+    pub long_description: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Queryable, Selectable, Insertable)]
+// Field yang boleh diubah lewat PATCH /gallery/{id}
+#[derive(Debug, Deserialize, AsChangeset)]
+#[diesel(table_name = nft)]
+pub struct UpdateNFT {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub attributes: Option<Value>,
+    pub visibility: Option<VisibilityEnum>,
+    pub watermark: Option<bool>,
+    pub alt_text: Option<String>,
+    pub long_description: Option<String>,
+}
+
+// Satu trait di kolom `nft.attributes`, mengikuti format metadata OpenSea
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NftAttribute {
+    pub trait_type: String,
+    pub value: Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub display_type: Option<String>,
+}
+
+// Item lain yang gambarnya mirip (jarak Hamming perceptual hash)
+#[derive(Debug, Serialize)]
+pub struct SimilarNFT {
+    pub id: Uuid,
+    pub title: String,
+    pub author: String,
+    pub filename: String,
+    pub alt_text: Option<String>,
+    pub distance: u32,
+}
+
+// Response POST /gallery: item baru + peringatan kalau ada gambar yang mirip
+#[derive(Debug, Serialize)]
+pub struct CreatedNFT {
+    #[serde(flatten)]
+    pub item: NFT,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub similar_items: Vec<SimilarNFT>,
+}
+
+// Response GET /gallery/{id}/url
+#[derive(Debug, Serialize)]
+pub struct SignedMediaUrl {
+    pub url: String,
+    pub variant: String,
+    pub expires_at: i64,
+}
+
+// Response GET /users/me/usage; batas None = tidak dibatasi
+#[derive(Debug, Serialize)]
+pub struct UploadUsage {
+    pub item_count: i64,
+    pub bytes_used: i64,
+    pub max_items: Option<i64>,
+    pub max_bytes: Option<i64>,
+}
+
+// Satu record ledger provenance; sequence 0 = genesis (pemilik pertama)
+#[derive(Debug, Serialize, Queryable, Selectable)]
+#[diesel(table_name = nft_transfers)]
+pub struct NftTransfer {
+    pub id: Uuid,
+    pub nft_id: Uuid,
+    pub sequence: i32,
+    pub from_user_id: Option<Uuid>,
+    pub to_user_id: Uuid,
+    pub transferred_at: NaiveDateTime,
+    pub prev_hash: Option<String>,
+    pub record_hash: String,
+    pub signature: String,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = nft_transfers)]
+pub struct NewNftTransfer {
+    pub nft_id: Uuid,
+    pub sequence: i32,
+    pub from_user_id: Option<Uuid>,
+    pub to_user_id: Uuid,
+    pub transferred_at: NaiveDateTime,
+    pub prev_hash: Option<String>,
+    pub record_hash: String,
+    pub signature: String,
+}
+
+// Body POST /gallery/{id}/transfer
+#[derive(Debug, Deserialize)]
+pub struct TransferPayload {
+    pub to: String,
+}
+
+// Response GET /gallery/{id}/provenance
+#[derive(Debug, Serialize)]
+pub struct ProvenanceChain {
+    pub nft_id: Uuid,
+    pub public_key: String,
+    pub records: Vec<NftTransfer>,
+}
+
+// Response GET /gallery/{id}/provenance/verify
+#[derive(Debug, Serialize)]
+pub struct ProvenanceVerification {
+    pub nft_id: Uuid,
+    pub valid: bool,
+    pub record_count: usize,
+    pub problems: Vec<ProvenanceProblem>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProvenanceProblem {
+    pub sequence: i32,
+    pub reason: String,
+}
+
+// manifest.json di dalam arsip GET /gallery/archive
+#[derive(Debug, Serialize)]
+pub struct ArchiveManifest {
+    pub generated_at: NaiveDateTime,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub album: Option<Album>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    pub items: Vec<ArchiveEntry>,
+}
+
+// Satu item di manifest; `file` None kalau file-nya tidak ada di disk atau
+// tidak boleh diunduh viewer (`variant` juga None)
+#[derive(Debug, Serialize)]
+pub struct ArchiveEntry {
+    pub file: Option<String>,
+    pub variant: Option<String>,
+    #[serde(flatten)]
+    pub item: NFT,
+}
+
+// Metadata token ERC-721 untuk GET /gallery/{id}/metadata.json
+#[derive(Debug, Serialize)]
+pub struct TokenMetadata {
+    pub name: String,
+    pub description: String,
+    pub image: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image_alt: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub external_url: Option<String>,
+    pub attributes: Value,
+}
+
+// Row users apa adanya, termasuk password_hash. Sengaja tidak Serialize:
+// response selalu lewat PublicUser, SelfUser atau AdminUser.
+#[derive(Debug, Clone, Deserialize, Queryable, Selectable, Insertable)]
 #[diesel(table_name = users)]
 pub struct User {
     pub id: Uuid,
@@ -54,10 +246,363 @@ pub struct User {
     pub email: String,
     pub password_hash: String,
     pub full_name: Option<String>,
-    pub created_at: NaiveDateTime
+    pub created_at: NaiveDateTime,
+    pub watermark_default: bool,
+    pub email_verified_at: Option<NaiveDateTime>,
+    pub tokens_revoked_at: Option<NaiveDateTime>,
+    pub role: UserRoleEnum,
+    pub deleted_at: Option<NaiveDateTime>,
+    pub avatar: Option<String>,
+    pub bio: Option<String>,
+    pub website: Option<String>,
+    pub location: Option<String>,
+    // Dibawa JWT di klaim `ver`; naik saat semua sesi dicabut
+    pub token_version: i32
 }
 
-#[derive(Debug, Deserialize, Insertable)]
+#[derive(Debug, Clone, Copy, Default, DbEnum, Serialize, Deserialize, PartialEq)]
+#[ExistingTypePath = "crate::schema::sql_types::UserRole"]
+pub enum UserRoleEnum {
+    Admin,
+    Editor,
+    Author,
+    // Token lama tanpa klaim role diperlakukan sebagai reader
+    #[default]
+    Reader
+}
+
+// Hak akses yang dicek di route (lihat middlewares::jwt::require_permission)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Permission {
+    WriteQuote,
+    // Upload dan kelola item galeri: edit, hapus, transfer, album
+    UploadGallery,
+    WriteArticle,
+    PublishArticle,
+    ModerateGallery,
+    ManageUsers
+}
+
+impl UserRoleEnum {
+    /// reader hanya membaca; author menulis quote, artikel (draft) dan
+    /// upload galeri; editor juga menerbitkan artikel dan memoderasi
+    /// galeri; admin boleh semuanya.
+    pub fn can(self, permission: Permission) -> bool {
+        match self {
+            UserRoleEnum::Admin => true,
+            UserRoleEnum::Editor => permission != Permission::ManageUsers,
+            UserRoleEnum::Author => matches!(
+                permission,
+                Permission::WriteQuote | Permission::UploadGallery | Permission::WriteArticle
+            ),
+            UserRoleEnum::Reader => false,
+        }
+    }
+}
+
+// Body PUT /users/{username}/role
+#[derive(Debug, Deserialize)]
+pub struct RolePayload {
+    pub role: UserRoleEnum,
+}
+
+// Profil publik, GET /users/{username}
+#[derive(Debug, Clone, Serialize)]
+pub struct PublicUser {
+    pub id: Uuid,
+    pub username: String,
+    pub full_name: Option<String>,
+    pub avatar_url: Option<String>,
+    pub bio: Option<String>,
+    pub website: Option<String>,
+    pub location: Option<String>,
+    pub created_at: NaiveDateTime,
+}
+
+impl From<User> for PublicUser {
+    fn from(user: User) -> Self {
+        PublicUser {
+            id: user.id,
+            username: user.username,
+            full_name: user.full_name,
+            avatar_url: user.avatar.as_deref().map(storage::avatar_url),
+            bio: user.bio,
+            website: user.website,
+            location: user.location,
+            created_at: user.created_at,
+        }
+    }
+}
+
+// Data akun sendiri, GET /users/me
+#[derive(Debug, Serialize)]
+pub struct SelfUser {
+    pub id: Uuid,
+    pub username: String,
+    pub email: String,
+    pub full_name: Option<String>,
+    pub avatar_url: Option<String>,
+    pub bio: Option<String>,
+    pub website: Option<String>,
+    pub location: Option<String>,
+    pub created_at: NaiveDateTime,
+    pub role: UserRoleEnum,
+    pub watermark_default: bool,
+    pub email_verified_at: Option<NaiveDateTime>,
+}
+
+impl From<User> for SelfUser {
+    fn from(user: User) -> Self {
+        SelfUser {
+            id: user.id,
+            username: user.username,
+            email: user.email,
+            full_name: user.full_name,
+            avatar_url: user.avatar.as_deref().map(storage::avatar_url),
+            bio: user.bio,
+            website: user.website,
+            location: user.location,
+            created_at: user.created_at,
+            role: user.role,
+            watermark_default: user.watermark_default,
+            email_verified_at: user.email_verified_at,
+        }
+    }
+}
+
+// Tampilan user untuk admin (daftar user, user yang baru dibuat, ganti role)
+#[derive(Debug, Serialize)]
+pub struct AdminUser {
+    pub id: Uuid,
+    pub username: String,
+    pub email: String,
+    pub full_name: Option<String>,
+    pub avatar_url: Option<String>,
+    pub created_at: NaiveDateTime,
+    pub role: UserRoleEnum,
+    pub email_verified_at: Option<NaiveDateTime>,
+    pub deleted_at: Option<NaiveDateTime>,
+}
+
+impl From<User> for AdminUser {
+    fn from(user: User) -> Self {
+        AdminUser {
+            id: user.id,
+            username: user.username,
+            email: user.email,
+            full_name: user.full_name,
+            avatar_url: user.avatar.as_deref().map(storage::avatar_url),
+            created_at: user.created_at,
+            role: user.role,
+            email_verified_at: user.email_verified_at,
+            deleted_at: user.deleted_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, DbEnum, Serialize, Deserialize, PartialEq)]
+#[ExistingTypePath = "crate::schema::sql_types::UserTokenPurpose"]
+pub enum UserTokenPurposeEnum {
+    EmailVerification,
+    PasswordReset
+}
+
+// Token sekali pakai milik user; token aslinya hanya dikirim lewat email
+#[derive(Debug, Queryable, Selectable)]
+#[diesel(table_name = user_tokens)]
+pub struct UserToken {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub purpose: UserTokenPurposeEnum,
+    pub token_hash: String,
+    pub expires_at: NaiveDateTime,
+    pub used_at: Option<NaiveDateTime>,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = user_tokens)]
+pub struct NewUserToken {
+    pub user_id: Uuid,
+    pub purpose: UserTokenPurposeEnum,
+    pub token_hash: String,
+    pub expires_at: NaiveDateTime,
+}
+
+// Body POST /auth/verify-email
+#[derive(Debug, Deserialize)]
+pub struct VerifyEmailPayload {
+    pub token: String,
+}
+
+// Body POST /auth/forgot-password
+#[derive(Debug, Deserialize)]
+pub struct ForgotPasswordPayload {
+    pub email: String,
+}
+
+// Body POST /auth/reset-password
+#[derive(Deserialize)]
+pub struct ResetPasswordPayload {
+    pub token: String,
+    pub password: String,
+}
+
+// Body PATCH /users/me; field yang tidak dikirim tidak diubah,
+// string kosong menghapus full_name, bio, website dan location
+#[derive(Debug, Deserialize)]
+pub struct UpdateProfilePayload {
+    pub username: Option<String>,
+    pub email: Option<String>,
+    pub full_name: Option<String>,
+    pub bio: Option<String>,
+    pub website: Option<String>,
+    pub location: Option<String>,
+}
+
+// Body POST /users/me/password
+#[derive(Deserialize)]
+pub struct ChangePasswordPayload {
+    pub current_password: String,
+    pub new_password: String,
+}
+
+// Body POST /users (admin); password di-hash server, sama seperti register
+#[derive(Deserialize)]
+pub struct CreateUserPayload {
+    pub username: String,
+    pub email: String,
+    pub password: String,
+    pub full_name: Option<String>,
+}
+
+// Body DELETE /users/me, konfirmasi dengan password
+#[derive(Deserialize)]
+pub struct DeleteAccountPayload {
+    pub password: String,
+}
+
+// Satu percobaan login (berhasil atau gagal) untuk akun yang ada
+#[derive(Debug, Serialize, Queryable, Selectable)]
+#[diesel(table_name = login_history)]
+pub struct LoginHistory {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub succeeded: bool,
+    pub ip_address: Option<String>,
+    pub user_agent: Option<String>,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = login_history)]
+pub struct NewLoginHistory {
+    pub user_id: Uuid,
+    pub succeeded: bool,
+    pub ip_address: Option<String>,
+    pub user_agent: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, DbEnum, Serialize, Deserialize, PartialEq)]
+#[ExistingTypePath = "crate::schema::sql_types::LoginThrottleScope"]
+pub enum LoginThrottleScopeEnum {
+    Username,
+    Ip
+}
+
+// Hitungan login gagal untuk satu username atau satu IP
+#[derive(Debug, Queryable, Selectable, Insertable)]
+#[diesel(table_name = login_throttles)]
+pub struct LoginThrottle {
+    pub scope: LoginThrottleScopeEnum,
+    pub subject: String,
+    pub failures: i32,
+    pub last_failed_at: NaiveDateTime,
+    pub locked_until: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Clone, Copy, DbEnum, Serialize, Deserialize, PartialEq)]
+#[ExistingTypePath = "crate::schema::sql_types::ExportStatus"]
+pub enum ExportStatusEnum {
+    Pending,
+    Processing,
+    Ready,
+    Failed,
+    Expired
+}
+
+// Ekspor data pribadi; file ZIP-nya ada di EXPORT_DIR/<id>.zip
+#[derive(Debug, Serialize, Queryable, Selectable)]
+#[diesel(table_name = data_exports)]
+pub struct DataExport {
+    pub id: Uuid,
+    #[serde(skip_serializing)]
+    pub user_id: Uuid,
+    pub status: ExportStatusEnum,
+    pub size_bytes: Option<i64>,
+    pub error: Option<String>,
+    pub created_at: NaiveDateTime,
+    pub completed_at: Option<NaiveDateTime>,
+    pub expires_at: Option<NaiveDateTime>,
+}
+
+// Response ekspor; download_url hanya ada kalau statusnya ready
+#[derive(Debug, Serialize)]
+pub struct DataExportView {
+    #[serde(flatten)]
+    pub export: DataExport,
+    pub download_url: Option<String>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = follows)]
+pub struct NewFollow {
+    pub follower_id: Uuid,
+    pub followee_id: Uuid,
+}
+
+// Satu baris daftar follower/following
+#[derive(Debug, Serialize)]
+pub struct FollowEntry {
+    #[serde(flatten)]
+    pub user: PublicUser,
+    pub followed_at: NaiveDateTime,
+}
+
+// Halaman hasil dengan cursor; next_cursor kosong berarti sudah habis
+#[derive(Debug, Serialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub next_cursor: Option<String>,
+}
+
+// Isi item feed, dibedakan lewat field "type"
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", content = "data", rename_all = "lowercase")]
+pub enum FeedContent {
+    Quote(Quote),
+    Article(ArticleSummary),
+    Gallery(Box<NFT>),
+}
+
+// Item feed GET /feed: konten + user yang mempostingnya
+#[derive(Debug, Serialize)]
+pub struct FeedItem {
+    #[serde(flatten)]
+    pub content: FeedContent,
+    pub posted_at: NaiveDateTime,
+    pub user: PublicUser,
+}
+
+// Preferensi user, body/response PATCH /users/me/preferences
+#[derive(Debug, Serialize, Deserialize, AsChangeset)]
+#[diesel(table_name = users)]
+pub struct UserPreferences {
+    pub watermark_default: Option<bool>,
+}
+
+// Selalu disusun server setelah validasi; tidak pernah langsung dari body request
+#[derive(Debug, Insertable)]
 #[diesel(table_name = users)]
 pub struct NewUser {
     pub username: String,
@@ -86,7 +631,29 @@ pub struct Article {
     pub author_id: Uuid,
     pub published_at: Option<NaiveDateTime>,
     pub created_at: NaiveDateTime,
-    pub updated_at: NaiveDateTime
+    pub updated_at: NaiveDateTime,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deleted_at: Option<NaiveDateTime>
+}
+
+// Response artikel beserta profil publik penulisnya
+#[derive(Debug, Serialize)]
+pub struct ArticleWithAuthor {
+    #[serde(flatten)]
+    pub article: Article,
+    pub author: PublicUser,
+}
+
+// Artikel tanpa konten, untuk feed
+#[derive(Debug, Serialize, Queryable, Selectable)]
+#[diesel(table_name = articles)]
+pub struct ArticleSummary {
+    pub id: Uuid,
+    pub title: String,
+    pub slug: String,
+    pub excerpt: Option<String>,
+    pub author_id: Uuid,
+    pub published_at: Option<NaiveDateTime>,
 }
 
 #[derive(Debug, Deserialize, Insertable)]
@@ -97,5 +664,84 @@ pub struct NewArticle {
     pub excerpt: String,
     pub content: Value,
     pub status: ArticleStatusEnum,
+    // Diisi dari token, bukan dari body request
+    #[serde(skip_deserializing)]
     pub author_id: Uuid,
+    // Diisi server saat artikel langsung terbit
+    #[serde(skip_deserializing)]
+    pub published_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Clone, Copy, DbEnum, Serialize, Deserialize, PartialEq)]
+#[ExistingTypePath = "crate::schema::sql_types::Visibility"]
+pub enum VisibilityEnum {
+    Public,
+    Unlisted,
+    Private
+}
+
+// Cara client menampilkan item galeri: gambar biasa, animasi (GIF/WebP), atau video
+#[derive(Debug, Clone, Copy, DbEnum, Serialize, Deserialize, PartialEq)]
+#[ExistingTypePath = "crate::schema::sql_types::MediaType"]
+pub enum MediaTypeEnum {
+    Image,
+    AnimatedImage,
+    Video
+}
+
+#[derive(Debug, Serialize, Deserialize, Queryable, Selectable, Insertable)]
+#[diesel(table_name = albums)]
+pub struct Album {
+    pub id: Uuid,
+    pub owner_id: Uuid,
+    pub title: String,
+    pub description: Option<String>,
+    pub cover_item_id: Option<Uuid>,
+    pub visibility: VisibilityEnum,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime
+}
+
+// Body POST /albums, owner diambil dari JWT
+#[derive(Debug, Deserialize)]
+pub struct AlbumPayload {
+    pub title: String,
+    pub description: Option<String>,
+    pub cover_item_id: Option<Uuid>,
+    pub visibility: Option<VisibilityEnum>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = albums)]
+pub struct NewAlbum {
+    pub owner_id: Uuid,
+    pub title: String,
+    pub description: Option<String>,
+    pub cover_item_id: Option<Uuid>,
+    pub visibility: VisibilityEnum,
+}
+
+#[derive(Debug, Deserialize, AsChangeset)]
+#[diesel(table_name = albums)]
+pub struct UpdateAlbum {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub cover_item_id: Option<Uuid>,
+    pub visibility: Option<VisibilityEnum>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = album_items)]
+pub struct NewAlbumItem {
+    pub album_id: Uuid,
+    pub nft_id: Uuid,
+    pub position: i32,
+}
+
+// Album beserta item-itemnya sesuai urutan
+#[derive(Debug, Serialize)]
+pub struct AlbumDetail {
+    #[serde(flatten)]
+    pub album: Album,
+    pub items: Vec<NFT>,
 }
\ No newline at end of file
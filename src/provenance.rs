@@ -0,0 +1,72 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::NaiveDateTime;
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier};
+use sha2::{Digest, Sha256};
+use std::env;
+use std::fmt;
+use uuid::Uuid;
+
+/// Kunci signing tidak ada atau formatnya salah.
+#[derive(Debug)]
+pub struct KeyError(pub String);
+
+impl fmt::Display for KeyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+// PROVENANCE_SIGNING_KEY: seed Ed25519 32 byte dalam base64 (`openssl rand -base64 32`)
+fn signing_key() -> Result<SigningKey, KeyError> {
+    let raw = env::var("PROVENANCE_SIGNING_KEY")
+        .map_err(|_| KeyError("PROVENANCE_SIGNING_KEY belum diset".to_string()))?;
+    let seed: [u8; 32] = STANDARD.decode(raw.trim())
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or(KeyError("PROVENANCE_SIGNING_KEY harus 32 byte dalam base64".to_string()))?;
+    Ok(SigningKey::from_bytes(&seed))
+}
+
+/// Public key server (base64), supaya pihak lain bisa memeriksa signature
+/// tanpa lewat endpoint verify.
+pub fn public_key() -> Result<String, KeyError> {
+    Ok(STANDARD.encode(signing_key()?.verifying_key().to_bytes()))
+}
+
+/// SHA-256 (hex) dari isi satu record transfer. Waktu ditulis dengan presisi
+/// mikrodetik, sama dengan kolom TIMESTAMP Postgres.
+pub fn record_hash(
+    nft_id: Uuid,
+    sequence: i32,
+    from_user: Option<Uuid>,
+    to_user: Uuid,
+    transferred_at: NaiveDateTime,
+    prev_hash: Option<&str>
+) -> String {
+    let canonical = format!(
+        "{}|{}|{}|{}|{}|{}",
+        nft_id,
+        sequence,
+        from_user.map(|id| id.to_string()).unwrap_or_default(),
+        to_user,
+        transferred_at.format("%Y-%m-%dT%H:%M:%S%.6f"),
+        prev_hash.unwrap_or_default()
+    );
+    format!("{:x}", Sha256::digest(canonical.as_bytes()))
+}
+
+/// Signature Ed25519 (base64) atas string hash record.
+pub fn sign(hash: &str) -> Result<String, KeyError> {
+    Ok(STANDARD.encode(signing_key()?.sign(hash.as_bytes()).to_bytes()))
+}
+
+pub fn verify(hash: &str, signature: &str) -> Result<bool, KeyError> {
+    let key = signing_key()?.verifying_key();
+    let Some(signature) = STANDARD.decode(signature)
+        .ok()
+        .and_then(|bytes| Signature::from_slice(&bytes).ok())
+    else {
+        return Ok(false);
+    };
+    Ok(key.verify(hash.as_bytes(), &signature).is_ok())
+}
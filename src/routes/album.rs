@@ -0,0 +1,24 @@
+use axum::{
+    routing::{
+        get,
+        post,
+        patch,
+        put,
+        delete
+    },
+    middleware::{self},
+    Router
+};
+use crate::handlers::album;
+use crate::middlewares;
+use quoteyourlife_be::models::Permission;
+
+pub fn router() -> Router {
+    Router::new()
+        .route("/", post(album::create_new_album).layer(middleware::from_fn_with_state(Permission::UploadGallery, middlewares::jwt::require_permission)))
+        .route("/", get(album::get_all_albums))
+        .route("/{id}", patch(album::update_album).delete(album::delete_album).layer(middleware::from_fn_with_state(Permission::UploadGallery, middlewares::jwt::require_permission)))
+        .route("/{id}", get(album::get_album))
+        .route("/{id}/items", put(album::set_album_items).post(album::add_album_item).layer(middleware::from_fn_with_state(Permission::UploadGallery, middlewares::jwt::require_permission)))
+        .route("/{id}/items/{item_id}", delete(album::remove_album_item).layer(middleware::from_fn_with_state(Permission::UploadGallery, middlewares::jwt::require_permission)))
+}
@@ -8,9 +8,10 @@ use axum::{
 };
 use crate::handlers::article;
 use crate::middlewares;
+use quoteyourlife_be::models::Permission;
 
 pub fn router() -> Router {
     Router::new()
-        .route("/", post(article::create_new_article).layer(middleware::from_fn(middlewares::jwt::jwt_validation)))
+        .route("/", post(article::create_new_article).layer(middleware::from_fn_with_state(Permission::WriteArticle, middlewares::jwt::require_permission)))
         .route("/", get(article::get_all_articles))
 }
\ No newline at end of file
@@ -3,12 +3,19 @@ use axum::{
         get,
         post
     },
+    middleware::{self},
     Router
 };
-use crate::handlers::auth;
+use crate::handlers::{account, auth};
+use crate::middlewares;
 
 pub fn router() -> Router {
     Router::new()
+        .route("/register", post(auth::register))
         .route("/login", post(auth::login))
         .route("/verify", get(auth::verify_jwt))
-}
\ No newline at end of file
+        .route("/forgot-password", post(account::forgot_password))
+        .route("/reset-password", post(account::reset_password))
+        .route("/verify-email", post(account::verify_email))
+        .route("/verify-email/resend", post(account::resend_verification).layer(middleware::from_fn(middlewares::jwt::jwt_validation)))
+}
@@ -0,0 +1,10 @@
+use axum::{
+    routing::get,
+    Router
+};
+use crate::handlers::export;
+
+pub fn router() -> Router {
+    Router::new()
+        .route("/{id}", get(export::download_export))
+}
@@ -0,0 +1,12 @@
+use axum::{
+    routing::get,
+    middleware::{self},
+    Router
+};
+use crate::handlers::feed;
+use crate::middlewares;
+
+pub fn router() -> Router {
+    Router::new()
+        .route("/", get(feed::get_feed).layer(middleware::from_fn(middlewares::jwt::jwt_validation)))
+}
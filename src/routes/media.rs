@@ -0,0 +1,11 @@
+use axum::{
+    routing::get,
+    Router
+};
+use crate::handlers::media;
+
+pub fn router() -> Router {
+    Router::new()
+        .route("/avatars/{filename}", get(media::get_avatar))
+        .route("/{id}/{variant}", get(media::get_media))
+}
@@ -1,5 +1,9 @@
 pub mod auth;
+pub mod album;
 pub mod article;
+pub mod export;
+pub mod feed;
+pub mod media;
 pub mod nft;
 pub mod quote;
 pub mod user;
\ No newline at end of file
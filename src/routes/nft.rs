@@ -1,16 +1,28 @@
 use axum::{
     routing::{
         get,
-        post
+        post,
+        patch
     },
     middleware::{self},
     Router
 };
-use crate::handlers::nft;
+use crate::handlers::{archive, nft, provenance};
 use crate::middlewares;
+use quoteyourlife_be::models::Permission;
 
 pub fn router() -> Router {
     Router::new()
-        .route("/", post(nft::create_new_nft).layer(middleware::from_fn(middlewares::jwt::jwt_validation)))
+        .route("/", post(nft::create_new_nft).layer(middleware::from_fn_with_state(Permission::UploadGallery, middlewares::jwt::require_permission)))
         .route("/", get(nft::get_all_nft))
-}
\ No newline at end of file
+        .route("/archive", get(archive::get_gallery_archive))
+        .route("/alt-text-queue", get(nft::get_alt_text_queue).layer(middleware::from_fn(middlewares::jwt::jwt_validation)))
+        .route("/duplicates", get(nft::get_duplicate_clusters).layer(middleware::from_fn_with_state(Permission::ModerateGallery, middlewares::jwt::require_permission)))
+        .route("/{id}", patch(nft::update_nft).delete(nft::delete_nft).layer(middleware::from_fn_with_state(Permission::UploadGallery, middlewares::jwt::require_permission)))
+        .route("/{id}", get(nft::get_nft))
+        .route("/{id}/metadata.json", get(nft::get_nft_metadata))
+        .route("/{id}/url", get(nft::get_media_url))
+        .route("/{id}/provenance", get(provenance::get_provenance))
+        .route("/{id}/provenance/verify", get(provenance::verify_provenance))
+        .route("/{id}/transfer", post(provenance::transfer_nft).layer(middleware::from_fn_with_state(Permission::UploadGallery, middlewares::jwt::require_permission)))
+}
@@ -8,9 +8,10 @@ use axum::{
 };
 use crate::handlers::quote;
 use crate::middlewares;
+use quoteyourlife_be::models::Permission;
 
 pub fn router() -> Router {
     Router::new()
-        .route("/", post(quote::create_new_quote).layer(middleware::from_fn(middlewares::jwt::jwt_validation)))
+        .route("/", post(quote::create_new_quote).layer(middleware::from_fn_with_state(Permission::WriteQuote, middlewares::jwt::require_permission)))
         .route("/", get(quote::get_all_quotes))
 }
\ No newline at end of file
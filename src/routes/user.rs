@@ -1,17 +1,32 @@
 use axum::{
     routing::{
+        delete,
         get,
-        post
+        post,
+        patch,
+        put
     },
     middleware::{self},
     Router,
 };
 use crate::middlewares;
-use crate::handlers::user;
+use crate::handlers::{avatar, export, follow, lockout, user};
+use quoteyourlife_be::models::Permission;
 
 pub fn router() -> Router {
     Router::new()
-        .route("/", post(user::create_new_user))
-        .route("/", get(user::get_all_users))
-        .layer(middleware::from_fn(middlewares::jwt::jwt_validation))
-}
\ No newline at end of file
+        .route("/", post(user::create_new_user).get(user::get_all_users).layer(middleware::from_fn_with_state(Permission::ManageUsers, middlewares::jwt::require_permission)))
+        .route("/me", get(user::get_me).patch(user::update_me).delete(user::delete_me).layer(middleware::from_fn(middlewares::jwt::jwt_validation)))
+        .route("/me/avatar", put(avatar::update_my_avatar).delete(avatar::delete_my_avatar).layer(middleware::from_fn(middlewares::jwt::jwt_validation)))
+        .route("/me/password", post(user::change_my_password).layer(middleware::from_fn(middlewares::jwt::jwt_validation)))
+        .route("/me/export", post(export::request_export).layer(middleware::from_fn(middlewares::jwt::jwt_validation)))
+        .route("/me/exports", get(export::list_exports).layer(middleware::from_fn(middlewares::jwt::jwt_validation)))
+        .route("/me/usage", get(user::get_my_usage).layer(middleware::from_fn(middlewares::jwt::jwt_validation)))
+        .route("/me/preferences", patch(user::update_my_preferences).layer(middleware::from_fn(middlewares::jwt::jwt_validation)))
+        .route("/{username}", get(user::get_user_profile))
+        .route("/{username}/follow", post(follow::follow_user).delete(follow::unfollow_user).layer(middleware::from_fn(middlewares::jwt::jwt_validation)))
+        .route("/{username}/followers", get(follow::get_followers))
+        .route("/{username}/following", get(follow::get_following))
+        .route("/{username}/lockout", delete(lockout::unlock_user).layer(middleware::from_fn_with_state(Permission::ManageUsers, middlewares::jwt::require_permission)))
+        .route("/{username}/role", put(user::update_user_role).layer(middleware::from_fn_with_state(Permission::ManageUsers, middlewares::jwt::require_permission)))
+}
@@ -4,6 +4,56 @@ pub mod sql_types {
     #[derive(diesel::query_builder::QueryId, diesel::sql_types::SqlType)]
     #[diesel(postgres_type(name = "article_status"))]
     pub struct ArticleStatus;
+
+    #[derive(diesel::query_builder::QueryId, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "export_status"))]
+    pub struct ExportStatus;
+
+    #[derive(diesel::query_builder::QueryId, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "login_throttle_scope"))]
+    pub struct LoginThrottleScope;
+
+    #[derive(diesel::query_builder::QueryId, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "media_type"))]
+    pub struct MediaType;
+
+    #[derive(diesel::query_builder::QueryId, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "user_role"))]
+    pub struct UserRole;
+
+    #[derive(diesel::query_builder::QueryId, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "user_token_purpose"))]
+    pub struct UserTokenPurpose;
+
+    #[derive(diesel::query_builder::QueryId, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "visibility"))]
+    pub struct Visibility;
+}
+
+diesel::table! {
+    album_items (album_id, nft_id) {
+        album_id -> Uuid,
+        nft_id -> Uuid,
+        position -> Int4,
+        added_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::Visibility;
+
+    albums (id) {
+        id -> Uuid,
+        owner_id -> Uuid,
+        #[max_length = 255]
+        title -> Varchar,
+        description -> Nullable<Text>,
+        cover_item_id -> Nullable<Uuid>,
+        visibility -> Visibility,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
 }
 
 diesel::table! {
@@ -23,10 +73,66 @@ diesel::table! {
         published_at -> Nullable<Timestamptz>,
         created_at -> Timestamptz,
         updated_at -> Timestamptz,
+        deleted_at -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::ExportStatus;
+
+    data_exports (id) {
+        id -> Uuid,
+        user_id -> Uuid,
+        status -> ExportStatus,
+        size_bytes -> Nullable<Int8>,
+        error -> Nullable<Text>,
+        created_at -> Timestamp,
+        completed_at -> Nullable<Timestamp>,
+        expires_at -> Nullable<Timestamp>,
+        heartbeat_at -> Timestamp,
     }
 }
 
 diesel::table! {
+    follows (follower_id, followee_id) {
+        follower_id -> Uuid,
+        followee_id -> Uuid,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    login_history (id) {
+        id -> Uuid,
+        user_id -> Uuid,
+        succeeded -> Bool,
+        #[max_length = 45]
+        ip_address -> Nullable<Varchar>,
+        user_agent -> Nullable<Text>,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::LoginThrottleScope;
+
+    login_throttles (scope, subject) {
+        scope -> LoginThrottleScope,
+        #[max_length = 255]
+        subject -> Varchar,
+        failures -> Int4,
+        last_failed_at -> Timestamp,
+        locked_until -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::Visibility;
+    use super::sql_types::MediaType;
+
     nft (id) {
         id -> Uuid,
         title -> Varchar,
@@ -34,6 +140,40 @@ diesel::table! {
         author -> Varchar,
         filename -> Varchar,
         created_at -> Timestamp,
+        width -> Nullable<Int4>,
+        height -> Nullable<Int4>,
+        dominant_colors -> Array<Nullable<Text>>,
+        taken_at -> Nullable<Timestamp>,
+        owner_id -> Nullable<Uuid>,
+        attributes -> Jsonb,
+        cid -> Nullable<Varchar>,
+        phash -> Nullable<Int8>,
+        visibility -> Visibility,
+        #[max_length = 64]
+        blurhash -> Nullable<Varchar>,
+        lqip -> Nullable<Text>,
+        size_bytes -> Int8,
+        watermark -> Bool,
+        media_type -> MediaType,
+        duration_ms -> Nullable<Int4>,
+        alt_text -> Nullable<Text>,
+        long_description -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    nft_transfers (id) {
+        id -> Uuid,
+        nft_id -> Uuid,
+        sequence -> Int4,
+        from_user_id -> Nullable<Uuid>,
+        to_user_id -> Uuid,
+        transferred_at -> Timestamp,
+        #[max_length = 64]
+        prev_hash -> Nullable<Varchar>,
+        #[max_length = 64]
+        record_hash -> Varchar,
+        signature -> Text,
     }
 }
 
@@ -43,10 +183,30 @@ diesel::table! {
         text -> Varchar,
         author -> Varchar,
         created_at -> Timestamp,
+        user_id -> Nullable<Uuid>,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::UserTokenPurpose;
+
+    user_tokens (id) {
+        id -> Uuid,
+        user_id -> Uuid,
+        purpose -> UserTokenPurpose,
+        #[max_length = 64]
+        token_hash -> Varchar,
+        expires_at -> Timestamp,
+        used_at -> Nullable<Timestamp>,
+        created_at -> Timestamp,
     }
 }
 
 diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::UserRole;
+
     users (id) {
         id -> Uuid,
         #[max_length = 50]
@@ -58,9 +218,45 @@ diesel::table! {
         #[max_length = 100]
         full_name -> Nullable<Varchar>,
         created_at -> Timestamptz,
+        watermark_default -> Bool,
+        email_verified_at -> Nullable<Timestamp>,
+        tokens_revoked_at -> Nullable<Timestamp>,
+        role -> UserRole,
+        deleted_at -> Nullable<Timestamp>,
+        #[max_length = 255]
+        avatar -> Nullable<Varchar>,
+        bio -> Nullable<Text>,
+        #[max_length = 255]
+        website -> Nullable<Varchar>,
+        #[max_length = 100]
+        location -> Nullable<Varchar>,
+        token_version -> Int4,
     }
 }
 
+diesel::joinable!(album_items -> albums (album_id));
+diesel::joinable!(album_items -> nft (nft_id));
+diesel::joinable!(albums -> nft (cover_item_id));
+diesel::joinable!(albums -> users (owner_id));
 diesel::joinable!(articles -> users (author_id));
+diesel::joinable!(data_exports -> users (user_id));
+diesel::joinable!(login_history -> users (user_id));
+diesel::joinable!(nft -> users (owner_id));
+diesel::joinable!(nft_transfers -> nft (nft_id));
+diesel::joinable!(quotes -> users (user_id));
+diesel::joinable!(user_tokens -> users (user_id));
 
-diesel::allow_tables_to_appear_in_same_query!(articles, nft, quotes, users,);
+diesel::allow_tables_to_appear_in_same_query!(
+    album_items,
+    albums,
+    articles,
+    data_exports,
+    follows,
+    login_history,
+    login_throttles,
+    nft,
+    nft_transfers,
+    quotes,
+    user_tokens,
+    users,
+);
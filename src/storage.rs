@@ -0,0 +1,129 @@
+use std::env;
+use std::fmt;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+// Folder tempat file upload galeri disimpan
+pub fn public_dir() -> PathBuf {
+    PathBuf::from(env::var("PUBLIC_DIR").expect("Set the Public Directory in .env"))
+}
+
+// Avatar user, terpisah dari file galeri
+pub fn avatar_dir() -> PathBuf {
+    public_dir().join("avatars")
+}
+
+/// URL avatar relatif terhadap root API (lihat `GET /media/avatars/{filename}`).
+/// Nama file baru di setiap upload, jadi URL-nya boleh di-cache selamanya.
+pub fn avatar_url(filename: &str) -> String {
+    format!("/media/avatars/{}", filename)
+}
+
+/// Versi file yang disimpan untuk satu item galeri. `Original` adalah file
+/// hasil upload (sudah dibersihkan dari EXIF); `Large`/`Medium` versi yang
+/// diperkecil; `Poster` frame pertama (JPEG) untuk item animasi/video.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Variant {
+    Original,
+    Large,
+    Medium,
+    Poster,
+}
+
+impl Variant {
+    pub const ALL: [Variant; 4] = [Variant::Original, Variant::Large, Variant::Medium, Variant::Poster];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Variant::Original => "original",
+            Variant::Large => "large",
+            Variant::Medium => "medium",
+            Variant::Poster => "poster",
+        }
+    }
+
+    // Sisi terpanjang maksimum (pixel); None = ukuran asli
+    pub fn max_dimension(&self) -> Option<u32> {
+        match self {
+            Variant::Original => None,
+            Variant::Large => Some(1600),
+            Variant::Medium => Some(800),
+            Variant::Poster => Some(1600),
+        }
+    }
+}
+
+/// Poster selalu JPEG, apa pun format original-nya (GIF, WebP, MP4, WebM).
+pub const POSTER_EXTENSION: &str = "jpg";
+
+impl fmt::Display for Variant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Variant {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Variant::ALL.into_iter()
+            .find(|variant| variant.as_str() == s)
+            .ok_or_else(|| format!("Variant '{}' tidak dikenali", s))
+    }
+}
+
+/// Nama file satu variant: `<uuid>.jpg` untuk original,
+/// `<uuid>.<variant>.jpg` untuk yang lain.
+pub fn variant_filename(filename: &str, variant: Variant) -> String {
+    if variant == Variant::Original {
+        return filename.to_string();
+    }
+    let (stem, _) = filename.rsplit_once('.').unwrap_or((filename, ""));
+    match native_extension(filename, variant) {
+        "" => format!("{}.{}", filename, variant),
+        ext => format!("{}.{}.{}", stem, variant, ext),
+    }
+}
+
+// Format asli satu variant: sama dengan original, kecuali poster
+fn native_extension(filename: &str, variant: Variant) -> &str {
+    if variant == Variant::Poster {
+        return POSTER_EXTENSION;
+    }
+    filename.rsplit_once('.').map(|(_, ext)| ext).unwrap_or("")
+}
+
+/// Format modern yang mungkin disimpan di samping format asli, urut dari
+/// yang paling disukai. Hanya disimpan kalau hasilnya lebih kecil.
+pub const ALTERNATE_FORMATS: [&str; 2] = ["avif", "webp"];
+
+/// Nama file variant dalam format lain: `<uuid>.<variant>.<ext>`.
+/// Kalau `ext` sama dengan format asli variant itu, hasilnya sama dengan
+/// `variant_filename`.
+pub fn rendition_filename(filename: &str, variant: Variant, ext: &str) -> String {
+    let (stem, _) = filename.rsplit_once('.').unwrap_or((filename, ""));
+    if ext.eq_ignore_ascii_case(native_extension(filename, variant)) {
+        return variant_filename(filename, variant);
+    }
+    format!("{}.{}.{}", stem, variant, ext)
+}
+
+/// Semua file di `PUBLIC_DIR` yang (mungkin) dimiliki satu row `nft`,
+/// berdasarkan kolom `filename`. Dipakai saat menghapus item dan oleh
+/// `reconcile_media`; rendition format modern bisa saja tidak ada.
+pub fn stored_files(filename: &str) -> Vec<String> {
+    let mut files = Vec::new();
+    for variant in Variant::ALL {
+        files.push(variant_filename(filename, variant));
+        for ext in ALTERNATE_FORMATS {
+            let name = rendition_filename(filename, variant, ext);
+            if !files.contains(&name) {
+                files.push(name);
+            }
+        }
+    }
+    files
+}
@@ -0,0 +1,28 @@
+use axum::http::{header, HeaderMap};
+use std::env;
+use std::net::SocketAddr;
+
+// User agent yang disimpan dipotong, header ini bebas diisi client
+const MAX_USER_AGENT_LENGTH: usize = 512;
+
+/// IP client. `X-Forwarded-For` hanya dipakai kalau server di belakang
+/// reverse proxy (`TRUST_PROXY_HEADERS=true`), selain itu header tersebut
+/// bisa dipalsukan siapa saja.
+pub fn client_ip(headers: &HeaderMap, addr: SocketAddr) -> String {
+    let trust_proxy = env::var("TRUST_PROXY_HEADERS")
+        .map(|v| v.eq_ignore_ascii_case("true"))
+        .unwrap_or(false);
+    if trust_proxy
+        && let Some(forwarded) = headers.get("X-Forwarded-For").and_then(|h| h.to_str().ok())
+        && let Some(first) = forwarded.split(',').next().map(str::trim).filter(|ip| !ip.is_empty())
+    {
+        return first.to_string();
+    }
+    addr.ip().to_string()
+}
+
+pub fn user_agent(headers: &HeaderMap) -> Option<String> {
+    headers.get(header::USER_AGENT)
+        .and_then(|h| h.to_str().ok())
+        .map(|agent| agent.chars().take(MAX_USER_AGENT_LENGTH).collect())
+}
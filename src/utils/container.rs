@@ -0,0 +1,727 @@
+//! Parser container media yang ditulis sendiri (tanpa ffmpeg): deteksi format
+//! dari isi file, durasi/dimensi video, dan pembersihan metadata tanpa
+//! encode ulang. Offset data (mis. tabel `stco` MP4) tidak berubah karena
+//! box/element metadata ditimpa, bukan dipotong.
+
+use std::ops::Range;
+
+use crate::handlers::AppError;
+
+/// Container yang diterima selain gambar diam (JPEG/PNG).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Container {
+    Gif,
+    WebP,
+    Mp4,
+    WebM,
+}
+
+impl Container {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Container::Gif => "gif",
+            Container::WebP => "webp",
+            Container::Mp4 => "mp4",
+            Container::WebM => "webm",
+        }
+    }
+
+    pub fn is_video(&self) -> bool {
+        matches!(self, Container::Mp4 | Container::WebM)
+    }
+}
+
+/// Dimensi dan durasi yang dibaca dari header video.
+#[derive(Debug)]
+pub struct VideoInfo {
+    pub width: u32,
+    pub height: u32,
+    pub duration_ms: u64,
+}
+
+// Brand ISO-BMFF yang sebenarnya gambar diam (HEIF/AVIF), bukan video
+const IMAGE_BRANDS: [&[u8; 4]; 6] = [b"avif", b"avis", b"heic", b"heix", b"mif1", b"msf1"];
+
+/// Kenali container dari magic bytes, bukan dari nama file/Content-Type.
+pub fn sniff(data: &[u8]) -> Option<Container> {
+    if data.starts_with(b"GIF87a") || data.starts_with(b"GIF89a") {
+        return Some(Container::Gif);
+    }
+    if data.len() >= 12 && &data[0..4] == b"RIFF" && &data[8..12] == b"WEBP" {
+        return Some(Container::WebP);
+    }
+    if data.len() >= 12 && &data[4..8] == b"ftyp" {
+        let brand: &[u8; 4] = data[8..12].try_into().ok()?;
+        return (!IMAGE_BRANDS.contains(&brand)).then_some(Container::Mp4);
+    }
+    if data.starts_with(&EBML_HEADER.to_be_bytes()) && ebml_doc_type(data).as_deref() == Some("webm") {
+        return Some(Container::WebM);
+    }
+    None
+}
+
+/// GIF/WebP dengan lebih dari satu frame. Yang hanya satu frame
+/// diperlakukan seperti gambar diam biasa.
+pub fn is_animated(data: &[u8], container: Container) -> bool {
+    match container {
+        Container::Gif => walk_gif(data).map(|(_, frames)| frames > 1).unwrap_or(false),
+        Container::WebP => riff_chunks(data)
+            .ok()
+            .and_then(|chunks| chunks.into_iter().find(|chunk| &chunk.fourcc == b"VP8X"))
+            .is_some_and(|chunk| data.get(chunk.payload.start).is_some_and(|flags| flags & VP8X_ANIMATION != 0)),
+        Container::Mp4 | Container::WebM => true,
+    }
+}
+
+/// Jumlah frame GIF/WebP dihitung dari struktur file, tanpa decode pixel.
+pub fn frame_count(data: &[u8], container: Container) -> Result<usize, AppError> {
+    match container {
+        Container::Gif => walk_gif(data).map(|(_, frames)| frames),
+        Container::WebP => Ok(riff_chunks(data)?.iter().filter(|chunk| &chunk.fourcc == b"ANMF").count().max(1)),
+        other => Err(invalid(format!("{:?} bukan container animasi", other))),
+    }
+}
+
+/// Buang metadata (komentar GIF, EXIF/XMP WebP, `udta`/`meta` MP4, Tags
+/// WebM) tanpa menyentuh data gambar/video.
+pub fn strip_metadata(data: &[u8], container: Container) -> Result<Vec<u8>, AppError> {
+    match container {
+        Container::Gif => walk_gif(data).map(|(stripped, _)| stripped),
+        Container::WebP => strip_webp(data),
+        Container::Mp4 => {
+            let mut out = data.to_vec();
+            blank_mp4_metadata(&mut out, 0, data.len(), 0)?;
+            Ok(out)
+        }
+        Container::WebM => strip_webm(data),
+    }
+}
+
+/// Dimensi track video pertama dan durasi total.
+pub fn probe_video(data: &[u8], container: Container) -> Result<VideoInfo, AppError> {
+    match container {
+        Container::Mp4 => probe_mp4(data),
+        Container::WebM => probe_webm(data),
+        other => Err(invalid(format!("{:?} bukan container video", other))),
+    }
+}
+
+fn invalid(reason: impl Into<String>) -> AppError {
+    AppError::BadRequest(format!("File media tidak valid: {}", reason.into()))
+}
+
+fn read_u32_le(data: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(data.get(at..at + 4)?.try_into().ok()?))
+}
+
+fn read_u32_be(data: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(data.get(at..at + 4)?.try_into().ok()?))
+}
+
+fn read_u64_be(data: &[u8], at: usize) -> Option<u64> {
+    Some(u64::from_be_bytes(data.get(at..at + 8)?.try_into().ok()?))
+}
+
+// ---------------------------------------------------------------------------
+// GIF
+
+const GIF_EXTENSION: u8 = 0x21;
+const GIF_IMAGE: u8 = 0x2C;
+const GIF_TRAILER: u8 = 0x3B;
+const GIF_COMMENT: u8 = 0xFE;
+const GIF_APPLICATION: u8 = 0xFF;
+// Application extension yang mengatur looping animasi, selain itu dibuang (mis. XMP)
+const GIF_LOOP_APPLICATIONS: [&[u8]; 2] = [b"NETSCAPE2.0", b"ANIMEXTS1.0"];
+
+/// Salin semua block GIF kecuali komentar dan application extension selain
+/// looping. Mengembalikan file hasil dan jumlah frame.
+fn walk_gif(data: &[u8]) -> Result<(Vec<u8>, usize), AppError> {
+    let truncated = || invalid("GIF terpotong");
+    let packed = *data.get(10).ok_or_else(truncated)?;
+    let mut pos = 13 + color_table_len(packed);
+    let mut out = data.get(..pos).ok_or_else(truncated)?.to_vec();
+    let mut frames = 0;
+
+    loop {
+        let start = pos;
+        // Beberapa encoder tidak menulis trailer; cukup ditambahkan
+        let Some(&block) = data.get(pos) else {
+            out.push(GIF_TRAILER);
+            return Ok((out, frames));
+        };
+        match block {
+            GIF_TRAILER => {
+                out.push(GIF_TRAILER);
+                return Ok((out, frames));
+            }
+            GIF_IMAGE => {
+                let packed = *data.get(pos + 9).ok_or_else(truncated)?;
+                // Descriptor 10 byte, color table lokal, lalu LZW minimum code size
+                pos = skip_sub_blocks(data, pos + 10 + color_table_len(packed) + 1).ok_or_else(truncated)?;
+                frames += 1;
+                out.extend_from_slice(&data[start..pos]);
+            }
+            GIF_EXTENSION => {
+                let label = *data.get(pos + 1).ok_or_else(truncated)?;
+                pos = skip_sub_blocks(data, pos + 2).ok_or_else(truncated)?;
+                let keep = match label {
+                    GIF_COMMENT => false,
+                    GIF_APPLICATION => data.get(start + 3..start + 14)
+                        .is_some_and(|identifier| GIF_LOOP_APPLICATIONS.contains(&identifier)),
+                    _ => true,
+                };
+                if keep {
+                    out.extend_from_slice(&data[start..pos]);
+                }
+            }
+            other => return Err(invalid(format!("block GIF 0x{:02x} tidak dikenal", other))),
+        }
+    }
+}
+
+fn color_table_len(packed: u8) -> usize {
+    if packed & 0x80 == 0 { 0 } else { 3 << ((packed & 0x07) + 1) }
+}
+
+// Posisi setelah rangkaian sub-block (diakhiri block berukuran 0)
+fn skip_sub_blocks(data: &[u8], mut pos: usize) -> Option<usize> {
+    loop {
+        let len = *data.get(pos)? as usize;
+        pos += 1 + len;
+        if len == 0 {
+            return (pos <= data.len()).then_some(pos);
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// WebP (RIFF)
+
+const VP8X_ANIMATION: u8 = 0x02;
+const VP8X_XMP: u8 = 0x04;
+const VP8X_EXIF: u8 = 0x08;
+
+struct Chunk {
+    fourcc: [u8; 4],
+    payload: Range<usize>,
+}
+
+// Setiap chunk setelah header `RIFF....WEBP`
+fn riff_chunks(data: &[u8]) -> Result<Vec<Chunk>, AppError> {
+    let mut chunks = Vec::new();
+    let mut pos = 12;
+    while pos + 8 <= data.len() {
+        let fourcc: [u8; 4] = data[pos..pos + 4].try_into().expect("panjang 4");
+        let size = read_u32_le(data, pos + 4).expect("panjang 4") as usize;
+        let payload = pos + 8..pos + 8 + size;
+        if payload.end > data.len() {
+            return Err(invalid("chunk WebP terpotong"));
+        }
+        // Payload dengan panjang ganjil diberi padding satu byte
+        pos = payload.end + (size & 1);
+        chunks.push(Chunk { fourcc, payload });
+    }
+    Ok(chunks)
+}
+
+fn strip_webp(data: &[u8]) -> Result<Vec<u8>, AppError> {
+    let mut out = b"RIFF\0\0\0\0WEBP".to_vec();
+    for Chunk { fourcc, payload } in riff_chunks(data)? {
+        if &fourcc == b"EXIF" || &fourcc == b"XMP " {
+            continue;
+        }
+        let chunk_start = out.len();
+        out.extend_from_slice(&data[payload.start - 8..payload.end]);
+        if payload.len() & 1 == 1 {
+            out.push(0);
+        }
+        if &fourcc == b"VP8X" && !payload.is_empty() {
+            out[chunk_start + 8] &= !(VP8X_XMP | VP8X_EXIF);
+        }
+    }
+    let riff_size = (out.len() - 8) as u32;
+    out[4..8].copy_from_slice(&riff_size.to_le_bytes());
+    Ok(out)
+}
+
+// ---------------------------------------------------------------------------
+// MP4 (ISO-BMFF)
+
+// Box yang isinya box lain dan perlu ditelusuri
+const MP4_CONTAINERS: [&[u8; 4]; 4] = [b"moov", b"trak", b"mdia", b"minf"];
+// Box metadata (judul, lokasi GPS, info perangkat perekam)
+const MP4_METADATA: [&[u8; 4]; 3] = [b"udta", b"meta", b"uuid"];
+
+struct Mp4Box {
+    kind: [u8; 4],
+    start: usize,
+    payload: usize,
+    end: usize,
+}
+
+// Box di rentang [start, end)
+fn mp4_boxes(data: &[u8], start: usize, end: usize) -> Result<Vec<Mp4Box>, AppError> {
+    let mut boxes = Vec::new();
+    let mut pos = start;
+    while pos + 8 <= end {
+        let size = read_u32_be(data, pos).expect("panjang 4") as u64;
+        let kind: [u8; 4] = data[pos + 4..pos + 8].try_into().expect("panjang 4");
+        let (header, size) = match size {
+            0 => (8, (end - pos) as u64),
+            1 => (16, read_u64_be(data, pos + 8).ok_or_else(|| invalid("box MP4 terpotong"))?),
+            size => (8, size),
+        };
+        let box_end = pos.checked_add(size as usize).filter(|e| size >= header && *e <= end)
+            .ok_or_else(|| invalid(format!("ukuran box MP4 '{}' tidak valid", String::from_utf8_lossy(&kind))))?;
+        boxes.push(Mp4Box { kind, start: pos, payload: pos + header as usize, end: box_end });
+        pos = box_end;
+    }
+    Ok(boxes)
+}
+
+// Ganti tipe box metadata menjadi `free` dan nol-kan isinya; ukuran tetap sama
+fn blank_mp4_metadata(data: &mut [u8], start: usize, end: usize, depth: usize) -> Result<(), AppError> {
+    for found in mp4_boxes(data, start, end)? {
+        if MP4_METADATA.contains(&&found.kind) {
+            data[found.start + 4..found.start + 8].copy_from_slice(b"free");
+            data[found.payload..found.end].fill(0);
+        } else if MP4_CONTAINERS.contains(&&found.kind) && depth < 4 {
+            blank_mp4_metadata(data, found.payload, found.end, depth + 1)?;
+        }
+    }
+    Ok(())
+}
+
+fn probe_mp4(data: &[u8]) -> Result<VideoInfo, AppError> {
+    let top = mp4_boxes(data, 0, data.len())?;
+    let moov = find_mp4_box(&top, b"moov")
+        .ok_or_else(|| invalid("MP4 tidak memiliki box moov"))?;
+    let moov_boxes = mp4_boxes(data, moov.payload, moov.end)?;
+
+    // mvhd: durasi total dalam satuan timescale
+    let mvhd = find_mp4_box(&moov_boxes, b"mvhd")
+        .ok_or_else(|| invalid("MP4 tidak memiliki box mvhd"))?
+        .payload;
+    let (timescale, duration) = match data.get(mvhd) {
+        Some(1) => (read_u32_be(data, mvhd + 20), read_u64_be(data, mvhd + 24)),
+        Some(_) => (read_u32_be(data, mvhd + 12), read_u32_be(data, mvhd + 16).map(u64::from)),
+        None => (None, None),
+    };
+    let (Some(timescale), Some(duration)) = (timescale, duration) else {
+        return Err(invalid("box mvhd terpotong"));
+    };
+    if timescale == 0 {
+        return Err(invalid("timescale MP4 nol"));
+    }
+
+    // Track video pertama (handler 'vide'); dimensi di tkhd dalam fixed-point 16.16
+    for trak in moov_boxes.iter().filter(|found| &found.kind == b"trak") {
+        let trak_boxes = mp4_boxes(data, trak.payload, trak.end)?;
+        let is_video = trak_boxes.iter()
+            .filter(|found| &found.kind == b"mdia")
+            .filter_map(|mdia| mp4_boxes(data, mdia.payload, mdia.end).ok())
+            .flatten()
+            .any(|found| &found.kind == b"hdlr" && data.get(found.payload + 8..found.payload + 12) == Some(b"vide"));
+        let Some(tkhd) = find_mp4_box(&trak_boxes, b"tkhd").filter(|_| is_video) else {
+            continue;
+        };
+        let dimensions = tkhd.payload + if data.get(tkhd.payload) == Some(&1) { 88 } else { 76 };
+        let (Some(width), Some(height)) = (read_u32_be(data, dimensions), read_u32_be(data, dimensions + 4)) else {
+            return Err(invalid("box tkhd terpotong"));
+        };
+        return Ok(VideoInfo {
+            width: width >> 16,
+            height: height >> 16,
+            duration_ms: duration.saturating_mul(1000) / timescale as u64,
+        });
+    }
+    Err(invalid("MP4 tidak memiliki track video"))
+}
+
+fn find_mp4_box<'a>(boxes: &'a [Mp4Box], kind: &[u8; 4]) -> Option<&'a Mp4Box> {
+    boxes.iter().find(|found| &found.kind == kind)
+}
+
+// ---------------------------------------------------------------------------
+// WebM (EBML/Matroska)
+
+const EBML_HEADER: u32 = 0x1A45DFA3;
+const EBML_DOC_TYPE: u32 = 0x4282;
+const SEGMENT: u32 = 0x18538067;
+const INFO: u32 = 0x1549A966;
+const TIMESTAMP_SCALE: u32 = 0x2AD7B1;
+const DURATION: u32 = 0x4489;
+const TRACKS: u32 = 0x1654AE6B;
+const TRACK_ENTRY: u32 = 0xAE;
+const VIDEO: u32 = 0xE0;
+const PIXEL_WIDTH: u32 = 0xB0;
+const PIXEL_HEIGHT: u32 = 0xBA;
+const TAGS: u32 = 0x1254C367;
+const VOID: u8 = 0xEC;
+// Nilai default TimestampScale: 1 ms dalam nanodetik
+const DEFAULT_TIMESTAMP_SCALE: u64 = 1_000_000;
+
+/// Satu element EBML: ID, awal element, awal data, akhir data
+/// (`None` kalau ukurannya "unknown", biasanya live stream).
+struct Element {
+    id: u32,
+    start: usize,
+    data: usize,
+    end: Option<usize>,
+}
+
+// Variable-length integer EBML; ID menyimpan bit penanda panjang, ukuran tidak
+fn read_vint(data: &[u8], pos: usize, keep_marker: bool) -> Option<(u64, usize, bool)> {
+    let first = *data.get(pos)?;
+    let len = first.leading_zeros() as usize + 1;
+    if len > 8 {
+        return None;
+    }
+    let bytes = data.get(pos..pos + len)?;
+    let mut value = if keep_marker { first as u64 } else { first as u64 & ((1 << (8 - len)) - 1) };
+    for byte in &bytes[1..] {
+        value = (value << 8) | *byte as u64;
+    }
+    // Semua bit data bernilai 1 berarti ukuran tidak diketahui
+    let unknown = !keep_marker && value == (1u64 << (7 * len)) - 1;
+    Some((value, len, unknown))
+}
+
+fn ebml_elements(data: &[u8], start: usize, end: usize) -> Vec<Element> {
+    let mut elements = Vec::new();
+    let mut pos = start;
+    while pos < end {
+        let Some((id, id_len, _)) = read_vint(data, pos, true) else { break };
+        let Some((size, size_len, unknown)) = read_vint(data, pos + id_len, false) else { break };
+        let data_start = pos + id_len + size_len;
+        let data_end = (!unknown).then(|| data_start.saturating_add(size as usize).min(end));
+        elements.push(Element { id: id as u32, start: pos, data: data_start, end: data_end });
+        // Element dengan ukuran unknown: isinya sampai akhir parent, berhenti di sini
+        let Some(next) = data_end else { break };
+        pos = next;
+    }
+    elements
+}
+
+fn ebml_child(data: &[u8], parent: &Element, id: u32, limit: usize) -> Option<Element> {
+    ebml_elements(data, parent.data, parent.end.unwrap_or(limit))
+        .into_iter()
+        .find(|element| element.id == id)
+}
+
+fn ebml_uint(data: &[u8], element: &Element) -> Option<u64> {
+    let bytes = data.get(element.data..element.end?)?;
+    (bytes.len() <= 8).then(|| bytes.iter().fold(0u64, |acc, b| (acc << 8) | *b as u64))
+}
+
+fn ebml_float(data: &[u8], element: &Element) -> Option<f64> {
+    let bytes = data.get(element.data..element.end?)?;
+    match bytes.len() {
+        4 => Some(f32::from_be_bytes(bytes.try_into().ok()?) as f64),
+        8 => Some(f64::from_be_bytes(bytes.try_into().ok()?)),
+        _ => None,
+    }
+}
+
+fn ebml_doc_type(data: &[u8]) -> Option<String> {
+    let header = ebml_elements(data, 0, data.len()).into_iter().next()?;
+    let doc_type = ebml_child(data, &header, EBML_DOC_TYPE, data.len())?;
+    let bytes = data.get(doc_type.data..doc_type.end?)?;
+    Some(String::from_utf8_lossy(bytes).trim_end_matches('\0').to_string())
+}
+
+fn webm_segment(data: &[u8]) -> Result<Element, AppError> {
+    ebml_elements(data, 0, data.len())
+        .into_iter()
+        .find(|element| element.id == SEGMENT)
+        .ok_or_else(|| invalid("WebM tidak memiliki Segment"))
+}
+
+fn probe_webm(data: &[u8]) -> Result<VideoInfo, AppError> {
+    let segment = webm_segment(data)?;
+    let limit = data.len();
+    let info = ebml_child(data, &segment, INFO, limit)
+        .ok_or_else(|| invalid("WebM tidak memiliki element Info"))?;
+    let scale = ebml_child(data, &info, TIMESTAMP_SCALE, limit)
+        .and_then(|element| ebml_uint(data, &element))
+        .unwrap_or(DEFAULT_TIMESTAMP_SCALE);
+    let duration = ebml_child(data, &info, DURATION, limit)
+        .and_then(|element| ebml_float(data, &element))
+        .ok_or_else(|| invalid("WebM tidak mencantumkan durasi"))?;
+
+    let tracks = ebml_child(data, &segment, TRACKS, limit)
+        .ok_or_else(|| invalid("WebM tidak memiliki element Tracks"))?;
+    let video = ebml_elements(data, tracks.data, tracks.end.unwrap_or(limit))
+        .into_iter()
+        .filter(|element| element.id == TRACK_ENTRY)
+        .find_map(|entry| ebml_child(data, &entry, VIDEO, limit))
+        .ok_or_else(|| invalid("WebM tidak memiliki track video"))?;
+    let dimension = |id| ebml_child(data, &video, id, limit).and_then(|element| ebml_uint(data, &element));
+    let (Some(width), Some(height)) = (dimension(PIXEL_WIDTH), dimension(PIXEL_HEIGHT)) else {
+        return Err(invalid("dimensi track video WebM tidak ada"));
+    };
+
+    Ok(VideoInfo {
+        width: width as u32,
+        height: height as u32,
+        duration_ms: (duration.max(0.0) * scale as f64 / 1_000_000.0) as u64,
+    })
+}
+
+// Tags (judul, encoder, lokasi) diganti element Void dengan panjang sama
+fn strip_webm(data: &[u8]) -> Result<Vec<u8>, AppError> {
+    let segment = webm_segment(data)?;
+    let mut out = data.to_vec();
+    for tags in ebml_elements(data, segment.data, segment.end.unwrap_or(data.len())) {
+        let Some(end) = tags.end.filter(|_| tags.id == TAGS) else {
+            continue;
+        };
+        // Void: ID 1 byte + ukuran 8 byte; element Tags sekecil itu tidak berisi apa-apa
+        let total = end - tags.start;
+        if total < 9 {
+            continue;
+        }
+        let size = (total - 9) as u64 | (1u64 << 56);
+        out[tags.start] = VOID;
+        out[tags.start + 1..tags.start + 9].copy_from_slice(&size.to_be_bytes());
+        out[tags.start + 9..end].fill(0);
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // ---- GIF 1x1 dengan palet 2 warna --------------------------------------
+
+    const GIF_NETSCAPE: &[u8] = b"\x21\xFF\x0BNETSCAPE2.0\x03\x01\x00\x00\x00";
+    const GIF_XMP: &[u8] = b"\x21\xFF\x0BXMP DataXMP\x03abc\x00";
+    const GIF_COMMENT_BLOCK: &[u8] = b"\x21\xFE\x07rahasia\x00";
+    // Graphic control (delay 10), descriptor 1x1, LZW untuk satu pixel index 0
+    const GIF_FRAME: &[u8] = b"\x21\xF9\x04\x00\x0A\x00\x00\x00\x2C\x00\x00\x00\x00\x01\x00\x01\x00\x00\x02\x02\x44\x01\x00";
+
+    fn gif(blocks: &[&[u8]]) -> Vec<u8> {
+        let mut data = b"GIF89a\x01\x00\x01\x00\x80\x00\x00\x00\x00\x00\xFF\xFF\xFF".to_vec();
+        for block in blocks {
+            data.extend_from_slice(block);
+        }
+        data.push(GIF_TRAILER);
+        data
+    }
+
+    #[test]
+    fn gif_frames_and_animation() {
+        let still = gif(&[GIF_FRAME]);
+        let animated = gif(&[GIF_NETSCAPE, GIF_FRAME, GIF_FRAME, GIF_FRAME]);
+        assert_eq!(sniff(&still), Some(Container::Gif));
+        assert_eq!(frame_count(&still, Container::Gif).unwrap(), 1);
+        assert!(!is_animated(&still, Container::Gif));
+        assert_eq!(frame_count(&animated, Container::Gif).unwrap(), 3);
+        assert!(is_animated(&animated, Container::Gif));
+    }
+
+    #[test]
+    fn gif_strip_drops_comments_and_foreign_applications() {
+        let data = gif(&[GIF_NETSCAPE, GIF_XMP, GIF_COMMENT_BLOCK, GIF_FRAME, GIF_FRAME]);
+        let stripped = strip_metadata(&data, Container::Gif).unwrap();
+        assert_eq!(stripped, gif(&[GIF_NETSCAPE, GIF_FRAME, GIF_FRAME]));
+
+        use image::AnimationDecoder;
+        let decoder = image::codecs::gif::GifDecoder::new(std::io::Cursor::new(stripped)).unwrap();
+        assert_eq!(decoder.into_frames().count(), 2);
+    }
+
+    #[test]
+    fn gif_without_trailer_gets_one() {
+        let mut data = gif(&[GIF_FRAME]);
+        data.pop();
+        assert_eq!(strip_metadata(&data, Container::Gif).unwrap(), gif(&[GIF_FRAME]));
+    }
+
+    #[test]
+    fn gif_truncated_or_unknown_block_is_rejected() {
+        let data = gif(&[GIF_FRAME]);
+        assert!(matches!(frame_count(&data[..data.len() - 4], Container::Gif), Err(AppError::BadRequest(_))));
+        assert!(matches!(frame_count(&data[..8], Container::Gif), Err(AppError::BadRequest(_))));
+        let mut unknown = gif(&[]);
+        unknown.insert(unknown.len() - 1, 0x00);
+        assert!(matches!(frame_count(&unknown, Container::Gif), Err(AppError::BadRequest(_))));
+    }
+
+    // ---- WebP --------------------------------------------------------------
+
+    fn riff_chunk(fourcc: &[u8; 4], payload: &[u8]) -> Vec<u8> {
+        let mut chunk = fourcc.to_vec();
+        chunk.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+        chunk.extend_from_slice(payload);
+        if payload.len() & 1 == 1 {
+            chunk.push(0);
+        }
+        chunk
+    }
+
+    fn webp(chunks: &[Vec<u8>]) -> Vec<u8> {
+        let body = chunks.concat();
+        let mut data = b"RIFF".to_vec();
+        data.extend_from_slice(&(body.len() as u32 + 4).to_le_bytes());
+        data.extend_from_slice(b"WEBP");
+        data.extend_from_slice(&body);
+        data
+    }
+
+    fn vp8x(flags: u8) -> Vec<u8> {
+        riff_chunk(b"VP8X", &[flags, 0, 0, 0, 0, 0, 0, 0, 0, 0])
+    }
+
+    #[test]
+    fn webp_frames_and_animation() {
+        let frame = riff_chunk(b"ANMF", &[0; 16]);
+        let animated = webp(&[vp8x(VP8X_ANIMATION), riff_chunk(b"ANIM", &[0; 6]), frame.clone(), frame]);
+        let still = webp(&[riff_chunk(b"VP8L", &[0; 5])]);
+        assert_eq!(sniff(&animated), Some(Container::WebP));
+        assert!(is_animated(&animated, Container::WebP));
+        assert_eq!(frame_count(&animated, Container::WebP).unwrap(), 2);
+        assert!(!is_animated(&still, Container::WebP));
+        assert_eq!(frame_count(&still, Container::WebP).unwrap(), 1);
+    }
+
+    #[test]
+    fn webp_strip_removes_exif_xmp_and_clears_flags() {
+        let frame = riff_chunk(b"ANMF", &[7; 16]);
+        let data = webp(&[
+            vp8x(VP8X_ANIMATION | VP8X_EXIF | VP8X_XMP),
+            riff_chunk(b"EXIF", b"gps"),
+            frame.clone(),
+            riff_chunk(b"XMP ", b"<x/>"),
+        ]);
+        let stripped = strip_metadata(&data, Container::WebP).unwrap();
+        assert_eq!(stripped, webp(&[vp8x(VP8X_ANIMATION), frame]));
+    }
+
+    #[test]
+    fn webp_truncated_chunk_is_rejected() {
+        let data = webp(&[riff_chunk(b"ANMF", &[0; 16])]);
+        assert!(matches!(frame_count(&data[..data.len() - 1], Container::WebP), Err(AppError::BadRequest(_))));
+    }
+
+    // ---- MP4 ---------------------------------------------------------------
+
+    fn mp4_box(kind: &[u8; 4], payload: &[u8]) -> Vec<u8> {
+        let mut data = ((payload.len() + 8) as u32).to_be_bytes().to_vec();
+        data.extend_from_slice(kind);
+        data.extend_from_slice(payload);
+        data
+    }
+
+    fn mp4(brand: &[u8; 4], width: u32, height: u32) -> Vec<u8> {
+        // mvhd versi 0: timescale 1000, durasi 3000
+        let mut mvhd = vec![0; 100];
+        mvhd[12..16].copy_from_slice(&1000u32.to_be_bytes());
+        mvhd[16..20].copy_from_slice(&3000u32.to_be_bytes());
+        // tkhd versi 0: lebar/tinggi 16.16 di offset 76
+        let mut tkhd = vec![0; 84];
+        tkhd[76..80].copy_from_slice(&(width << 16).to_be_bytes());
+        tkhd[80..84].copy_from_slice(&(height << 16).to_be_bytes());
+        let mut hdlr = vec![0; 24];
+        hdlr[8..12].copy_from_slice(b"vide");
+        let trak = [mp4_box(b"tkhd", &tkhd), mp4_box(b"mdia", &mp4_box(b"hdlr", &hdlr))].concat();
+        let moov = [
+            mp4_box(b"mvhd", &mvhd),
+            mp4_box(b"trak", &trak),
+            mp4_box(b"udta", b"\xA9xyz+06.2+106.8/"),
+        ].concat();
+        [mp4_box(b"ftyp", &[brand.as_slice(), &[0; 4]].concat()), mp4_box(b"moov", &moov), mp4_box(b"mdat", &[1; 32])].concat()
+    }
+
+    #[test]
+    fn mp4_sniff_skips_still_image_brands() {
+        assert_eq!(sniff(&mp4(b"isom", 640, 360)), Some(Container::Mp4));
+        assert_eq!(sniff(&mp4(b"avif", 640, 360)), None);
+        assert_eq!(sniff(&mp4(b"heic", 640, 360)), None);
+    }
+
+    #[test]
+    fn mp4_probe_reads_dimensions_and_duration() {
+        let info = probe_video(&mp4(b"isom", 640, 360), Container::Mp4).unwrap();
+        assert_eq!((info.width, info.height, info.duration_ms), (640, 360, 3000));
+    }
+
+    #[test]
+    fn mp4_strip_blanks_metadata_in_place() {
+        let data = mp4(b"isom", 640, 360);
+        let stripped = strip_metadata(&data, Container::Mp4).unwrap();
+        assert_eq!(stripped.len(), data.len());
+        let udta = data.windows(4).position(|window| window == b"udta").unwrap();
+        assert_eq!(&stripped[udta..udta + 4], b"free");
+        assert!(stripped[udta + 4..udta + 20].iter().all(|byte| *byte == 0));
+        assert_eq!(&stripped[..udta], &data[..udta]);
+        assert_eq!(&stripped[udta + 20..], &data[udta + 20..]);
+        assert_eq!(probe_video(&stripped, Container::Mp4).unwrap().duration_ms, 3000);
+    }
+
+    #[test]
+    fn mp4_invalid_box_size_is_rejected() {
+        let mut data = mp4(b"isom", 640, 360);
+        let moov = data.windows(4).position(|window| window == b"moov").unwrap() - 4;
+        data[moov..moov + 4].copy_from_slice(&u32::MAX.to_be_bytes());
+        assert!(matches!(probe_video(&data, Container::Mp4), Err(AppError::BadRequest(_))));
+    }
+
+    // ---- WebM --------------------------------------------------------------
+
+    // Ukuran 1 byte (cukup untuk fixture kecil)
+    fn element(id: &[u8], payload: &[u8]) -> Vec<u8> {
+        assert!(payload.len() < 0x7F);
+        [id, &[0x80 | payload.len() as u8], payload].concat()
+    }
+
+    fn webm(doc_type: &str, tags: bool) -> Vec<u8> {
+        let header = element(&EBML_HEADER.to_be_bytes(), &element(&[0x42, 0x82], doc_type.as_bytes()));
+        let info = element(&[0x15, 0x49, 0xA9, 0x66], &[
+            element(&[0x2A, 0xD7, 0xB1], &[0x0F, 0x42, 0x40]),
+            element(&[0x44, 0x89], &2500f32.to_be_bytes()),
+        ].concat());
+        let video = element(&[0xE0], &[element(&[0xB0], &[0x02, 0x80]), element(&[0xBA], &[0x01, 0x68])].concat());
+        let tracks = element(&[0x16, 0x54, 0xAE, 0x6B], &element(&[0xAE], &video));
+        let mut segment = [info, tracks].concat();
+        if tags {
+            segment.extend(element(&[0x12, 0x54, 0xC3, 0x67], b"judul video"));
+        }
+        [header, element(&[0x18, 0x53, 0x80, 0x67], &segment)].concat()
+    }
+
+    #[test]
+    fn webm_sniff_checks_doc_type() {
+        assert_eq!(sniff(&webm("webm", false)), Some(Container::WebM));
+        assert_eq!(sniff(&webm("matroska", false)), None);
+    }
+
+    #[test]
+    fn webm_probe_reads_dimensions_and_duration() {
+        let info = probe_video(&webm("webm", true), Container::WebM).unwrap();
+        assert_eq!((info.width, info.height, info.duration_ms), (640, 360, 2500));
+    }
+
+    #[test]
+    fn webm_strip_replaces_tags_with_void() {
+        let data = webm("webm", true);
+        let stripped = strip_metadata(&data, Container::WebM).unwrap();
+        assert_eq!(stripped.len(), data.len());
+        assert!(!stripped.windows(11).any(|window| window == b"judul video"));
+        let segment = webm_segment(&stripped).unwrap();
+        let ids: Vec<u32> = ebml_elements(&stripped, segment.data, segment.end.unwrap())
+            .iter()
+            .map(|element| element.id)
+            .collect();
+        assert_eq!(ids, vec![INFO, TRACKS, VOID as u32]);
+        assert_eq!(probe_video(&stripped, Container::WebM).unwrap().width, 640);
+    }
+
+    #[test]
+    fn unknown_data_is_not_sniffed() {
+        assert_eq!(sniff(b""), None);
+        assert_eq!(sniff(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR"), None);
+        assert_eq!(sniff(b"RIFF\0\0\0\0WAVE"), None);
+    }
+}
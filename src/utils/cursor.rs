@@ -0,0 +1,124 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, NaiveDateTime};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::handlers::AppError;
+
+const DEFAULT_PAGE_SIZE: i64 = 20;
+const MAX_PAGE_SIZE: i64 = 50;
+
+// Query ?cursor=&limit= untuk list yang dipaginasi
+#[derive(Deserialize)]
+pub struct PageQuery {
+    pub cursor: Option<String>,
+    pub limit: Option<i64>,
+}
+
+impl PageQuery {
+    pub fn limit(&self) -> i64 {
+        self.limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE)
+    }
+
+    pub fn cursor(&self) -> Result<Option<Cursor>, AppError> {
+        self.cursor.as_deref().map(Cursor::decode).transpose()
+    }
+}
+
+/// Posisi item terakhir di list yang diurutkan mundur berdasarkan
+/// (waktu, rank, id). `rank` membedakan sumber yang digabung dalam satu list
+/// (lihat feed); list dari satu tabel cukup pakai 0. Untuk client isinya
+/// opaque.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Cursor {
+    pub at: NaiveDateTime,
+    pub rank: u8,
+    pub id: Uuid,
+}
+
+impl Cursor {
+    pub fn encode(&self) -> String {
+        let raw = format!("{}:{}:{}", self.at.and_utc().timestamp_micros(), self.rank, self.id);
+        URL_SAFE_NO_PAD.encode(raw)
+    }
+
+    pub fn decode(raw: &str) -> Result<Cursor, AppError> {
+        let invalid = || AppError::BadRequest("Cursor tidak valid".to_string());
+        let decoded = URL_SAFE_NO_PAD.decode(raw).map_err(|_| invalid())?;
+        let decoded = String::from_utf8(decoded).map_err(|_| invalid())?;
+        let mut parts = decoded.splitn(3, ':');
+        let (Some(micros), Some(rank), Some(id)) = (parts.next(), parts.next(), parts.next()) else {
+            return Err(invalid());
+        };
+        let at = micros.parse::<i64>().ok()
+            .and_then(DateTime::from_timestamp_micros)
+            .ok_or_else(invalid)?
+            .naive_utc();
+        Ok(Cursor {
+            at,
+            rank: rank.parse().map_err(|_| invalid())?,
+            id: id.parse().map_err(|_| invalid())?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn sample() -> Cursor {
+        Cursor {
+            at: NaiveDate::from_ymd_opt(2024, 5, 17).unwrap()
+                .and_hms_micro_opt(8, 30, 15, 123_456).unwrap(),
+            rank: 2,
+            id: Uuid::parse_str("0190a6a4-7b1c-7cc2-9a0e-3f5e2d1c4b6a").unwrap(),
+        }
+    }
+
+    fn assert_invalid(raw: &str) {
+        match Cursor::decode(raw) {
+            Err(AppError::BadRequest(message)) => assert_eq!(message, "Cursor tidak valid"),
+            other => panic!("cursor {:?} seharusnya ditolak, dapat {:?}", raw, other),
+        }
+    }
+
+    #[test]
+    fn roundtrip_keeps_microseconds() {
+        let cursor = sample();
+        assert_eq!(Cursor::decode(&cursor.encode()).unwrap(), cursor);
+    }
+
+    #[test]
+    fn encoded_cursor_is_url_safe() {
+        let encoded = sample().encode();
+        assert!(encoded.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+    }
+
+    #[test]
+    fn rejects_malformed_cursors() {
+        assert_invalid("");
+        assert_invalid("bukan base64!");
+        assert_invalid(&URL_SAFE_NO_PAD.encode("123:0"));
+        assert_invalid(&URL_SAFE_NO_PAD.encode("abc:0:0190a6a4-7b1c-7cc2-9a0e-3f5e2d1c4b6a"));
+        assert_invalid(&URL_SAFE_NO_PAD.encode("123:256:0190a6a4-7b1c-7cc2-9a0e-3f5e2d1c4b6a"));
+        assert_invalid(&URL_SAFE_NO_PAD.encode("123:0:bukan-uuid"));
+        assert_invalid(&URL_SAFE_NO_PAD.encode([0xff, 0xfe, 0xfd]));
+    }
+
+    #[test]
+    fn limit_defaults_and_clamps() {
+        let query = |limit| PageQuery { cursor: None, limit };
+        assert_eq!(query(None).limit(), DEFAULT_PAGE_SIZE);
+        assert_eq!(query(Some(0)).limit(), 1);
+        assert_eq!(query(Some(-5)).limit(), 1);
+        assert_eq!(query(Some(10)).limit(), 10);
+        assert_eq!(query(Some(1000)).limit(), MAX_PAGE_SIZE);
+    }
+
+    #[test]
+    fn page_query_without_cursor_is_first_page() {
+        let query = PageQuery { cursor: None, limit: None };
+        assert!(query.cursor().unwrap().is_none());
+    }
+}
@@ -4,6 +4,7 @@ use jsonwebtoken::{
     Header,
     EncodingKey,
     DecodingKey,
+    TokenData,
     Validation
 };
 use std::time::{
@@ -15,46 +16,96 @@ use serde::{
     Deserialize, 
     Serialize
 };
-use tracing::info;
+use axum::http::HeaderMap;
+use uuid::Uuid;
+use quoteyourlife_be::models::{Permission, UserRoleEnum};
 
-use crate::handlers::AppError;
+use crate::db::{PgPool, get_conn};
+use crate::handlers::{AppError, account::ensure_token_current};
 
 #[derive(Deserialize, Serialize, Clone)]
 pub struct Claims {
     pub sub: String,
     pub exp: usize,
-    pub iat: usize
+    pub iat: usize,
+    // Role saat login; ganti role mencabut token lama (lihat handlers::user::update_user_role)
+    #[serde(default)]
+    pub role: UserRoleEnum,
+    // users.token_version saat token dibuat (lihat handlers::account::ensure_token_current)
+    #[serde(default)]
+    pub ver: i32
 }
 
-pub fn create_jwt(user_id: &str) -> Result<String, AppError>{
+impl Claims {
+    // `sub` berisi id user (lihat handlers::auth::login)
+    pub fn user_id(&self) -> Result<Uuid, AppError> {
+        Uuid::parse_str(&self.sub)
+            .map_err(|_| AppError::JWTValidationError(jsonwebtoken::errors::ErrorKind::InvalidSubject.into()))
+    }
+
+    pub fn require(&self, permission: Permission) -> Result<(), AppError> {
+        if self.role.can(permission) {
+            return Ok(());
+        }
+        Err(AppError::Forbidden(format!("Role {:?} tidak punya izin {:?}", self.role, permission)))
+    }
+}
+
+pub fn create_jwt(user_id: &str, role: UserRoleEnum, version: i32) -> Result<String, AppError>{
     let secret_key = env::var("JWT_KEY").expect("Gagal membaca environment variable");
     let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() as usize;
     let claims = Claims {
         sub: user_id.to_owned(),
         iat: now,
         exp: now + (24 * 3600),
+        role,
+        ver: version,
     };
     encode(
         &Header::default(),
         &claims,
         &EncodingKey::from_secret(secret_key.as_ref())
-    ).map_err(|err| AppError::JWTValidationError(err))
+    ).map_err(AppError::JWTValidationError)
 }
 
-pub fn verify_jwt(token: &str) -> Result<Claims, jsonwebtoken::errors::Error> {
-let secret_key = env::var("JWT_KEY")
-    .map_err(|e| {
-        // Ini akan memunculkan daftar env yang terbaca di log prod
-        info!("Current envs: {:?}", env::vars().collect::<Vec<_>>());
-        e
-    })
-    .expect("Gagal membaca JWT_KEY");
+pub fn verify_jwt(token: &str) -> Result<TokenData<Claims>, jsonwebtoken::errors::Error> {
+    let secret_key = env::var("JWT_KEY").expect("Gagal membaca JWT_KEY");
 
-    let token_data = decode(
-        &token,
+    decode(
+        token,
         &DecodingKey::from_secret(secret_key.as_ref()),
         &Validation::default()
-    )?;
+    )
+}
 
-    Ok(token_data.claims)
+/// Verifikasi token lalu pastikan belum dicabut (mis. setelah reset password
+/// atau ganti role) walau belum expired. Semua jalur yang menerima token
+/// lewat sini: middleware JWT, `optional_claims`, dan `/auth/verify`.
+pub async fn authenticate(pool: &PgPool, token: &str) -> Result<TokenData<Claims>, AppError> {
+    let token_data = verify_jwt(token).map_err(AppError::JWTValidationError)?;
+    let pool = pool.clone();
+    let claims = token_data.claims.clone();
+    tokio::task::spawn_blocking(move || -> Result<_, AppError> {
+        let mut conn = get_conn(&pool)?;
+        ensure_token_current(&mut conn, &claims)
+    })
+    .await
+    .map_err(AppError::AsyncTaskError)?
+    ?;
+    Ok(token_data)
+}
+
+// Untuk route publik yang hasilnya berbeda kalau user login (mis. album private).
+// Tanpa header Authorization -> Ok(None); token yang tidak valid atau sudah dicabut tetap ditolak.
+pub async fn optional_claims(pool: &PgPool, headers: &HeaderMap) -> Result<Option<Claims>, AppError> {
+    let Some(auth_header) = headers.get("Authorization") else {
+        return Ok(None);
+    };
+    let token = auth_header
+        .to_str()
+        .ok()
+        .and_then(|h| h.strip_prefix("Bearer "))
+        .ok_or(AppError::JWTValidationError(jsonwebtoken::errors::ErrorKind::InvalidToken.into()))?;
+    let token_data = authenticate(pool, token).await?;
+    Ok(Some(token_data.claims))
 }
@@ -0,0 +1,332 @@
+use serde_json::Value;
+
+/// Render konten Editor.js (`{"blocks": [...]}`) jadi Markdown. Blok yang
+/// tidak dikenal ditulis sebagai komentar HTML supaya isinya tidak hilang
+/// diam-diam; data lengkapnya tetap ada di JSON ekspor.
+pub fn editorjs_to_markdown(content: &Value) -> String {
+    let blocks = content.get("blocks")
+        .and_then(Value::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    let rendered: Vec<String> = blocks.iter()
+        .filter_map(render_block)
+        .filter(|block| !block.trim().is_empty())
+        .collect();
+    let mut markdown = rendered.join("\n\n");
+    markdown.push('\n');
+    markdown
+}
+
+fn render_block(block: &Value) -> Option<String> {
+    let kind = block.get("type")?.as_str()?;
+    let data = block.get("data").unwrap_or(&Value::Null);
+    let text = |key: &str| inline(data.get(key).and_then(Value::as_str).unwrap_or_default());
+
+    let rendered = match kind {
+        "header" => {
+            let level = data.get("level").and_then(Value::as_u64).unwrap_or(2).clamp(1, 6) as usize;
+            format!("{} {}", "#".repeat(level), text("text"))
+        }
+        "paragraph" => text("text"),
+        "list" => {
+            let ordered = data.get("style").and_then(Value::as_str) == Some("ordered");
+            render_list(data.get("items"), ordered, 0)
+        }
+        "checklist" => data.get("items")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .map(|item| {
+                let checked = item.get("checked").and_then(Value::as_bool).unwrap_or(false);
+                let label = inline(item.get("text").and_then(Value::as_str).unwrap_or_default());
+                format!("- [{}] {}", if checked { "x" } else { " " }, label)
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        "quote" => {
+            let mut quote = text("text")
+                .lines()
+                .map(|line| format!("> {}", line))
+                .collect::<Vec<_>>()
+                .join("\n");
+            let caption = text("caption");
+            if !caption.is_empty() {
+                quote.push_str(&format!("\n>\n> — {}", caption));
+            }
+            quote
+        }
+        "code" => {
+            let code = data.get("code").and_then(Value::as_str).unwrap_or_default();
+            format!("```\n{}\n```", code.trim_end())
+        }
+        "delimiter" => "---".to_string(),
+        "image" | "simpleImage" => {
+            let url = data.get("file")
+                .and_then(|file| file.get("url"))
+                .or_else(|| data.get("url"))
+                .and_then(Value::as_str)
+                .unwrap_or_default();
+            // Alt text dari blok, lalu dari item galeri (lihat handlers::article), terakhir caption
+            let caption = text("caption");
+            let alt = data.get("alt_text")
+                .and_then(Value::as_str)
+                .map(inline)
+                .filter(|alt| !alt.is_empty())
+                .unwrap_or_else(|| caption.clone());
+            let image = format!("![{}]({})", escape_brackets(&alt), url);
+            if caption.is_empty() || caption == alt {
+                image
+            } else {
+                format!("{}\n\n_{}_", image, caption)
+            }
+        }
+        "embed" => {
+            let url = data.get("source").or_else(|| data.get("embed")).and_then(Value::as_str).unwrap_or_default();
+            let caption = text("caption");
+            let label = if caption.is_empty() { url.to_string() } else { caption };
+            format!("[{}]({})", escape_brackets(&label), url)
+        }
+        "linkTool" => {
+            let link = data.get("link").and_then(Value::as_str).unwrap_or_default();
+            let title = data.get("meta")
+                .and_then(|meta| meta.get("title"))
+                .and_then(Value::as_str)
+                .unwrap_or(link);
+            format!("[{}]({})", escape_brackets(title), link)
+        }
+        "table" => render_table(data),
+        "warning" => format!("> **{}**\n>\n> {}", text("title"), text("message")),
+        "raw" => data.get("html").and_then(Value::as_str).unwrap_or_default().to_string(),
+        other => format!("<!-- blok Editor.js '{}' tidak dirender -->", other),
+    };
+    Some(rendered)
+}
+
+// Item list bisa berupa string (list v1) atau {content, items} (nested-list)
+fn render_list(items: Option<&Value>, ordered: bool, depth: usize) -> String {
+    let mut lines = Vec::new();
+    for (index, item) in items.and_then(Value::as_array).into_iter().flatten().enumerate() {
+        let (content, children) = match item {
+            Value::String(content) => (content.as_str(), None),
+            Value::Object(object) => (
+                object.get("content").and_then(Value::as_str).unwrap_or_default(),
+                object.get("items"),
+            ),
+            _ => continue,
+        };
+        let marker = if ordered { format!("{}.", index + 1) } else { "-".to_string() };
+        lines.push(format!("{}{} {}", "   ".repeat(depth), marker, inline(content)));
+        let nested = render_list(children, ordered, depth + 1);
+        if !nested.is_empty() {
+            lines.push(nested);
+        }
+    }
+    lines.join("\n")
+}
+
+fn render_table(data: &Value) -> String {
+    let rows: Vec<Vec<String>> = data.get("content")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .map(|row| row.as_array()
+            .into_iter()
+            .flatten()
+            .map(|cell| inline(cell.as_str().unwrap_or_default()).replace('|', "\\|"))
+            .collect())
+        .collect();
+    let Some(columns) = rows.iter().map(Vec::len).max().filter(|columns| *columns > 0) else {
+        return String::new();
+    };
+    let line = |cells: &[String]| {
+        let mut padded: Vec<&str> = cells.iter().map(String::as_str).collect();
+        padded.resize(columns, "");
+        format!("| {} |", padded.join(" | "))
+    };
+    // Tanpa baris judul, Markdown tetap butuh header; pakai baris kosong
+    let with_headings = data.get("withHeadings").and_then(Value::as_bool).unwrap_or(false);
+    let (header, body) = if with_headings {
+        (line(&rows[0]), &rows[1..])
+    } else {
+        (line(&[]), &rows[..])
+    };
+    let mut lines = vec![header, format!("|{}", " --- |".repeat(columns))];
+    lines.extend(body.iter().map(|row| line(row)));
+    lines.join("\n")
+}
+
+/// HTML inline Editor.js (`<b>`, `<i>`, `<a>`, `<code>`, `<br>`, `<mark>`)
+/// jadi Markdown; tag lain dibuang, entity umum di-decode.
+fn inline(html: &str) -> String {
+    let mut output = String::with_capacity(html.len());
+    let mut links: Vec<String> = Vec::new();
+    let mut rest = html;
+    while let Some(start) = rest.find('<') {
+        output.push_str(&rest[..start]);
+        let Some(end) = rest[start..].find('>') else {
+            output.push_str(&rest[start..]);
+            rest = "";
+            break;
+        };
+        let tag = &rest[start + 1..start + end];
+        rest = &rest[start + end + 1..];
+
+        let closing = tag.starts_with('/');
+        let name = tag.trim_start_matches('/')
+            .split(|c: char| c.is_whitespace() || c == '/')
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        match name.as_str() {
+            "b" | "strong" => output.push_str("**"),
+            "i" | "em" => output.push('_'),
+            "code" => output.push('`'),
+            "s" | "del" => output.push_str("~~"),
+            "br" => output.push_str("  \n"),
+            "a" if closing => {
+                if let Some(href) = links.pop() {
+                    output.push_str(&format!("]({})", href));
+                }
+            }
+            "a" => {
+                links.push(attribute(tag, "href").unwrap_or_default());
+                output.push('[');
+            }
+            _ => {}
+        }
+    }
+    output.push_str(rest);
+    decode_entities(&output)
+}
+
+fn attribute(tag: &str, key: &str) -> Option<String> {
+    let (_, after) = tag.split_once(&format!("{}=", key))?;
+    let quote = after.chars().next()?;
+    if quote == '"' || quote == '\'' {
+        after[1..].split(quote).next().map(decode_entities)
+    } else {
+        after.split_whitespace().next().map(decode_entities)
+    }
+}
+
+fn decode_entities(text: &str) -> String {
+    text.replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+fn escape_brackets(text: &str) -> String {
+    text.replace('[', "\\[").replace(']', "\\]")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn render(blocks: Value) -> String {
+        editorjs_to_markdown(&json!({ "blocks": blocks }))
+    }
+
+    #[test]
+    fn empty_or_missing_blocks_render_empty_document() {
+        assert_eq!(editorjs_to_markdown(&json!({})), "\n");
+        assert_eq!(editorjs_to_markdown(&Value::Null), "\n");
+        assert_eq!(render(json!([{ "type": "paragraph", "data": { "text": "  " } }])), "\n");
+    }
+
+    #[test]
+    fn headers_and_paragraphs_are_separated_by_blank_lines() {
+        let markdown = render(json!([
+            { "type": "header", "data": { "text": "Judul", "level": 1 } },
+            { "type": "header", "data": { "text": "Terlalu dalam", "level": 9 } },
+            { "type": "paragraph", "data": { "text": "Halo <b>dunia</b> &amp; <i>kamu</i>" } },
+        ]));
+        assert_eq!(markdown, "# Judul\n\n###### Terlalu dalam\n\nHalo **dunia** & _kamu_\n");
+    }
+
+    #[test]
+    fn inline_links_code_and_line_breaks() {
+        let markdown = render(json!([{ "type": "paragraph", "data": {
+            "text": "Baca <a href=\"https://example.com/?a=1&amp;b=2\">ini</a>,<br>pakai <code>cargo</code>"
+        } }]));
+        assert_eq!(markdown, "Baca [ini](https://example.com/?a=1&b=2),  \npakai `cargo`\n");
+    }
+
+    #[test]
+    fn lists_support_plain_and_nested_items() {
+        let markdown = render(json!([
+            { "type": "list", "data": { "style": "ordered", "items": ["satu", "dua"] } },
+            { "type": "list", "data": { "style": "unordered", "items": [
+                { "content": "induk", "items": [{ "content": "anak", "items": [] }] }
+            ] } },
+            { "type": "checklist", "data": { "items": [
+                { "text": "selesai", "checked": true },
+                { "text": "belum" }
+            ] } },
+        ]));
+        assert_eq!(markdown, "1. satu\n2. dua\n\n- induk\n   - anak\n\n- [x] selesai\n- [ ] belum\n");
+    }
+
+    #[test]
+    fn quote_code_and_delimiter() {
+        let markdown = render(json!([
+            { "type": "quote", "data": { "text": "baris satu<br>baris dua", "caption": "Budi" } },
+            { "type": "code", "data": { "code": "fn main() {}\n\n" } },
+            { "type": "delimiter", "data": {} },
+        ]));
+        assert_eq!(
+            markdown,
+            "> baris satu  \n> baris dua\n>\n> — Budi\n\n```\nfn main() {}\n```\n\n---\n"
+        );
+    }
+
+    #[test]
+    fn images_prefer_alt_text_and_keep_distinct_caption() {
+        let markdown = render(json!([
+            { "type": "image", "data": {
+                "file": { "url": "https://cdn.example.com/a.png" },
+                "caption": "Pantai [sore]",
+                "alt_text": "Matahari terbenam"
+            } },
+            { "type": "simpleImage", "data": { "url": "/b.png", "caption": "Gunung" } },
+        ]));
+        assert_eq!(
+            markdown,
+            "![Matahari terbenam](https://cdn.example.com/a.png)\n\n_Pantai [sore]_\n\n![Gunung](/b.png)\n"
+        );
+    }
+
+    #[test]
+    fn tables_pad_rows_and_escape_pipes() {
+        let markdown = render(json!([
+            { "type": "table", "data": { "withHeadings": true, "content": [
+                ["Nama", "Nilai"],
+                ["a|b"]
+            ] } },
+            { "type": "table", "data": { "content": [["x", "y"]] } },
+            { "type": "table", "data": { "content": [] } },
+        ]));
+        assert_eq!(
+            markdown,
+            "| Nama | Nilai |\n| --- | --- |\n| a\\|b |  |\n\n|  |  |\n| --- | --- |\n| x | y |\n"
+        );
+    }
+
+    #[test]
+    fn links_and_unknown_blocks() {
+        let markdown = render(json!([
+            { "type": "linkTool", "data": { "link": "https://example.com", "meta": { "title": "Contoh" } } },
+            { "type": "embed", "data": { "source": "https://youtu.be/x" } },
+            { "type": "carousel", "data": {} },
+            { "data": { "text": "tanpa tipe" } },
+        ]));
+        assert_eq!(
+            markdown,
+            "[Contoh](https://example.com)\n\n[https://youtu.be/x](https://youtu.be/x)\n\n<!-- blok Editor.js 'carousel' tidak dirender -->\n"
+        );
+    }
+}
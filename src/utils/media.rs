@@ -0,0 +1,625 @@
+use std::collections::HashMap;
+use std::io::Cursor;
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::{NaiveDate, NaiveDateTime};
+use image::{
+    codecs::avif::AvifEncoder,
+    codecs::gif::GifDecoder,
+    codecs::jpeg::JpegEncoder,
+    codecs::webp::WebPDecoder,
+    imageops::FilterType,
+    AnimationDecoder,
+    DynamicImage,
+    GenericImageView,
+    ImageDecoder,
+    ImageFormat,
+    ImageReader,
+    Limits
+};
+use quoteyourlife_be::models::MediaTypeEnum;
+use quoteyourlife_be::storage::{self, Variant};
+use tracing::{debug, warn};
+
+use crate::handlers::AppError;
+use crate::utils::container::{self, Container};
+use crate::utils::watermark::Watermark;
+
+// Jumlah warna dominan yang disimpan per gambar
+const DOMINANT_COLOR_COUNT: usize = 5;
+const JPEG_QUALITY: u8 = 90;
+// Rendition format modern; speed AVIF 1-10 (10 = paling cepat)
+const WEBP_QUALITY: f32 = 80.0;
+const AVIF_QUALITY: u8 = 70;
+const AVIF_SPEED: u8 = 8;
+// Preview LQIP: sisi terpanjang (pixel) dan kualitas JPEG-nya
+const LQIP_SIZE: u32 = 16;
+const LQIP_QUALITY: u8 = 50;
+// Delay frame di bawah 20 ms diputar browser sebagai 100 ms
+const MIN_FRAME_DELAY_MS: u32 = 20;
+const CLAMPED_FRAME_DELAY_MS: u32 = 100;
+// Batas animasi GIF/WebP, dicek dari struktur file sebelum frame di-decode:
+// jumlah frame, sisi kanvas, dan total pixel semua frame (frame × lebar × tinggi)
+const MAX_ANIMATION_FRAMES: usize = 1000;
+const MAX_ANIMATION_SIDE: u32 = 4096;
+const MAX_ANIMATION_PIXELS: u64 = 250_000_000;
+// Alokasi maksimum decoder untuk satu frame (RGBA) beserta buffer internalnya
+const MAX_ANIMATION_ALLOC: u64 = 256 * 1024 * 1024;
+// Sisi avatar (pixel) setelah di-crop persegi
+const AVATAR_SIZE: u32 = 512;
+
+/// Metadata yang diambil dari gambar sebelum EXIF/XMP dibuang.
+#[derive(Debug)]
+pub struct ImageMetadata {
+    pub width: u32,
+    pub height: u32,
+    pub dominant_colors: Vec<String>,
+    pub taken_at: Option<NaiveDateTime>,
+    pub phash: u64,
+    pub blurhash: String,
+    // Data URI `data:image/jpeg;base64,...`
+    pub lqip: String,
+}
+
+/// Hasil upload yang sudah dibersihkan dan siap ditulis ke disk.
+pub struct SanitizedImage {
+    pub data: Vec<u8>,
+    pub format: ImageFormat,
+    pub metadata: ImageMetadata,
+    // Pixel hasil decode, dipakai lagi untuk membuat rendition
+    pub image: DynamicImage,
+}
+
+/// File turunan yang disimpan di samping original: versi yang diperkecil
+/// dan/atau format lain (AVIF, WebP).
+pub struct Rendition {
+    pub variant: Variant,
+    pub format: ImageFormat,
+    pub data: Vec<u8>,
+}
+
+impl Rendition {
+    pub fn extension(&self) -> &'static str {
+        self.format.extensions_str().first().copied().unwrap_or("bin")
+    }
+}
+
+impl SanitizedImage {
+    /// Ekstensi file sesuai format hasil encode, bukan nama file dari client.
+    pub fn extension(&self) -> &'static str {
+        self.format.extensions_str().first().copied().unwrap_or("bin")
+    }
+}
+
+/// Animasi (GIF/WebP) atau video pendek (MP4/WebM). Disimpan apa adanya
+/// setelah metadata container dibuang, tanpa encode ulang.
+pub struct SanitizedMotion {
+    pub data: Vec<u8>,
+    pub container: Container,
+    pub media_type: MediaTypeEnum,
+    pub width: u32,
+    pub height: u32,
+    pub duration_ms: u64,
+    // Frame pertama; video tidak di-decode, posternya dikirim client
+    pub poster: Option<DynamicImage>,
+}
+
+pub enum SanitizedUpload {
+    Still(SanitizedImage),
+    Motion(SanitizedMotion),
+}
+
+/// Upload yang siap ditulis ke disk beserta isi kolom row `nft`.
+pub struct PreparedUpload {
+    pub data: Vec<u8>,
+    pub extension: &'static str,
+    pub media_type: MediaTypeEnum,
+    pub width: u32,
+    pub height: u32,
+    pub duration_ms: Option<u64>,
+    // None untuk video yang di-upload tanpa poster
+    pub metadata: Option<ImageMetadata>,
+    pub renditions: Vec<Rendition>,
+}
+
+impl PreparedUpload {
+    /// Total byte original + semua rendition, untuk kuota.
+    pub fn size_bytes(&self) -> i64 {
+        (self.data.len() + self.renditions.iter().map(|r| r.data.len()).sum::<usize>()) as i64
+    }
+}
+
+/// Kenali jenis upload dari isinya. GIF/WebP satu frame diproses seperti
+/// gambar diam biasa (di-decode dan di-encode ulang).
+pub fn sanitize_upload(data: &[u8]) -> Result<SanitizedUpload, AppError> {
+    match container::sniff(data) {
+        Some(kind) if kind.is_video() => sanitize_video(data, kind).map(SanitizedUpload::Motion),
+        Some(kind) if container::is_animated(data, kind) => sanitize_animation(data, kind).map(SanitizedUpload::Motion),
+        _ => sanitize_image(data).map(SanitizedUpload::Still),
+    }
+}
+
+fn sanitize_video(data: &[u8], kind: Container) -> Result<SanitizedMotion, AppError> {
+    let info = container::probe_video(data, kind)?;
+    if info.width == 0 || info.height == 0 {
+        return Err(AppError::BadRequest("Dimensi video tidak valid".to_string()));
+    }
+    debug!("Video probed: container={:?}, {:?}", kind, info);
+    Ok(SanitizedMotion {
+        data: container::strip_metadata(data, kind)?,
+        container: kind,
+        media_type: MediaTypeEnum::Video,
+        width: info.width,
+        height: info.height,
+        duration_ms: info.duration_ms,
+        poster: None,
+    })
+}
+
+// Semua frame di-decode sekali untuk validasi dan durasi; hanya frame pertama yang disimpan
+fn sanitize_animation(data: &[u8], kind: Container) -> Result<SanitizedMotion, AppError> {
+    let expected_frames = container::frame_count(data, kind)?;
+    let frames = match kind {
+        Container::Gif => limited_frames(GifDecoder::new(Cursor::new(data)).map_err(invalid_image)?, expected_frames)?,
+        _ => limited_frames(WebPDecoder::new(Cursor::new(data)).map_err(invalid_image)?, expected_frames)?,
+    };
+    let mut poster = None;
+    let mut frame_count = 0;
+    let mut duration_ms = 0u64;
+    for frame in frames {
+        // Decoder tidak boleh menghasilkan lebih banyak frame dari yang sudah dihitung
+        if frame_count >= expected_frames {
+            return Err(AppError::BadRequest("Jumlah frame animasi tidak sesuai struktur file".to_string()));
+        }
+        let frame = frame.map_err(invalid_image)?;
+        let (numer, denom) = frame.delay().numer_denom_ms();
+        let delay = numer / denom.max(1);
+        duration_ms += u64::from(if delay < MIN_FRAME_DELAY_MS { CLAMPED_FRAME_DELAY_MS } else { delay });
+        frame_count += 1;
+        if poster.is_none() {
+            poster = Some(DynamicImage::ImageRgba8(frame.into_buffer()));
+        }
+    }
+    let poster = poster.ok_or(AppError::BadRequest("Animasi tidak memiliki frame".to_string()))?;
+    debug!("Animation decoded: container={:?}, frames={}, duration={}ms", kind, frame_count, duration_ms);
+
+    let (width, height) = poster.dimensions();
+    Ok(SanitizedMotion {
+        data: container::strip_metadata(data, kind)?,
+        container: kind,
+        media_type: MediaTypeEnum::AnimatedImage,
+        width,
+        height,
+        duration_ms,
+        poster: Some(poster),
+    })
+}
+
+// Tolak animasi yang terlalu besar sebelum frame apa pun di-decode, lalu
+// batasi alokasi decoder-nya
+fn limited_frames<'a, D>(mut decoder: D, frame_count: usize) -> Result<image::Frames<'a>, AppError>
+where
+    D: ImageDecoder + AnimationDecoder<'a>,
+{
+    let (width, height) = decoder.dimensions();
+    let total_pixels = frame_count as u64 * u64::from(width) * u64::from(height);
+    debug!("Animation canvas {}x{}, {} frame(s), {} pixel(s) total", width, height, frame_count, total_pixels);
+    if frame_count > MAX_ANIMATION_FRAMES {
+        return Err(AppError::PayloadTooLarge(format!(
+            "Animasi maksimal {} frame, file ini {} frame", MAX_ANIMATION_FRAMES, frame_count
+        )));
+    }
+    if width > MAX_ANIMATION_SIDE || height > MAX_ANIMATION_SIDE || total_pixels > MAX_ANIMATION_PIXELS {
+        return Err(AppError::PayloadTooLarge(format!(
+            "Animasi terlalu besar: {}x{} dengan {} frame (maksimal sisi {} pixel dan {} juta pixel total)",
+            width, height, frame_count, MAX_ANIMATION_SIDE, MAX_ANIMATION_PIXELS / 1_000_000
+        )));
+    }
+    let mut limits = Limits::default();
+    limits.max_image_width = Some(MAX_ANIMATION_SIDE);
+    limits.max_image_height = Some(MAX_ANIMATION_SIDE);
+    limits.max_alloc = Some(MAX_ANIMATION_ALLOC);
+    decoder.set_limits(limits).map_err(invalid_image)?;
+    Ok(decoder.into_frames())
+}
+
+/// Decode gambar yang di-upload, terapkan orientasi EXIF ke pixel, lalu
+/// encode ulang tanpa metadata. Encoder `image` tidak pernah menulis
+/// EXIF/XMP, jadi GPS dan serial kamera tidak ikut tersimpan.
+pub fn sanitize_image(data: &[u8]) -> Result<SanitizedImage, AppError> {
+    let reader = ImageReader::new(Cursor::new(data))
+        .with_guessed_format()
+        .map_err(|err| AppError::BadRequest(format!("Gagal membaca file gambar: {}", err)))?;
+    let format = reader.format()
+        .ok_or(AppError::BadRequest("Format gambar tidak dikenali".to_string()))?;
+    let mut decoder = reader.into_decoder().map_err(invalid_image)?;
+
+    let orientation = decoder.orientation().map_err(invalid_image)?;
+    let taken_at = decoder.exif_metadata()
+        .ok()
+        .flatten()
+        .and_then(capture_date);
+    debug!("Image decoded: format={:?}, orientation={:?}, taken_at={:?}", format, orientation, taken_at);
+
+    let mut img = DynamicImage::from_decoder(decoder).map_err(invalid_image)?;
+    img.apply_orientation(orientation);
+
+    let metadata = describe(&img, taken_at)?;
+    let data = encode(&img, format)?;
+    Ok(SanitizedImage { data, format, metadata, image: img })
+}
+
+/// Dimensi, warna dominan, hash dan placeholder dari pixel gambar
+/// (gambar diam atau poster animasi/video).
+pub fn describe(img: &DynamicImage, taken_at: Option<NaiveDateTime>) -> Result<ImageMetadata, AppError> {
+    let (width, height) = img.dimensions();
+    Ok(ImageMetadata {
+        width,
+        height,
+        dominant_colors: dominant_colors(img),
+        taken_at,
+        phash: perceptual_hash(img),
+        blurhash: blurhash(img)?,
+        lqip: lqip(img)?,
+    })
+}
+
+/// Gambar diam: original hasil sanitize + rendition Large/Medium (dan AVIF/WebP).
+pub fn prepare_still(upload: SanitizedImage, watermark: Option<&Watermark>) -> Result<PreparedUpload, AppError> {
+    let renditions = build_renditions(&upload.image, upload.format, &upload.data, watermark)?;
+    Ok(PreparedUpload {
+        extension: upload.extension(),
+        media_type: MediaTypeEnum::Image,
+        width: upload.metadata.width,
+        height: upload.metadata.height,
+        duration_ms: None,
+        data: upload.data,
+        metadata: Some(upload.metadata),
+        renditions,
+    })
+}
+
+/// Animasi/video: file asli tanpa Large/Medium (browser memperkecil sendiri),
+/// ditambah poster JPEG kalau ada. Metadata warna/hash diambil dari poster.
+pub fn prepare_motion(motion: SanitizedMotion, poster: Option<DynamicImage>) -> Result<PreparedUpload, AppError> {
+    let poster = motion.poster.or(poster);
+    let metadata = poster.as_ref().map(|img| describe(img, None)).transpose()?;
+    let renditions = poster.as_ref().map(poster_rendition).transpose()?.into_iter().collect();
+    Ok(PreparedUpload {
+        data: motion.data,
+        extension: motion.container.extension(),
+        media_type: motion.media_type,
+        width: motion.width,
+        height: motion.height,
+        duration_ms: Some(motion.duration_ms),
+        metadata,
+        renditions,
+    })
+}
+
+/// Avatar: gambar hasil sanitize di-crop persegi dari tengah lalu
+/// diperkecil ke 512x512 (tidak diperbesar), ditambah versi AVIF/WebP.
+/// Animasi hanya diambil frame pertamanya.
+pub fn prepare_avatar(upload: SanitizedImage) -> Result<PreparedUpload, AppError> {
+    let (width, height) = upload.image.dimensions();
+    let side = width.min(height);
+    let mut square = upload.image.crop_imm((width - side) / 2, (height - side) / 2, side, side);
+    if side > AVATAR_SIZE {
+        square = square.resize_exact(AVATAR_SIZE, AVATAR_SIZE, FilterType::Lanczos3);
+    }
+    let data = encode(&square, upload.format)?;
+    let renditions = alternate_renditions(&square, upload.format, data.len())
+        .into_iter()
+        .map(|(format, data)| Rendition { variant: Variant::Original, format, data })
+        .collect();
+    Ok(PreparedUpload {
+        extension: upload.extension(),
+        media_type: MediaTypeEnum::Image,
+        width: square.width(),
+        height: square.height(),
+        duration_ms: None,
+        data,
+        metadata: None,
+        renditions,
+    })
+}
+
+fn poster_rendition(img: &DynamicImage) -> Result<Rendition, AppError> {
+    let max = Variant::Poster.max_dimension().unwrap_or(u32::MAX);
+    let data = if img.width().max(img.height()) > max {
+        encode(&img.resize(max, max, FilterType::Lanczos3), ImageFormat::Jpeg)?
+    } else {
+        encode(img, ImageFormat::Jpeg)?
+    };
+    Ok(Rendition { variant: Variant::Poster, format: ImageFormat::Jpeg, data })
+}
+
+/// Decode file original yang sudah tersimpan (sudah bersih dan orientasinya
+/// sudah diterapkan), untuk membuat ulang rendition.
+pub fn decode_stored(data: &[u8]) -> Result<(DynamicImage, ImageFormat), AppError> {
+    let reader = ImageReader::new(Cursor::new(data))
+        .with_guessed_format()
+        .map_err(|err| AppError::GeneralError(format!("Gagal membaca file original: {}", err)))?;
+    let format = reader.format()
+        .ok_or(AppError::GeneralError("Format file original tidak dikenali".to_string()))?;
+    let img = reader.decode()
+        .map_err(|err| AppError::GeneralError(format!("Gagal decode file original: {}", err)))?;
+    Ok((img, format))
+}
+
+/// Encode setiap variant non-original dalam format asli, ditambah versi
+/// AVIF/WebP untuk semua variant. Gambar yang sudah lebih kecil dari batas
+/// variant tidak diperbesar. Format modern hanya disimpan kalau hasilnya
+/// lebih kecil dari format asli di variant yang sama.
+///
+/// `watermark` hanya ditempel ke Large/Medium; Original selalu bersih.
+pub fn build_renditions(
+    img: &DynamicImage,
+    format: ImageFormat,
+    original: &[u8],
+    watermark: Option<&Watermark>
+) -> Result<Vec<Rendition>, AppError> {
+    let (width, height) = img.dimensions();
+    // Variant yang sama persis dengan original memakai hasil encode original (AVIF lambat)
+    let original_alternates = alternate_renditions(img, format, original.len());
+    let mut renditions = Vec::new();
+    // Poster hanya untuk animasi/video
+    for variant in Variant::ALL.into_iter().filter(|v| *v != Variant::Poster) {
+        let resized = match variant.max_dimension() {
+            Some(max) if width.max(height) > max => Some(img.resize(max, max, FilterType::Lanczos3)),
+            _ => None,
+        };
+        let (native, alternates) = match (resized, watermark) {
+            (None, None) => (original.to_vec(), original_alternates.clone()),
+            _ if variant == Variant::Original => (original.to_vec(), original_alternates.clone()),
+            (resized, watermark) => {
+                let mut source = resized.unwrap_or_else(|| img.clone());
+                if let Some(watermark) = watermark {
+                    source = watermark.apply(&source);
+                }
+                let data = encode(&source, format)?;
+                let alternates = alternate_renditions(&source, format, data.len());
+                (data, alternates)
+            }
+        };
+
+        renditions.extend(alternates.into_iter().map(|(alternate, data)| Rendition { variant, format: alternate, data }));
+        if variant != Variant::Original {
+            renditions.push(Rendition { variant, format, data: native });
+        }
+    }
+    Ok(renditions)
+}
+
+// Versi AVIF/WebP yang lebih kecil dari `native_len`; gagal encode tidak menggagalkan upload
+fn alternate_renditions(img: &DynamicImage, format: ImageFormat, native_len: usize) -> Vec<(ImageFormat, Vec<u8>)> {
+    let mut alternates = Vec::new();
+    for alternate in storage::ALTERNATE_FORMATS.iter().filter_map(ImageFormat::from_extension) {
+        if alternate == format {
+            continue;
+        }
+        match encode_alternate(img, alternate) {
+            Ok(data) if data.len() < native_len => alternates.push((alternate, data)),
+            Ok(data) => debug!("Skipping {:?} rendition: {} bytes >= {} bytes", alternate, data.len(), native_len),
+            Err(err) => warn!("Failed to encode {:?} rendition: {}", alternate, err),
+        }
+    }
+    alternates
+}
+
+// WebP lossy lewat libwebp, AVIF lewat encoder `image` (rav1e)
+fn encode_alternate(img: &DynamicImage, format: ImageFormat) -> Result<Vec<u8>, String> {
+    match format {
+        ImageFormat::WebP => {
+            let rgba = img.to_rgba8();
+            let encoded = webp::Encoder::from_rgba(rgba.as_raw(), rgba.width(), rgba.height())
+                .encode(WEBP_QUALITY);
+            Ok(encoded.to_vec())
+        }
+        ImageFormat::Avif => {
+            let mut out = Cursor::new(Vec::new());
+            img.write_with_encoder(AvifEncoder::new_with_speed_quality(&mut out, AVIF_SPEED, AVIF_QUALITY))
+                .map_err(|err| err.to_string())?;
+            Ok(out.into_inner())
+        }
+        other => Err(format!("Format {:?} tidak didukung", other)),
+    }
+}
+
+/// Encode gambar dengan format yang sama seperti aslinya.
+pub fn encode(img: &DynamicImage, format: ImageFormat) -> Result<Vec<u8>, AppError> {
+    let mut out = Cursor::new(Vec::new());
+    let result = match format {
+        // JPEG tidak punya alpha channel, dan kualitas default encoder (75) terlalu rendah
+        ImageFormat::Jpeg => img.to_rgb8()
+            .write_with_encoder(JpegEncoder::new_with_quality(&mut out, JPEG_QUALITY)),
+        ImageFormat::Png | ImageFormat::Gif | ImageFormat::WebP => img.write_to(&mut out, format),
+        other => {
+            return Err(AppError::BadRequest(format!("Format gambar {:?} tidak didukung", other)));
+        }
+    };
+    result.map_err(|err| AppError::GeneralError(format!("Gagal encode gambar: {}", err)))?;
+    Ok(out.into_inner())
+}
+
+fn invalid_image(err: image::ImageError) -> AppError {
+    warn!("Rejected upload, image could not be decoded: {}", err);
+    AppError::BadRequest("File bukan gambar yang valid".to_string())
+}
+
+fn capture_date(raw_exif: Vec<u8>) -> Option<NaiveDateTime> {
+    let exif = exif::Reader::new().read_raw(raw_exif).ok()?;
+    let field = exif.get_field(exif::Tag::DateTimeOriginal, exif::In::PRIMARY)
+        .or_else(|| exif.get_field(exif::Tag::DateTime, exif::In::PRIMARY))?;
+    let exif::Value::Ascii(ref values) = field.value else {
+        return None;
+    };
+    let dt = exif::DateTime::from_ascii(values.first()?).ok()?;
+    NaiveDate::from_ymd_opt(dt.year.into(), dt.month.into(), dt.day.into())?
+        .and_hms_opt(dt.hour.into(), dt.minute.into(), dt.second.into())
+}
+
+/// Warna dominan dalam format hex `#rrggbb`, diurutkan dari yang paling banyak.
+///
+/// Gambar diperkecil dulu lalu setiap pixel dikelompokkan ke bucket 4-bit
+/// per channel; warna yang dikembalikan adalah rata-rata tiap bucket.
+fn dominant_colors(img: &DynamicImage) -> Vec<String> {
+    let small = img.thumbnail(64, 64).to_rgba8();
+    let mut buckets: HashMap<u16, (u32, [u32; 3])> = HashMap::new();
+    for pixel in small.pixels() {
+        let [r, g, b, a] = pixel.0;
+        if a < 128 {
+            continue;
+        }
+        let key = ((r as u16 >> 4) << 8) | ((g as u16 >> 4) << 4) | (b as u16 >> 4);
+        let entry = buckets.entry(key).or_insert((0, [0; 3]));
+        entry.0 += 1;
+        entry.1[0] += r as u32;
+        entry.1[1] += g as u32;
+        entry.1[2] += b as u32;
+    }
+
+    let mut sorted: Vec<_> = buckets.into_iter().collect();
+    sorted.sort_by(|(key_a, a), (key_b, b)| b.0.cmp(&a.0).then(key_a.cmp(key_b)));
+    sorted.into_iter()
+        .take(DOMINANT_COLOR_COUNT)
+        .map(|(_, (count, sum))| format!(
+            "#{:02x}{:02x}{:02x}",
+            sum[0] / count,
+            sum[1] / count,
+            sum[2] / count
+        ))
+        .collect()
+}
+
+/// BlurHash dari thumbnail 32px. Jumlah komponen 4 di sisi panjang dan
+/// 3 di sisi pendek, sesuai rekomendasi blurha.sh.
+fn blurhash(img: &DynamicImage) -> Result<String, AppError> {
+    let small = img.thumbnail(32, 32).to_rgba8();
+    let (width, height) = small.dimensions();
+    let (components_x, components_y) = if width >= height { (4, 3) } else { (3, 4) };
+    blurhash::encode(components_x, components_y, width, height, small.as_raw())
+        .map_err(|err| AppError::GeneralError(format!("Gagal menghitung BlurHash: {}", err)))
+}
+
+/// Preview kecil yang langsung bisa dipakai sebagai `src` gambar
+/// (beberapa ratus byte) sebelum file aslinya selesai dimuat.
+fn lqip(img: &DynamicImage) -> Result<String, AppError> {
+    let mut out = Cursor::new(Vec::new());
+    img.thumbnail(LQIP_SIZE, LQIP_SIZE)
+        .to_rgb8()
+        .write_with_encoder(JpegEncoder::new_with_quality(&mut out, LQIP_QUALITY))
+        .map_err(|err| AppError::GeneralError(format!("Gagal membuat preview: {}", err)))?;
+    Ok(format!("data:image/jpeg;base64,{}", STANDARD.encode(out.into_inner())))
+}
+
+/// dHash 64-bit: gambar grayscale 9x8, tiap bit menandakan apakah pixel
+/// lebih terang dari tetangga kanannya. Tahan terhadap resize dan re-encode.
+pub fn perceptual_hash(img: &DynamicImage) -> u64 {
+    let small = img.resize_exact(9, 8, FilterType::Triangle).to_luma8();
+    let mut hash = 0u64;
+    for y in 0..8 {
+        for x in 0..8 {
+            let left = small.get_pixel(x, y).0[0];
+            let right = small.get_pixel(x + 1, y).0[0];
+            hash = (hash << 1) | u64::from(left > right);
+        }
+    }
+    hash
+}
+
+pub fn hamming_distance(a: u64, b: u64) -> u32 {
+    (a ^ b).count_ones()
+}
+
+/// Kelompokkan hash yang saling berdekatan (union-find, transitif).
+/// Hanya cluster dengan lebih dari satu anggota yang dikembalikan.
+pub fn cluster_by_hash<T: Copy>(items: &[(T, u64)], max_distance: u32) -> Vec<Vec<T>> {
+    fn root(parent: &mut [usize], mut i: usize) -> usize {
+        while parent[i] != i {
+            parent[i] = parent[parent[i]];
+            i = parent[i];
+        }
+        i
+    }
+
+    let mut parent: Vec<usize> = (0..items.len()).collect();
+    for i in 0..items.len() {
+        for j in (i + 1)..items.len() {
+            if hamming_distance(items[i].1, items[j].1) <= max_distance {
+                let (a, b) = (root(&mut parent, i), root(&mut parent, j));
+                parent[a] = b;
+            }
+        }
+    }
+
+    let mut clusters: HashMap<usize, Vec<T>> = HashMap::new();
+    for (i, item) in items.iter().enumerate() {
+        let r = root(&mut parent, i);
+        clusters.entry(r).or_default().push(item.0);
+    }
+    clusters.into_values().filter(|c| c.len() > 1).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{Rgb, RgbImage};
+
+    // Gradien diagonal dengan lingkaran gelap, cukup bervariasi untuk dHash
+    fn sample(width: u32, height: u32) -> DynamicImage {
+        DynamicImage::ImageRgb8(RgbImage::from_fn(width, height, |x, y| {
+            let (fx, fy) = (x as f32 / width as f32, y as f32 / height as f32);
+            if (fx - 0.35).powi(2) + (fy - 0.5).powi(2) < 0.04 {
+                Rgb([20, 20, 40])
+            } else {
+                Rgb([(fx * 255.0) as u8, (fy * 255.0) as u8, ((1.0 - fx) * 200.0) as u8])
+            }
+        }))
+    }
+
+    #[test]
+    fn hamming_distance_counts_differing_bits() {
+        assert_eq!(hamming_distance(0, 0), 0);
+        assert_eq!(hamming_distance(0b1011, 0b0001), 2);
+        assert_eq!(hamming_distance(0, u64::MAX), 64);
+    }
+
+    #[test]
+    fn perceptual_hash_survives_resize_and_reencode() {
+        let original = sample(640, 480);
+        let (reencoded, _) = decode_stored(&encode(&original.resize(200, 150, FilterType::Lanczos3), ImageFormat::Jpeg).unwrap()).unwrap();
+        let distance = hamming_distance(perceptual_hash(&original), perceptual_hash(&reencoded));
+        assert!(distance <= 4, "jarak {} terlalu jauh", distance);
+    }
+
+    #[test]
+    fn perceptual_hash_differs_for_other_images() {
+        let original = sample(640, 480);
+        let mirrored = original.fliph();
+        let distance = hamming_distance(perceptual_hash(&original), perceptual_hash(&mirrored));
+        assert!(distance > 16, "jarak {} terlalu dekat", distance);
+    }
+
+    #[test]
+    fn clusters_are_transitive_and_skip_singletons() {
+        let items = [
+            ('a', 0b0000u64),
+            ('b', 0b0001),
+            ('c', 0b0011),
+            ('d', u64::MAX),
+            ('e', u64::MAX ^ 1),
+            ('f', 0xF0F0_0000),
+        ];
+        let mut clusters: Vec<Vec<char>> = cluster_by_hash(&items, 1)
+            .into_iter()
+            .map(|mut cluster| {
+                cluster.sort();
+                cluster
+            })
+            .collect();
+        clusters.sort();
+        assert_eq!(clusters, vec![vec!['a', 'b', 'c'], vec!['d', 'e']]);
+        assert!(cluster_by_hash(&items, 0).is_empty());
+    }
+}
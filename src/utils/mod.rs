@@ -1 +1,11 @@
-pub mod jwt;
\ No newline at end of file
+pub mod client;
+pub mod container;
+pub mod cursor;
+pub mod jwt;
+pub mod markdown;
+pub mod mailer;
+pub mod media;
+pub mod signed_url;
+pub mod user_token;
+pub mod validation;
+pub mod watermark;
\ No newline at end of file
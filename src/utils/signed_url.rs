@@ -0,0 +1,141 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::env;
+use uuid::Uuid;
+
+use quoteyourlife_be::storage::Variant;
+
+type HmacSha256 = Hmac<Sha256>;
+
+// Key terpisah dari JWT_KEY, supaya bocornya satu tidak membuka yang lain
+fn secret_key() -> String {
+    env::var("MEDIA_URL_KEY").expect("Gagal membaca MEDIA_URL_KEY")
+}
+
+fn mac(message: &str) -> HmacSha256 {
+    let mut mac = HmacSha256::new_from_slice(secret_key().as_bytes())
+        .expect("HMAC menerima key dengan panjang berapa pun");
+    mac.update(message.as_bytes());
+    mac
+}
+
+fn media_message(nft_id: Uuid, variant: Variant, expires: i64) -> String {
+    format!("{}:{}:{}", nft_id, variant, expires)
+}
+
+// Prefix "export:" supaya signature media tidak bisa dipakai untuk ekspor, dan sebaliknya
+fn export_message(export_id: Uuid, expires: i64) -> String {
+    format!("export:{}:{}", export_id, expires)
+}
+
+fn verify_message(message: &str, signature: &str) -> bool {
+    let Ok(raw) = URL_SAFE_NO_PAD.decode(signature) else {
+        return false;
+    };
+    mac(message).verify_slice(&raw).is_ok()
+}
+
+/// Signature untuk satu file (item + variant) yang berlaku sampai `expires`
+/// (unix timestamp, detik).
+pub fn sign(nft_id: Uuid, variant: Variant, expires: i64) -> String {
+    URL_SAFE_NO_PAD.encode(mac(&media_message(nft_id, variant, expires)).finalize().into_bytes())
+}
+
+/// Cek signature dari query `sig`. Perbandingan constant-time lewat `verify_slice`.
+pub fn verify(nft_id: Uuid, variant: Variant, expires: i64, signature: &str) -> bool {
+    verify_message(&media_message(nft_id, variant, expires), signature)
+}
+
+/// Path media yang sudah ditandatangani, relatif terhadap root API.
+pub fn media_path(nft_id: Uuid, variant: Variant, expires: i64) -> String {
+    format!(
+        "/media/{}/{}?exp={}&sig={}",
+        nft_id,
+        variant,
+        expires,
+        sign(nft_id, variant, expires)
+    )
+}
+
+/// Path download ekspor data yang sudah ditandatangani, berlaku sampai `expires`.
+pub fn export_path(export_id: Uuid, expires: i64) -> String {
+    let signature = URL_SAFE_NO_PAD.encode(mac(&export_message(export_id, expires)).finalize().into_bytes());
+    format!("/exports/{}?exp={}&sig={}", export_id, expires, signature)
+}
+
+pub fn verify_export(export_id: Uuid, expires: i64, signature: &str) -> bool {
+    verify_message(&export_message(export_id, expires), signature)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Once;
+
+    // Semua test memakai key yang sama; diset sekali sebelum dibaca
+    fn with_test_key() {
+        static KEY: Once = Once::new();
+        KEY.call_once(|| unsafe { env::set_var("MEDIA_URL_KEY", "test-media-key") });
+    }
+
+    fn query_param<'a>(path: &'a str, name: &str) -> &'a str {
+        path.split_once('?').unwrap().1
+            .split('&')
+            .find_map(|pair| pair.strip_prefix(name)?.strip_prefix('='))
+            .unwrap()
+    }
+
+    #[test]
+    fn media_signature_roundtrip() {
+        with_test_key();
+        let item = Uuid::new_v4();
+        let signature = sign(item, Variant::Large, 1_900_000_000);
+        assert!(verify(item, Variant::Large, 1_900_000_000, &signature));
+    }
+
+    #[test]
+    fn media_signature_is_bound_to_item_variant_and_expiry() {
+        with_test_key();
+        let item = Uuid::new_v4();
+        let signature = sign(item, Variant::Large, 1_900_000_000);
+        assert!(!verify(Uuid::new_v4(), Variant::Large, 1_900_000_000, &signature));
+        assert!(!verify(item, Variant::Original, 1_900_000_000, &signature));
+        // Expiry tidak bisa diperpanjang tanpa signature baru
+        assert!(!verify(item, Variant::Large, 1_900_000_001, &signature));
+    }
+
+    #[test]
+    fn malformed_signature_is_rejected() {
+        with_test_key();
+        let item = Uuid::new_v4();
+        assert!(!verify(item, Variant::Medium, 1_900_000_000, ""));
+        assert!(!verify(item, Variant::Medium, 1_900_000_000, "bukan base64!"));
+        let mut signature = sign(item, Variant::Medium, 1_900_000_000);
+        signature.pop();
+        assert!(!verify(item, Variant::Medium, 1_900_000_000, &signature));
+    }
+
+    #[test]
+    fn media_path_carries_a_valid_signature() {
+        with_test_key();
+        let item = Uuid::new_v4();
+        let path = media_path(item, Variant::Poster, 1_900_000_000);
+        assert!(path.starts_with(&format!("/media/{}/poster?exp=1900000000&sig=", item)));
+        assert!(verify(item, Variant::Poster, 1_900_000_000, query_param(&path, "sig")));
+    }
+
+    #[test]
+    fn export_and_media_signatures_are_not_interchangeable() {
+        with_test_key();
+        let export = Uuid::new_v4();
+        let path = export_path(export, 1_900_000_000);
+        let signature = query_param(&path, "sig");
+        assert!(verify_export(export, 1_900_000_000, signature));
+        assert!(!verify_export(export, 1_900_000_001, signature));
+        for variant in Variant::ALL {
+            assert!(!verify(export, variant, 1_900_000_000, signature));
+        }
+        assert!(!verify_export(export, 1_900_000_000, &sign(export, Variant::Original, 1_900_000_000)));
+    }
+}
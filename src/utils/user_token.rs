@@ -0,0 +1,22 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+
+// 256 bit acak, cukup panjang supaya tidak bisa ditebak
+const TOKEN_BYTES: usize = 32;
+
+/// Token baru untuk dikirim ke user (base64url) beserta hash yang disimpan
+/// di database.
+pub fn generate() -> (String, String) {
+    let mut bytes = [0u8; TOKEN_BYTES];
+    rand::rng().fill_bytes(&mut bytes);
+    let token = URL_SAFE_NO_PAD.encode(bytes);
+    let token_hash = hash(&token);
+    (token, token_hash)
+}
+
+/// SHA-256 (hex) dari token. Token sudah acak penuh, jadi tidak perlu salt
+/// atau hash lambat seperti password.
+pub fn hash(token: &str) -> String {
+    format!("{:x}", Sha256::digest(token.trim().as_bytes()))
+}
@@ -0,0 +1,223 @@
+use crate::handlers::AppError;
+
+// Sesuai kolom users.username VARCHAR(50) dan users.email VARCHAR(255)
+const USERNAME_MIN_LENGTH: usize = 3;
+const USERNAME_MAX_LENGTH: usize = 50;
+const EMAIL_MAX_LENGTH: usize = 255;
+const FULL_NAME_MAX_LENGTH: usize = 100;
+// Profil publik; website dan location sesuai kolom VARCHAR-nya
+const BIO_MAX_LENGTH: usize = 500;
+const WEBSITE_MAX_LENGTH: usize = 255;
+const LOCATION_MAX_LENGTH: usize = 100;
+// bcrypt hanya memakai 72 byte pertama, sisanya diam-diam diabaikan
+const PASSWORD_MIN_LENGTH: usize = 8;
+const PASSWORD_MAX_BYTES: usize = 72;
+/// Akun yang dihapus dianonimkan jadi `deleted-<id>` (lihat handlers::user::delete_me)
+pub const DELETED_USERNAME_PREFIX: &str = "deleted-";
+
+/// Username 3-50 karakter: huruf, angka, `_`, `.` atau `-`, diawali huruf/angka.
+/// Prefix `deleted-` dicadangkan untuk akun yang sudah dihapus.
+pub fn validate_username(raw: &str) -> Result<String, AppError> {
+    let username = raw.trim();
+    let length = username.chars().count();
+    if !(USERNAME_MIN_LENGTH..=USERNAME_MAX_LENGTH).contains(&length) {
+        return Err(AppError::BadRequest(format!(
+            "Username harus {}-{} karakter", USERNAME_MIN_LENGTH, USERNAME_MAX_LENGTH
+        )));
+    }
+    if !username.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-'))
+        || !username.starts_with(|c: char| c.is_ascii_alphanumeric())
+    {
+        return Err(AppError::BadRequest(
+            "Username hanya boleh berisi huruf, angka, '_', '.' atau '-' dan diawali huruf/angka".to_string()
+        ));
+    }
+    if username.to_ascii_lowercase().starts_with(DELETED_USERNAME_PREFIX) {
+        return Err(AppError::BadRequest(format!("Username tidak boleh diawali '{}'", DELETED_USERNAME_PREFIX)));
+    }
+    Ok(username.to_string())
+}
+
+/// Cek sintaks email sederhana (`local@domain.tld`), hasilnya huruf kecil
+/// supaya constraint UNIQUE tidak bisa diakali dengan huruf besar.
+pub fn validate_email(raw: &str) -> Result<String, AppError> {
+    let email = raw.trim().to_lowercase();
+    let invalid = || AppError::BadRequest("Format email tidak valid".to_string());
+    if email.len() > EMAIL_MAX_LENGTH || email.chars().any(|c| c.is_whitespace() || c.is_control()) {
+        return Err(invalid());
+    }
+    let (local, domain) = email.split_once('@').ok_or_else(invalid)?;
+    if local.is_empty() || local.len() > 64 || local.starts_with('.') || local.ends_with('.') || local.contains("..") {
+        return Err(invalid());
+    }
+    let labels: Vec<&str> = domain.split('.').collect();
+    let valid_label = |label: &&str| !label.is_empty()
+        && !label.starts_with('-')
+        && !label.ends_with('-')
+        && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-');
+    if labels.len() < 2 || !labels.iter().all(valid_label) {
+        return Err(invalid());
+    }
+    Ok(email)
+}
+
+/// Minimal 8 karakter, maksimal 72 byte, berisi huruf dan angka, dan tidak
+/// memuat username.
+pub fn validate_password(password: &str, username: &str) -> Result<(), AppError> {
+    if password.chars().count() < PASSWORD_MIN_LENGTH {
+        return Err(AppError::BadRequest(format!("Password minimal {} karakter", PASSWORD_MIN_LENGTH)));
+    }
+    if password.len() > PASSWORD_MAX_BYTES {
+        return Err(AppError::BadRequest(format!("Password maksimal {} byte", PASSWORD_MAX_BYTES)));
+    }
+    if !password.chars().any(char::is_alphabetic) || !password.chars().any(|c| c.is_ascii_digit()) {
+        return Err(AppError::BadRequest("Password harus berisi huruf dan angka".to_string()));
+    }
+    if !username.is_empty() && password.to_lowercase().contains(&username.to_lowercase()) {
+        return Err(AppError::BadRequest("Password tidak boleh memuat username".to_string()));
+    }
+    Ok(())
+}
+
+/// Nama lengkap opsional; string kosong = tidak diisi.
+pub fn validate_full_name(raw: Option<&str>) -> Result<Option<String>, AppError> {
+    optional_text(raw, "Nama lengkap", FULL_NAME_MAX_LENGTH)
+}
+
+pub fn validate_bio(raw: &str) -> Result<Option<String>, AppError> {
+    optional_text(Some(raw), "Bio", BIO_MAX_LENGTH)
+}
+
+pub fn validate_location(raw: &str) -> Result<Option<String>, AppError> {
+    optional_text(Some(raw), "Lokasi", LOCATION_MAX_LENGTH)
+}
+
+/// Website harus URL `http://` atau `https://` dengan host, supaya link di
+/// profil tidak bisa berisi `javascript:` dan sejenisnya.
+pub fn validate_website(raw: &str) -> Result<Option<String>, AppError> {
+    let Some(website) = optional_text(Some(raw), "Website", WEBSITE_MAX_LENGTH)? else {
+        return Ok(None);
+    };
+    let invalid = || AppError::BadRequest("Website harus URL http:// atau https://".to_string());
+    let lower = website.to_ascii_lowercase();
+    let rest = lower.strip_prefix("https://")
+        .or_else(|| lower.strip_prefix("http://"))
+        .ok_or_else(invalid)?;
+    let host = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    if host.is_empty() || website.chars().any(|c| c.is_whitespace() || c.is_control()) {
+        return Err(invalid());
+    }
+    Ok(Some(website))
+}
+
+// Teks opsional yang di-trim; string kosong = tidak diisi
+fn optional_text(raw: Option<&str>, label: &str, max_length: usize) -> Result<Option<String>, AppError> {
+    let Some(text) = raw.map(str::trim).filter(|text| !text.is_empty()) else {
+        return Ok(None);
+    };
+    if text.chars().count() > max_length {
+        return Err(AppError::BadRequest(format!("{} maksimal {} karakter", label, max_length)));
+    }
+    Ok(Some(text.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rejected<T: std::fmt::Debug>(result: Result<T, AppError>) -> String {
+        match result {
+            Err(AppError::BadRequest(message)) => message,
+            other => panic!("seharusnya BadRequest, dapat {:?}", other),
+        }
+    }
+
+    #[test]
+    fn username_is_trimmed_and_length_checked() {
+        assert_eq!(validate_username("  budi_01 ").unwrap(), "budi_01");
+        assert_eq!(validate_username("abc").unwrap(), "abc");
+        assert_eq!(validate_username(&"a".repeat(50)).unwrap(), "a".repeat(50));
+        rejected(validate_username("ab"));
+        rejected(validate_username(&"a".repeat(51)));
+    }
+
+    #[test]
+    fn username_rejects_other_characters_and_leading_symbols() {
+        rejected(validate_username("budi santoso"));
+        rejected(validate_username("budi@home"));
+        rejected(validate_username("_budi"));
+        rejected(validate_username(".budi"));
+        rejected(validate_username("bùdi"));
+        assert!(validate_username("budi.s-01").is_ok());
+    }
+
+    #[test]
+    fn username_rejects_deleted_prefix_in_any_case() {
+        rejected(validate_username("deleted-0190a6a4"));
+        rejected(validate_username("Deleted-budi"));
+        assert!(validate_username("deleted_budi").is_ok());
+    }
+
+    #[test]
+    fn email_is_lowercased() {
+        assert_eq!(validate_email(" Budi.Santoso@Example.COM ").unwrap(), "budi.santoso@example.com");
+        assert!(validate_email("a@sub.example.co.id").is_ok());
+    }
+
+    #[test]
+    fn email_rejects_malformed_addresses() {
+        for email in [
+            "", "budi", "budi@", "@example.com", "budi@example", "budi@@example.com",
+            ".budi@example.com", "budi.@example.com", "bu..di@example.com",
+            "budi@-example.com", "budi@example..com", "bu di@example.com",
+        ] {
+            assert_eq!(rejected(validate_email(email)), "Format email tidak valid", "{:?}", email);
+        }
+        rejected(validate_email(&format!("{}@example.com", "a".repeat(65))));
+    }
+
+    #[test]
+    fn password_rules() {
+        assert!(validate_password("rahasia123", "budi").is_ok());
+        rejected(validate_password("abc123", "budi"));
+        rejected(validate_password("rahasiasekali", "budi"));
+        rejected(validate_password("1234567890", "budi"));
+        rejected(validate_password("Budi12345", "budi"));
+        assert!(validate_password("rahasia123", "").is_ok());
+    }
+
+    #[test]
+    fn password_is_limited_to_bcrypt_bytes() {
+        let at_limit = format!("a1{}", "b".repeat(70));
+        assert!(validate_password(&at_limit, "budi").is_ok());
+        rejected(validate_password(&format!("{}c", at_limit), "budi"));
+        // 8 karakter tapi 24 byte tetap lolos batas minimal
+        assert!(validate_password("ééééééé1", "budi").is_ok());
+    }
+
+    #[test]
+    fn optional_text_treats_blank_as_unset() {
+        assert_eq!(validate_full_name(None).unwrap(), None);
+        assert_eq!(validate_full_name(Some("   ")).unwrap(), None);
+        assert_eq!(validate_full_name(Some(" Budi Santoso ")).unwrap().as_deref(), Some("Budi Santoso"));
+        assert_eq!(validate_bio("").unwrap(), None);
+        assert_eq!(validate_location(" Bandung ").unwrap().as_deref(), Some("Bandung"));
+        assert_eq!(rejected(validate_bio(&"x".repeat(501))), "Bio maksimal 500 karakter");
+        assert!(validate_bio(&"é".repeat(500)).is_ok());
+    }
+
+    #[test]
+    fn website_must_be_http_url_with_host() {
+        assert_eq!(validate_website("").unwrap(), None);
+        assert_eq!(
+            validate_website(" https://example.com/budi ").unwrap().as_deref(),
+            Some("https://example.com/budi")
+        );
+        assert!(validate_website("HTTP://example.com").is_ok());
+        rejected(validate_website("javascript:alert(1)"));
+        rejected(validate_website("ftp://example.com"));
+        rejected(validate_website("https://"));
+        rejected(validate_website("https:///budi"));
+        rejected(validate_website("https://example.com/a b"));
+    }
+}
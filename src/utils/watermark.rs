@@ -0,0 +1,161 @@
+use std::env;
+
+use image::{imageops, DynamicImage, Rgba, RgbaImage};
+use tracing::warn;
+
+// Ukuran glyph font bitmap (pixel sebelum diperbesar)
+const GLYPH_WIDTH: u32 = 5;
+const GLYPH_HEIGHT: u32 = 7;
+// Teks watermark paling lebar setengah gambar, logo seperlima
+const MAX_TEXT_RATIO: f32 = 0.5;
+const LOGO_RATIO: f32 = 0.2;
+const TEXT_OPACITY: f32 = 0.7;
+const SHADOW_OPACITY: f32 = 0.45;
+const LOGO_OPACITY: f32 = 0.6;
+
+/// Watermark yang ditempel di pojok kanan bawah variant Large/Medium.
+pub enum Watermark {
+    Text(String),
+    Logo(RgbaImage),
+}
+
+impl Watermark {
+    /// Logo dari `WATERMARK_LOGO` (path PNG) kalau diset, selain itu `@username`.
+    pub fn for_user(username: &str) -> Watermark {
+        if let Ok(path) = env::var("WATERMARK_LOGO").map(|p| p.trim().to_string()) && !path.is_empty() {
+            match image::open(&path) {
+                Ok(logo) => return Watermark::Logo(logo.to_rgba8()),
+                Err(err) => warn!("Failed to load WATERMARK_LOGO {}: {}, falling back to text", path, err),
+            }
+        }
+        Watermark::Text(format!("@{}", username))
+    }
+
+    pub fn apply(&self, img: &DynamicImage) -> DynamicImage {
+        let mut canvas = img.to_rgba8();
+        match self {
+            Watermark::Text(text) => draw_text(&mut canvas, text),
+            Watermark::Logo(logo) => draw_logo(&mut canvas, logo),
+        }
+        DynamicImage::ImageRgba8(canvas)
+    }
+}
+
+fn draw_text(canvas: &mut RgbaImage, text: &str) {
+    let (width, height) = canvas.dimensions();
+    let chars: Vec<char> = text.chars().collect();
+    // Tiap karakter 5 kolom + 1 kolom spasi
+    let units = (chars.len() as u32 * (GLYPH_WIDTH + 1)).saturating_sub(1).max(1);
+    let scale = (width.min(height) / 120)
+        .max(1)
+        .min((width as f32 * MAX_TEXT_RATIO) as u32 / units);
+    if scale == 0 {
+        return;
+    }
+
+    let margin = 3 * scale;
+    let text_width = units * scale;
+    let text_height = GLYPH_HEIGHT * scale;
+    if text_width + margin > width || text_height + margin > height {
+        return;
+    }
+    let origin_x = width - text_width - margin;
+    let origin_y = height - text_height - margin;
+    let shadow = (scale / 2).max(1);
+
+    for (index, c) in chars.iter().enumerate() {
+        let x = origin_x + index as u32 * (GLYPH_WIDTH + 1) * scale;
+        draw_glyph(canvas, glyph(*c), x + shadow, origin_y + shadow, scale, Rgba([0, 0, 0, 255]), SHADOW_OPACITY);
+        draw_glyph(canvas, glyph(*c), x, origin_y, scale, Rgba([255, 255, 255, 255]), TEXT_OPACITY);
+    }
+}
+
+fn draw_glyph(canvas: &mut RgbaImage, rows: [u8; 7], x: u32, y: u32, scale: u32, color: Rgba<u8>, opacity: f32) {
+    for (row, bits) in rows.iter().enumerate() {
+        for col in 0..GLYPH_WIDTH {
+            if bits & (1 << (GLYPH_WIDTH - 1 - col)) == 0 {
+                continue;
+            }
+            for dy in 0..scale {
+                for dx in 0..scale {
+                    let (px, py) = (x + col * scale + dx, y + row as u32 * scale + dy);
+                    if px < canvas.width() && py < canvas.height() {
+                        blend(canvas.get_pixel_mut(px, py), color, opacity);
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn blend(pixel: &mut Rgba<u8>, color: Rgba<u8>, opacity: f32) {
+    for channel in 0..3 {
+        let base = pixel.0[channel] as f32;
+        pixel.0[channel] = (base + (color.0[channel] as f32 - base) * opacity).round() as u8;
+    }
+}
+
+fn draw_logo(canvas: &mut RgbaImage, logo: &RgbaImage) {
+    let (width, height) = canvas.dimensions();
+    let target_width = ((width as f32 * LOGO_RATIO) as u32).max(1);
+    let target_height = (logo.height() as u64 * target_width as u64 / logo.width().max(1) as u64) as u32;
+    if target_height == 0 || target_height >= height {
+        return;
+    }
+    let mut scaled = imageops::resize(logo, target_width, target_height, imageops::FilterType::Lanczos3);
+    for pixel in scaled.pixels_mut() {
+        pixel.0[3] = (pixel.0[3] as f32 * LOGO_OPACITY) as u8;
+    }
+    let margin = (width.min(height) / 40).max(1);
+    let x = width.saturating_sub(target_width + margin);
+    let y = height.saturating_sub(target_height + margin);
+    imageops::overlay(canvas, &scaled, x as i64, y as i64);
+}
+
+// Font bitmap 5x7, huruf kecil ditampilkan sebagai huruf besar
+fn glyph(c: char) -> [u8; 7] {
+    match c.to_ascii_uppercase() {
+        '0' => [0x0E, 0x11, 0x13, 0x15, 0x19, 0x11, 0x0E],
+        '1' => [0x04, 0x0C, 0x04, 0x04, 0x04, 0x04, 0x0E],
+        '2' => [0x0E, 0x11, 0x01, 0x02, 0x04, 0x08, 0x1F],
+        '3' => [0x1F, 0x02, 0x04, 0x02, 0x01, 0x11, 0x0E],
+        '4' => [0x02, 0x06, 0x0A, 0x12, 0x1F, 0x02, 0x02],
+        '5' => [0x1F, 0x10, 0x1E, 0x01, 0x01, 0x11, 0x0E],
+        '6' => [0x06, 0x08, 0x10, 0x1E, 0x11, 0x11, 0x0E],
+        '7' => [0x1F, 0x01, 0x02, 0x04, 0x08, 0x08, 0x08],
+        '8' => [0x0E, 0x11, 0x11, 0x0E, 0x11, 0x11, 0x0E],
+        '9' => [0x0E, 0x11, 0x11, 0x0F, 0x01, 0x02, 0x0C],
+        'A' => [0x0E, 0x11, 0x11, 0x11, 0x1F, 0x11, 0x11],
+        'B' => [0x1E, 0x11, 0x11, 0x1E, 0x11, 0x11, 0x1E],
+        'C' => [0x0E, 0x11, 0x10, 0x10, 0x10, 0x11, 0x0E],
+        'D' => [0x1C, 0x12, 0x11, 0x11, 0x11, 0x12, 0x1C],
+        'E' => [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x1F],
+        'F' => [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x10],
+        'G' => [0x0E, 0x11, 0x10, 0x17, 0x11, 0x11, 0x0F],
+        'H' => [0x11, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11],
+        'I' => [0x0E, 0x04, 0x04, 0x04, 0x04, 0x04, 0x0E],
+        'J' => [0x07, 0x02, 0x02, 0x02, 0x02, 0x12, 0x0C],
+        'K' => [0x11, 0x12, 0x14, 0x18, 0x14, 0x12, 0x11],
+        'L' => [0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x1F],
+        'M' => [0x11, 0x1B, 0x15, 0x15, 0x11, 0x11, 0x11],
+        'N' => [0x11, 0x11, 0x19, 0x15, 0x13, 0x11, 0x11],
+        'O' => [0x0E, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E],
+        'P' => [0x1E, 0x11, 0x11, 0x1E, 0x10, 0x10, 0x10],
+        'Q' => [0x0E, 0x11, 0x11, 0x11, 0x15, 0x12, 0x0D],
+        'R' => [0x1E, 0x11, 0x11, 0x1E, 0x14, 0x12, 0x11],
+        'S' => [0x0F, 0x10, 0x10, 0x0E, 0x01, 0x01, 0x1E],
+        'T' => [0x1F, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04],
+        'U' => [0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E],
+        'V' => [0x11, 0x11, 0x11, 0x11, 0x11, 0x0A, 0x04],
+        'W' => [0x11, 0x11, 0x11, 0x15, 0x15, 0x15, 0x0A],
+        'X' => [0x11, 0x11, 0x0A, 0x04, 0x0A, 0x11, 0x11],
+        'Y' => [0x11, 0x11, 0x11, 0x0A, 0x04, 0x04, 0x04],
+        'Z' => [0x1F, 0x01, 0x02, 0x04, 0x08, 0x10, 0x1F],
+        '-' => [0x00, 0x00, 0x00, 0x1F, 0x00, 0x00, 0x00],
+        '_' => [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x1F],
+        '.' => [0x00, 0x00, 0x00, 0x00, 0x00, 0x0C, 0x0C],
+        '@' => [0x0E, 0x11, 0x01, 0x0D, 0x15, 0x15, 0x0E],
+        ' ' => [0x00; 7],
+        _ => [0x0E, 0x11, 0x01, 0x02, 0x04, 0x00, 0x04],
+    }
+}